@@ -43,6 +43,25 @@ impl AudioBuffer {
         dropped
     }
 
+    /// Push samples, discarding the oldest ones when the buffer is full
+    ///
+    /// Keeps the buffer as a ring of the most recent `capacity` samples.
+    ///
+    /// Returns the number of old samples that were discarded
+    pub fn push_overwrite(&mut self, samples: &[f32]) -> usize {
+        let samples = &samples[samples.len().saturating_sub(self.capacity)..];
+        let discarded = (self.buffer.len() + samples.len()).saturating_sub(self.capacity);
+
+        self.buffer.drain(..discarded);
+        self.buffer.extend(samples);
+        discarded
+    }
+
+    /// Take all buffered samples, oldest first
+    pub fn take_all(&mut self) -> Vec<f32> {
+        self.buffer.drain(..).collect()
+    }
+
     /// Try to get a chunk of the specified size
     ///
     /// Returns None if not enough samples are available
@@ -180,6 +199,25 @@ mod tests {
         assert_eq!(buffer.len(), 10);
     }
 
+    #[test]
+    fn test_audio_buffer_overwrite_keeps_latest() {
+        let mut buffer = AudioBuffer::new(10, 5);
+
+        let samples: Vec<f32> = (0..8).map(|i| i as f32).collect();
+        assert_eq!(buffer.push_overwrite(&samples), 0);
+        let samples: Vec<f32> = (8..12).map(|i| i as f32).collect();
+        assert_eq!(buffer.push_overwrite(&samples), 2);
+        assert_eq!(buffer.len(), 10);
+
+        // More than the capacity in one go
+        let samples: Vec<f32> = (12..30).map(|i| i as f32).collect();
+        buffer.push_overwrite(&samples);
+
+        let expected: Vec<f32> = (20..30).map(|i| i as f32).collect();
+        assert_eq!(buffer.take_all(), expected);
+        assert!(buffer.is_empty());
+    }
+
     #[test]
     fn test_batch_processor() {
         let mut processor = BatchProcessor::new(5);
@@ -1,23 +1,180 @@
 use anyhow::{anyhow, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Device, Host, Stream, StreamConfig};
+use cpal::{
+    BufferSize, Device, FromSample, Host, Sample, SampleFormat, SizedSample, Stream, StreamConfig,
+    StreamError, SupportedBufferSize, SupportedStreamConfig, SupportedStreamConfigRange,
+};
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 use tracing::{debug, error, info};
 
+use super::downmix::{ChannelMode, Downmixer};
+
 pub type AudioPacket = Vec<f32>;
 
+/// Rate the pipeline works at, devices running at it need no resampling
+const PREFERRED_SAMPLE_RATE: u32 = 16000;
+
+/// Sample rates reported in [`DeviceInfo`]
+const COMMON_SAMPLE_RATES: [u32; 9] =
+    [8000, 11025, 16000, 22050, 24000, 32000, 44100, 48000, 96000];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceInfo {
     pub name: String,
     pub is_default: bool,
+    /// Common sample rates the device supports, in Hz
+    #[serde(default)]
+    pub sample_rates: Vec<u32>,
+    /// Supported channel counts
+    #[serde(default)]
+    pub channels: Vec<u16>,
+    /// Supported sample formats, e.g. "i16" or "f32"
+    #[serde(default)]
+    pub sample_formats: Vec<String>,
+}
+
+impl DeviceInfo {
+    fn new(name: String, is_default: bool, ranges: &[SupportedStreamConfigRange]) -> Self {
+        let mut sample_rates: Vec<u32> = COMMON_SAMPLE_RATES
+            .into_iter()
+            .filter(|&rate| {
+                ranges
+                    .iter()
+                    .any(|r| (r.min_sample_rate()..=r.max_sample_rate()).contains(&rate))
+            })
+            .collect();
+        sample_rates.dedup();
+
+        let mut channels: Vec<u16> = ranges.iter().map(|r| r.channels()).collect();
+        channels.sort_unstable();
+        channels.dedup();
+
+        let mut formats: Vec<SampleFormat> = ranges.iter().map(|r| r.sample_format()).collect();
+        formats.sort_unstable();
+        formats.dedup();
+
+        Self {
+            name,
+            is_default,
+            sample_rates,
+            channels,
+            sample_formats: formats.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+/// Human-readable name of a device
+fn device_name(device: &Device) -> std::result::Result<String, cpal::DeviceNameError> {
+    device.description().map(|desc| desc.name().to_string())
+}
+
+/// Pick the supported config that needs the least conversion
+///
+/// Prefers 16kHz, then the closest rate above it (downsampling keeps the
+/// whole speech band), then fewer channels, then formats closer to f32.
+///
+/// # Arguments
+/// * `min_channels` - Channels the device has to deliver at least
+pub fn choose_config(
+    ranges: &[SupportedStreamConfigRange],
+    min_channels: u16,
+) -> Option<SupportedStreamConfig> {
+    ranges
+        .iter()
+        .filter(|r| r.channels() >= min_channels && format_rank(r.sample_format()).is_some())
+        .map(|r| {
+            let rate = PREFERRED_SAMPLE_RATE.clamp(r.min_sample_rate(), r.max_sample_rate());
+            (r, rate)
+        })
+        .min_by_key(|(r, rate)| {
+            (
+                rate_cost(*rate),
+                r.channels(),
+                format_rank(r.sample_format()),
+            )
+        })
+        .map(|(r, rate)| r.with_sample_rate(rate))
+}
+
+/// Rates below 16kHz lose part of the speech band, so they rank after all
+/// higher ones
+fn rate_cost(rate: u32) -> u32 {
+    if rate >= PREFERRED_SAMPLE_RATE {
+        rate - PREFERRED_SAMPLE_RATE
+    } else {
+        u32::MAX / 2 + (PREFERRED_SAMPLE_RATE - rate)
+    }
+}
+
+/// Preference among the formats that can be captured, `None` for unknown ones
+fn format_rank(format: SampleFormat) -> Option<u8> {
+    match format {
+        SampleFormat::F32 => Some(0),
+        SampleFormat::I32 | SampleFormat::I24 | SampleFormat::F64 => Some(1),
+        SampleFormat::I16 | SampleFormat::U16 | SampleFormat::U24 | SampleFormat::U32 => Some(2),
+        SampleFormat::I8 | SampleFormat::U8 | SampleFormat::I64 | SampleFormat::U64 => Some(3),
+        _ => None,
+    }
+}
+
+/// Requested buffer size kept within what the device supports
+fn buffer_size(frames: Option<u32>, supported: &SupportedBufferSize) -> BufferSize {
+    match (frames, supported) {
+        (None, _) => BufferSize::Default,
+        (Some(frames), SupportedBufferSize::Range { min, max }) => {
+            BufferSize::Fixed(frames.clamp(*min, *max))
+        }
+        (Some(frames), SupportedBufferSize::Unknown) => BufferSize::Fixed(frames),
+    }
+}
+
+/// Convert device samples to f32 in [-1, 1]
+fn convert_samples<T>(data: &[T], out: &mut Vec<f32>)
+where
+    T: Sample,
+    f32: FromSample<T>,
+{
+    out.clear();
+    out.extend(data.iter().map(|s| s.to_sample::<f32>()));
+}
+
+/// Build an input stream for samples of type `T`, handing them on as f32
+fn build_stream<T, F, E>(
+    device: &Device,
+    config: &StreamConfig,
+    mut on_data: F,
+    on_error: E,
+) -> Result<Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+    F: FnMut(&[f32]) + Send + 'static,
+    E: FnMut(StreamError) + Send + 'static,
+{
+    let mut samples = Vec::new();
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            convert_samples(data, &mut samples);
+            on_data(&samples);
+        },
+        on_error,
+        None,
+    )?;
+    Ok(stream)
 }
 
 pub struct AudioCapture {
     host: Host,
     device: Option<Device>,
+    device_name: Option<String>,
     config: Option<StreamConfig>,
+    sample_format: SampleFormat,
     stream: Option<Stream>,
+    channel_mode: ChannelMode,
+    buffer_size: Option<u32>,
+    error_tx: Option<mpsc::UnboundedSender<String>>,
 }
 
 impl AudioCapture {
@@ -27,49 +184,88 @@ impl AudioCapture {
         Ok(Self {
             host,
             device: None,
+            device_name: None,
             config: None,
+            sample_format: SampleFormat::F32,
             stream: None,
+            channel_mode: ChannelMode::default(),
+            buffer_size: None,
+            error_tx: None,
         })
     }
 
+    /// Set how multichannel input is turned into mono
+    ///
+    /// Call before selecting the device, a single channel needs a config
+    /// with enough channels.
+    pub fn set_channel_mode(&mut self, mode: ChannelMode) {
+        self.channel_mode = mode;
+    }
+
+    /// Set the buffer size in frames, `None` for the device default
+    ///
+    /// Call before selecting the device, the size is clamped to what it
+    /// supports.
+    pub fn set_buffer_size(&mut self, frames: Option<u32>) {
+        self.buffer_size = frames;
+    }
+
+    /// Report errors that end the stream, e.g. the device being unplugged
+    ///
+    /// Call before [`start_stream`](Self::start_stream).
+    pub fn set_error_sender(&mut self, tx: mpsc::UnboundedSender<String>) {
+        self.error_tx = Some(tx);
+    }
+
+    /// Input devices with their names
+    fn input_devices(&self) -> Result<Vec<(String, Device)>> {
+        Ok(self
+            .host
+            .input_devices()?
+            .filter_map(|device| device_name(&device).ok().map(|name| (name, device)))
+            .collect())
+    }
+
     /// List all available input audio devices
     pub fn list_devices(&self) -> Result<Vec<DeviceInfo>> {
         let mut devices = Vec::new();
         let default_device = self.host.default_input_device();
-        let default_name = default_device
-            .as_ref()
-            .and_then(|d| d.name().ok());
-
-        for device in self.host.input_devices()? {
-            if let Ok(name) = device.name() {
-                let is_default = default_name.as_ref().map_or(false, |dn| dn == &name);
-                devices.push(DeviceInfo { name, is_default });
-            }
+        let default_name = default_device.as_ref().and_then(|d| device_name(d).ok());
+
+        for (name, device) in self.input_devices()? {
+            let is_default = default_name.as_ref() == Some(&name);
+            let ranges: Vec<_> = device
+                .supported_input_configs()
+                .map(|configs| configs.collect())
+                .unwrap_or_default();
+            devices.push(DeviceInfo::new(name, is_default, &ranges));
         }
 
         debug!("Found {} input devices", devices.len());
         Ok(devices)
     }
 
+    /// Names of the input devices, cheaper than [`list_devices`](Self::list_devices)
+    pub fn device_names(&self) -> Result<Vec<String>> {
+        Ok(self
+            .input_devices()?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+
     /// Set the audio device by name
     pub fn set_device(&mut self, device_name: &str) -> Result<()> {
-        for device in self.host.input_devices()? {
-            if let Ok(name) = device.name() {
-                if name == device_name {
-                    let config = device.default_input_config()?;
-                    info!(
-                        "Selected device: {} with config: {:?}",
-                        device_name, config
-                    );
-
-                    // Convert to StreamConfig
-                    self.config = Some(config.config());
-                    self.device = Some(device);
-                    return Ok(());
-                }
-            }
-        }
-        Err(anyhow!("Device not found: {}", device_name))
+        let device = self
+            .input_devices()?
+            .into_iter()
+            .find(|(name, _)| name == device_name)
+            .map(|(_, device)| device)
+            .ok_or_else(|| anyhow!("Device not found: {}", device_name))?;
+
+        let config = self.configure(device, device_name.to_string())?;
+        info!("Selected device: {} with config: {:?}", device_name, config);
+        Ok(())
     }
 
     /// Use the default input device
@@ -79,14 +275,47 @@ impl AudioCapture {
             .default_input_device()
             .ok_or_else(|| anyhow!("No default input device available"))?;
 
-        let name = device.name()?;
-        let config = device.default_input_config()?;
+        let name = device_name(&device)?;
+        let config = self.configure(device, name.clone())?;
 
         info!("Using default device: {} with config: {:?}", name, config);
+        Ok(())
+    }
 
-        self.config = Some(config.config());
+    /// Name of the selected device
+    pub fn device_name(&self) -> Option<&str> {
+        self.device_name.as_deref()
+    }
+
+    /// Negotiate the stream config and keep the device
+    fn configure(&mut self, device: Device, name: String) -> Result<SupportedStreamConfig> {
+        let min_channels = match self.channel_mode {
+            ChannelMode::Single(channel) => channel + 1,
+            _ => 1,
+        };
+        let ranges: Vec<_> = device
+            .supported_input_configs()
+            .map(|configs| configs.collect())
+            .unwrap_or_default();
+
+        let supported = match choose_config(&ranges, min_channels) {
+            Some(config) => config,
+            None => {
+                debug!(
+                    "No matching config in {} ranges, using the default",
+                    ranges.len()
+                );
+                device.default_input_config()?
+            }
+        };
+
+        let mut config = supported.config();
+        config.buffer_size = buffer_size(self.buffer_size, supported.buffer_size());
+        self.config = Some(config);
+        self.sample_format = supported.sample_format();
         self.device = Some(device);
-        Ok(())
+        self.device_name = Some(name);
+        Ok(supported)
     }
 
     /// Start capturing audio and send packets to the channel
@@ -104,133 +333,94 @@ impl AudioCapture {
         let channels = config.channels as usize;
 
         info!(
-            "Starting audio stream with sample rate: {} Hz, channels: {}",
-            config.sample_rate, channels
+            "Starting audio stream with sample rate: {} Hz, channels: {}, format: {}, buffer: {:?}",
+            config.sample_rate, channels, self.sample_format, config.buffer_size
         );
 
-        // Diagnostic counter for stereo-to-mono analysis
+        // Diagnostic counter for the level analysis
         use std::sync::atomic::{AtomicU64, Ordering};
         use std::sync::Arc;
         let packet_counter = Arc::new(AtomicU64::new(0));
         let packet_counter_clone = packet_counter.clone();
 
-        let stream = device.build_input_stream(
-            config,
-            move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                let packet_num = packet_counter_clone.fetch_add(1, Ordering::Relaxed);
-
-                // Convert to mono if stereo by averaging channels
-                let mono_data: Vec<f32> = if channels == 2 {
-                    // Diagnostic: analyze stereo channels every 100 packets
-                    if packet_num % 100 == 0 && !data.is_empty() {
-                        // Extract left and right channels
-                        let left: Vec<f32> = data.chunks(2).map(|chunk| chunk[0]).collect();
-                        let right: Vec<f32> = data.chunks(2).map(|chunk| chunk.get(1).copied().unwrap_or(0.0)).collect();
-
-                        // Calculate RMS for each channel
-                        let left_rms = (left.iter().map(|x| x * x).sum::<f32>() / left.len() as f32).sqrt();
-                        let right_rms = (right.iter().map(|x| x * x).sum::<f32>() / right.len() as f32).sqrt();
-
-                        // Calculate mixed RMS (theoretical)
-                        let mixed: Vec<f32> = left.iter().zip(right.iter())
-                            .map(|(l, r)| (l + r) / 2.0)
-                            .collect();
-                        let mixed_rms = (mixed.iter().map(|x| x * x).sum::<f32>() / mixed.len() as f32).sqrt();
-
-                        // Calculate correlation coefficient to detect phase cancellation
-                        let mean_left = left.iter().sum::<f32>() / left.len() as f32;
-                        let mean_right = right.iter().sum::<f32>() / right.len() as f32;
-
-                        let mut covariance = 0.0;
-                        let mut var_left = 0.0;
-                        let mut var_right = 0.0;
-
-                        for (l, r) in left.iter().zip(right.iter()) {
-                            let l_diff = l - mean_left;
-                            let r_diff = r - mean_right;
-                            covariance += l_diff * r_diff;
-                            var_left += l_diff * l_diff;
-                            var_right += r_diff * r_diff;
-                        }
-
-                        let correlation = if var_left > 0.0 && var_right > 0.0 {
-                            covariance / (var_left.sqrt() * var_right.sqrt())
-                        } else {
-                            0.0
-                        };
-
-                        // Calculate signal loss percentage
-                        let expected_rms = (left_rms + right_rms) / 2.0;
-                        let signal_loss_pct = if expected_rms > 0.0 {
-                            ((expected_rms - mixed_rms) / expected_rms * 100.0).max(0.0)
-                        } else {
-                            0.0
-                        };
-
-                        info!(
-                            "🎧 Stereo Analysis [packet #{}]:\n  \
-                             L-RMS: {:.6} | R-RMS: {:.6} | Mixed-RMS: {:.6}\n  \
-                             Correlation: {:.3} | Signal Loss: {:.1}%\n  \
-                             {}",
-                            packet_num,
-                            left_rms,
-                            right_rms,
-                            mixed_rms,
-                            correlation,
-                            signal_loss_pct,
-                            if correlation < -0.5 {
-                                "⚠️  WARNING: Negative correlation detected! Possible phase cancellation!"
-                            } else if signal_loss_pct > 30.0 {
-                                "⚠️  WARNING: High signal loss detected!"
-                            } else if correlation > 0.9 {
-                                "✅ Channels highly correlated (good)"
-                            } else {
-                                "ℹ️  Normal stereo signal"
-                            }
-                        );
-                    }
+        let mut downmixer = Downmixer::new(channels, self.channel_mode);
+        if channels > 1 {
+            info!(
+                "Channel mode: {:?}, mixing channel(s) {:?}",
+                self.channel_mode,
+                downmixer
+                    .selected_channels()
+                    .iter()
+                    .map(|c| c + 1)
+                    .collect::<Vec<_>>()
+            );
+        }
 
-                    data.chunks(2)
-                        .map(|chunk| (chunk[0] + chunk.get(1).unwrap_or(&0.0)) / 2.0)
-                        .collect()
-                } else {
-                    // Mono audio - add diagnostic output
-                    if packet_num % 100 == 0 && !data.is_empty() {
-                        let mono_rms = (data.iter().map(|x| x * x).sum::<f32>() / data.len() as f32).sqrt();
-                        let peak = data.iter().map(|x| x.abs()).fold(0.0f32, f32::max);
-
-                        info!(
-                            "🎤 Mono Audio Analysis [packet #{}]:\n  \
-                             RMS: {:.6} | Peak: {:.6}\n  \
-                             {}",
-                            packet_num,
-                            mono_rms,
-                            peak,
-                            if mono_rms < 0.001 {
-                                "⚠️  WARNING: Signal too weak! Speak louder or increase mic volume"
-                            } else if peak > 0.95 {
-                                "⚠️  WARNING: Signal clipping detected! Reduce mic volume"
-                            } else if mono_rms < 0.01 {
-                                "⚡ Low signal level (speak louder)"
-                            } else if mono_rms > 0.3 {
-                                "✅ Strong signal level (good)"
-                            } else {
-                                "✅ Normal signal level"
-                            }
-                        );
+        let on_data = move |data: &[f32]| {
+            let packet_num = packet_counter_clone.fetch_add(1, Ordering::Relaxed);
+            let mono_data = downmixer.process(data);
+
+            // Diagnostic: analyze the mono signal every 100 packets
+            if packet_num.is_multiple_of(100) && !mono_data.is_empty() {
+                let mono_rms =
+                    (mono_data.iter().map(|x| x * x).sum::<f32>() / mono_data.len() as f32).sqrt();
+                let peak = mono_data.iter().map(|x| x.abs()).fold(0.0f32, f32::max);
+
+                info!(
+                    "🎤 Mono Audio Analysis [packet #{}]:\n  \
+                     RMS: {:.6} | Peak: {:.6}\n  \
+                     {}",
+                    packet_num,
+                    mono_rms,
+                    peak,
+                    if mono_rms < 0.001 {
+                        "⚠️  WARNING: Signal too weak! Speak louder or increase mic volume"
+                    } else if peak > 0.95 {
+                        "⚠️  WARNING: Signal clipping detected! Reduce mic volume"
+                    } else if mono_rms < 0.01 {
+                        "⚡ Low signal level (speak louder)"
+                    } else if mono_rms > 0.3 {
+                        "✅ Strong signal level (good)"
+                    } else {
+                        "✅ Normal signal level"
                     }
-                    data.to_vec()
-                };
+                );
+            }
 
-                if let Err(e) = tx.try_send(mono_data) {
-                    error!("Failed to send audio packet: {}", e);
+            if let Err(e) = tx.try_send(mono_data) {
+                error!("Failed to send audio packet: {}", e);
+            }
+        };
+
+        let error_tx = self.error_tx.clone();
+        let on_error = move |err: StreamError| {
+            error!("Audio stream error: {}", err);
+            // The stream won't deliver audio anymore
+            if matches!(
+                err,
+                StreamError::DeviceNotAvailable | StreamError::StreamInvalidated
+            ) {
+                if let Some(tx) = &error_tx {
+                    let _ = tx.send(err.to_string());
                 }
-            },
-            move |err| {
-                error!("Audio stream error: {}", err);
-            },
-            None,
-        )?;
+            }
+        };
+
+        let stream = match self.sample_format {
+            SampleFormat::I8 => build_stream::<i8, _, _>(device, config, on_data, on_error),
+            SampleFormat::I16 => build_stream::<i16, _, _>(device, config, on_data, on_error),
+            SampleFormat::I24 => build_stream::<cpal::I24, _, _>(device, config, on_data, on_error),
+            SampleFormat::I32 => build_stream::<i32, _, _>(device, config, on_data, on_error),
+            SampleFormat::I64 => build_stream::<i64, _, _>(device, config, on_data, on_error),
+            SampleFormat::U8 => build_stream::<u8, _, _>(device, config, on_data, on_error),
+            SampleFormat::U16 => build_stream::<u16, _, _>(device, config, on_data, on_error),
+            SampleFormat::U24 => build_stream::<cpal::U24, _, _>(device, config, on_data, on_error),
+            SampleFormat::U32 => build_stream::<u32, _, _>(device, config, on_data, on_error),
+            SampleFormat::U64 => build_stream::<u64, _, _>(device, config, on_data, on_error),
+            SampleFormat::F32 => build_stream::<f32, _, _>(device, config, on_data, on_error),
+            SampleFormat::F64 => build_stream::<f64, _, _>(device, config, on_data, on_error),
+            format => Err(anyhow!("Unsupported sample format: {}", format)),
+        }?;
 
         stream.play()?;
         self.stream = Some(stream);
@@ -250,7 +440,7 @@ impl AudioCapture {
 
     /// Get current sample rate
     pub fn sample_rate(&self) -> Option<u32> {
-        self.config.as_ref().map(|c| c.sample_rate.into())
+        self.config.as_ref().map(|c| c.sample_rate)
     }
 }
 
@@ -265,3 +455,82 @@ impl Drop for AudioCapture {
         let _ = self.stop_stream();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(
+        channels: u16,
+        min: u32,
+        max: u32,
+        format: SampleFormat,
+    ) -> SupportedStreamConfigRange {
+        let buffer = SupportedBufferSize::Range { min: 64, max: 4096 };
+        SupportedStreamConfigRange::new(channels, min, max, buffer, format)
+    }
+
+    #[test]
+    fn test_choose_config_prefers_16khz_mono() {
+        let ranges = [
+            range(2, 44100, 48000, SampleFormat::F32),
+            range(1, 8000, 48000, SampleFormat::I16),
+            range(2, 8000, 48000, SampleFormat::F32),
+        ];
+        let config = choose_config(&ranges, 1).unwrap();
+        assert_eq!(config.sample_rate(), 16000);
+        assert_eq!(config.channels(), 1);
+        assert_eq!(config.sample_format(), SampleFormat::I16);
+
+        // A single channel needs enough of them
+        let config = choose_config(&ranges, 2).unwrap();
+        assert_eq!(config.channels(), 2);
+        assert_eq!(config.sample_format(), SampleFormat::F32);
+        assert!(choose_config(&ranges, 3).is_none());
+    }
+
+    #[test]
+    fn test_choose_config_prefers_downsampling() {
+        let ranges = [
+            range(1, 8000, 8000, SampleFormat::F32),
+            range(1, 48000, 48000, SampleFormat::I32),
+            range(1, 44100, 44100, SampleFormat::I16),
+        ];
+        assert_eq!(choose_config(&ranges, 1).unwrap().sample_rate(), 44100);
+        assert_eq!(choose_config(&ranges[..1], 1).unwrap().sample_rate(), 8000);
+    }
+
+    #[test]
+    fn test_convert_samples() {
+        let mut out = Vec::new();
+        convert_samples(&[i16::MIN, 0, 16384], &mut out);
+        assert_eq!(out, vec![-1.0, 0.0, 0.5]);
+
+        convert_samples(&[0u16, 32768], &mut out);
+        assert_eq!(out, vec![-1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_buffer_size_is_clamped() {
+        let supported = SupportedBufferSize::Range { min: 64, max: 4096 };
+        assert_eq!(buffer_size(None, &supported), BufferSize::Default);
+        assert_eq!(buffer_size(Some(512), &supported), BufferSize::Fixed(512));
+        assert_eq!(buffer_size(Some(16), &supported), BufferSize::Fixed(64));
+        assert_eq!(
+            buffer_size(Some(16), &SupportedBufferSize::Unknown),
+            BufferSize::Fixed(16)
+        );
+    }
+
+    #[test]
+    fn test_device_info_capabilities() {
+        let ranges = [
+            range(2, 44100, 48000, SampleFormat::F32),
+            range(1, 16000, 16000, SampleFormat::I16),
+        ];
+        let info = DeviceInfo::new("Mic".to_string(), true, &ranges);
+        assert_eq!(info.sample_rates, vec![16000, 44100, 48000]);
+        assert_eq!(info.channels, vec![1, 2]);
+        assert_eq!(info.sample_formats, vec!["i16", "f32"]);
+    }
+}
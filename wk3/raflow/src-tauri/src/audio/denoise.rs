@@ -0,0 +1,313 @@
+use realfft::num_complex::Complex;
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+use std::sync::Arc;
+use tracing::debug;
+
+/// Samples per analysis frame (32ms at 16kHz)
+pub const FRAME_SIZE: usize = 512;
+
+/// Frames overlap by half
+const HOP_SIZE: usize = FRAME_SIZE / 2;
+
+const BINS: usize = FRAME_SIZE / 2 + 1;
+
+/// Default suppression strength
+pub const DEFAULT_STRENGTH: f32 = 0.5;
+
+/// Smoothing of the power spectrum the noise is tracked on
+const POWER_SMOOTHING: f32 = 0.8;
+
+/// The tracked minimum sits below the mean noise power, this compensates
+const NOISE_BIAS: f32 = 2.0;
+
+/// Factor the noise estimate may rise by per frame (about 1.4dB per second),
+/// slow enough that speech barely moves it
+const NOISE_RISE: f32 = 1.005;
+
+/// How fast the noise estimate falls to a quieter background
+const NOISE_FALL: f32 = 0.7;
+
+/// Weight of the previous frame in the speech-to-noise estimate, smooths
+/// the gains over time and so avoids "musical noise"
+const DECISION_DIRECTED: f32 = 0.98;
+
+/// Strongest attenuation, reached at full strength (about -26dB)
+const MIN_GAIN: f32 = 0.05;
+
+/// RMS of the audio before and after suppression
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SuppressionStats {
+    pub input_rms: f32,
+    pub output_rms: f32,
+}
+
+impl SuppressionStats {
+    /// Level difference in dB, positive when the output is quieter
+    pub fn reduction_db(&self) -> f32 {
+        if self.output_rms <= 0.0 || self.input_rms <= 0.0 {
+            return 0.0;
+        }
+        20.0 * (self.input_rms / self.output_rms).log10()
+    }
+}
+
+/// Spectral noise suppressor for 16kHz mono audio
+///
+/// The noise spectrum is tracked per frequency bin as a slowly rising
+/// minimum of the smoothed power, so steady noise like fans is learned
+/// within a few seconds while speech hardly raises it. Each bin is then
+/// attenuated with a Wiener gain from its estimated speech-to-noise ratio.
+/// Output is delayed by half a frame.
+pub struct NoiseSuppressor {
+    forward: Arc<dyn RealToComplex<f32>>,
+    inverse: Arc<dyn ComplexToReal<f32>>,
+    window: Vec<f32>,
+    input: Vec<f32>,
+    overlap: Vec<f32>,
+    frame: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+    smoothed: Option<Vec<f32>>,
+    noise: Vec<f32>,
+    /// Speech-to-noise ratio of the previous output frame
+    prev_snr: Vec<f32>,
+    strength: f32,
+    min_gain: f32,
+    input_energy: f64,
+    output_energy: f64,
+    input_samples: u64,
+    output_samples: u64,
+}
+
+impl NoiseSuppressor {
+    /// Create a new noise suppressor
+    ///
+    /// # Arguments
+    /// * `strength` - 0.0 leaves the audio untouched, 1.0 suppresses the most
+    pub fn new(strength: f32) -> Self {
+        let strength = strength.clamp(0.0, 1.0);
+        let mut planner = RealFftPlanner::<f32>::new();
+        let forward = planner.plan_fft_forward(FRAME_SIZE);
+        let inverse = planner.plan_fft_inverse(FRAME_SIZE);
+        let spectrum = forward.make_output_vec();
+
+        // Square root of a periodic Hann window, applied before and after
+        // the FFT it adds up to one at half overlap
+        let window = (0..FRAME_SIZE)
+            .map(|i| {
+                let phase = 2.0 * std::f32::consts::PI * i as f32 / FRAME_SIZE as f32;
+                (0.5 - 0.5 * phase.cos()).sqrt()
+            })
+            .collect();
+
+        debug!("Creating noise suppressor with strength {:.2}", strength);
+
+        Self {
+            forward,
+            inverse,
+            window,
+            // Half a frame of silence in front, so the first samples are
+            // covered by two frames like all the others
+            input: vec![0.0; HOP_SIZE],
+            overlap: vec![0.0; FRAME_SIZE],
+            frame: vec![0.0; FRAME_SIZE],
+            spectrum,
+            smoothed: None,
+            noise: vec![0.0; BINS],
+            prev_snr: vec![0.0; BINS],
+            strength,
+            min_gain: 1.0 - (1.0 - MIN_GAIN) * strength,
+            input_energy: 0.0,
+            output_energy: 0.0,
+            input_samples: 0,
+            output_samples: 0,
+        }
+    }
+
+    /// Denoise the next samples
+    ///
+    /// # Returns
+    /// As many samples as complete frames allow, delayed by half a frame
+    pub fn process(&mut self, samples: &[f32]) -> Vec<f32> {
+        self.input.extend_from_slice(samples);
+
+        let mut output = Vec::with_capacity(samples.len() + HOP_SIZE);
+        while self.input.len() >= FRAME_SIZE {
+            self.process_frame();
+            output.extend_from_slice(&self.overlap[..HOP_SIZE]);
+            self.overlap.copy_within(HOP_SIZE.., 0);
+            self.overlap[FRAME_SIZE - HOP_SIZE..].fill(0.0);
+            self.input.drain(..HOP_SIZE);
+        }
+
+        self.input_energy += samples.iter().map(|s| (s * s) as f64).sum::<f64>();
+        self.input_samples += samples.len() as u64;
+        self.output_energy += output.iter().map(|s| (s * s) as f64).sum::<f64>();
+        self.output_samples += output.len() as u64;
+        output
+    }
+
+    fn process_frame(&mut self) {
+        for ((out, sample), w) in self
+            .frame
+            .iter_mut()
+            .zip(&self.input[..FRAME_SIZE])
+            .zip(&self.window)
+        {
+            *out = sample * w;
+        }
+        if self
+            .forward
+            .process(&mut self.frame, &mut self.spectrum)
+            .is_err()
+        {
+            return;
+        }
+
+        let power: Vec<f32> = self.spectrum.iter().map(|c| c.norm_sqr()).collect();
+        let first = self.smoothed.is_none();
+        let smoothed = self.smoothed.get_or_insert_with(|| power.clone());
+
+        for bin in 0..BINS {
+            let p = power[bin];
+            let s = &mut smoothed[bin];
+            *s = POWER_SMOOTHING * *s + (1.0 - POWER_SMOOTHING) * p;
+
+            let n = &mut self.noise[bin];
+            *n = if first {
+                *s
+            } else if *s < *n {
+                NOISE_FALL * *n + (1.0 - NOISE_FALL) * *s
+            } else {
+                (*n * NOISE_RISE).min(*s)
+            };
+
+            let noise = (*n * NOISE_BIAS).max(f32::MIN_POSITIVE);
+            let snr = DECISION_DIRECTED * self.prev_snr[bin]
+                + (1.0 - DECISION_DIRECTED) * (p / noise - 1.0).max(0.0);
+            let wiener = snr / (1.0 + snr);
+            let gain = (1.0 - self.strength * (1.0 - wiener)).max(self.min_gain);
+
+            self.prev_snr[bin] = gain * gain * p / noise;
+            self.spectrum[bin] *= gain;
+        }
+
+        // The DC and Nyquist bins must stay real for the inverse transform
+        self.spectrum[0].im = 0.0;
+        self.spectrum[BINS - 1].im = 0.0;
+        if self
+            .inverse
+            .process(&mut self.spectrum, &mut self.frame)
+            .is_err()
+        {
+            return;
+        }
+
+        // realfft doesn't normalize
+        let scale = 1.0 / FRAME_SIZE as f32;
+        for ((acc, sample), w) in self.overlap.iter_mut().zip(&self.frame).zip(&self.window) {
+            *acc += sample * w * scale;
+        }
+    }
+
+    /// Levels before and after suppression so far
+    pub fn stats(&self) -> SuppressionStats {
+        let rms = |energy: f64, samples: u64| {
+            if samples == 0 {
+                0.0
+            } else {
+                (energy / samples as f64).sqrt() as f32
+            }
+        };
+        SuppressionStats {
+            input_rms: rms(self.input_energy, self.input_samples),
+            output_rms: rms(self.output_energy, self.output_samples),
+        }
+    }
+}
+
+impl Default for NoiseSuppressor {
+    fn default() -> Self {
+        Self::new(DEFAULT_STRENGTH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::vad::rms;
+
+    fn noise(len: usize, level: f32, seed: &mut u32) -> Vec<f32> {
+        (0..len)
+            .map(|_| {
+                *seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let uniform = (*seed >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0;
+                uniform * level * 3f32.sqrt()
+            })
+            .collect()
+    }
+
+    fn tone(len: usize, offset: usize, amplitude: f32) -> Vec<f32> {
+        (offset..offset + len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 16000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_zero_strength_passes_audio_through() {
+        let mut suppressor = NoiseSuppressor::new(0.0);
+        let input = tone(16000, 0, 0.3);
+
+        let mut output = Vec::new();
+        for chunk in input.chunks(1600) {
+            output.extend(suppressor.process(chunk));
+        }
+
+        // Delayed by half a frame, otherwise unchanged
+        assert_eq!(output.len(), input.len() / HOP_SIZE * HOP_SIZE);
+        for (out, sample) in output[FRAME_SIZE..]
+            .iter()
+            .zip(&input[FRAME_SIZE - HOP_SIZE..])
+        {
+            assert!((out - sample).abs() < 1e-4);
+        }
+        assert!(suppressor.stats().reduction_db().abs() < 0.1);
+    }
+
+    #[test]
+    fn test_steady_noise_is_attenuated() {
+        let mut seed = 5;
+        let mut suppressor = NoiseSuppressor::new(1.0);
+
+        for _ in 0..30 {
+            suppressor.process(&noise(1600, 0.02, &mut seed));
+        }
+        let stats = suppressor.stats();
+        assert!((0.019..0.021).contains(&stats.input_rms), "{:?}", stats);
+        assert!(stats.reduction_db() > 10.0, "{:?}", stats);
+    }
+
+    #[test]
+    fn test_speech_survives_suppression() {
+        let mut seed = 9;
+        let mut suppressor = NoiseSuppressor::new(1.0);
+
+        // A tone 20dB over the learned noise keeps most of its level
+        for _ in 0..30 {
+            suppressor.process(&noise(1600, 0.02, &mut seed));
+        }
+        let background = suppressor.stats();
+        let mut output = Vec::new();
+        for i in 0..10 {
+            let chunk: Vec<f32> = tone(1600, i * 1600, 0.3)
+                .iter()
+                .zip(noise(1600, 0.02, &mut seed))
+                .map(|(t, n)| t + n)
+                .collect();
+            output.extend(suppressor.process(&chunk));
+        }
+
+        let level = rms(&output[FRAME_SIZE..]);
+        assert!(level > 0.8 * 0.3 / 2f32.sqrt(), "{}", level);
+        assert!(background.output_rms < 0.01, "{:?}", background);
+    }
+}
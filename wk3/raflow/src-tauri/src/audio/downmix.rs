@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Frames analyzed before the auto mode reconsiders its channels
+/// (about half a second at 48kHz)
+const AUTO_WINDOW_FRAMES: usize = 24000;
+
+/// Channels correlating less than this with the loudest one are left out,
+/// mixing them in would cancel part of the signal
+const MIN_CORRELATION: f32 = 0.5;
+
+/// Channels quieter than this fraction of the loudest one are left out,
+/// e.g. unused inputs of an audio interface
+const MIN_RELATIVE_RMS: f32 = 0.25;
+
+/// How the channels of an input device are turned into mono
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelMode {
+    /// Average all channels
+    #[default]
+    Mix,
+    /// Use a single channel, counted from 0
+    Single(u16),
+    /// Mix the channels carrying the signal and drop silent or
+    /// phase-inverted ones
+    Auto,
+}
+
+/// Converts interleaved multichannel audio to mono
+pub struct Downmixer {
+    channels: usize,
+    mode: ChannelMode,
+    /// Channels mixed in auto mode
+    selected: Vec<usize>,
+    /// Sums of channel products over the current window, row-major
+    products: Vec<f64>,
+    frames: usize,
+}
+
+impl Downmixer {
+    /// Create a new downmixer
+    ///
+    /// # Arguments
+    /// * `channels` - Channels of the interleaved input
+    /// * `mode` - A `Single` channel the device doesn't have falls back to `Mix`
+    pub fn new(channels: usize, mode: ChannelMode) -> Self {
+        let channels = channels.max(1);
+        let mode = match mode {
+            ChannelMode::Single(channel) if channel as usize >= channels => {
+                warn!(
+                    "Channel {} not available on a {}-channel device, mixing all channels",
+                    channel + 1,
+                    channels
+                );
+                ChannelMode::Mix
+            }
+            mode => mode,
+        };
+
+        Self {
+            channels,
+            mode,
+            selected: (0..channels).collect(),
+            products: vec![0.0; channels * channels],
+            frames: 0,
+        }
+    }
+
+    /// Channels currently mixed into the output
+    pub fn selected_channels(&self) -> Vec<usize> {
+        match self.mode {
+            ChannelMode::Mix => (0..self.channels).collect(),
+            ChannelMode::Single(channel) => vec![channel as usize],
+            ChannelMode::Auto => self.selected.clone(),
+        }
+    }
+
+    /// Downmix interleaved samples, a trailing partial frame is dropped
+    pub fn process(&mut self, data: &[f32]) -> Vec<f32> {
+        let frames = data.chunks_exact(self.channels);
+        match self.mode {
+            _ if self.channels == 1 => data.to_vec(),
+            ChannelMode::Mix => {
+                let scale = 1.0 / self.channels as f32;
+                frames
+                    .map(|frame| frame.iter().sum::<f32>() * scale)
+                    .collect()
+            }
+            ChannelMode::Single(channel) => frames.map(|frame| frame[channel as usize]).collect(),
+            ChannelMode::Auto => {
+                self.analyze(data);
+                let scale = 1.0 / self.selected.len() as f32;
+                frames
+                    .map(|frame| self.selected.iter().map(|&c| frame[c]).sum::<f32>() * scale)
+                    .collect()
+            }
+        }
+    }
+
+    fn analyze(&mut self, data: &[f32]) {
+        let n = self.channels;
+        for frame in data.chunks_exact(n) {
+            for i in 0..n {
+                for j in i..n {
+                    self.products[i * n + j] += (frame[i] * frame[j]) as f64;
+                }
+            }
+            self.frames += 1;
+
+            if self.frames >= AUTO_WINDOW_FRAMES {
+                self.select();
+                self.products.fill(0.0);
+                self.frames = 0;
+            }
+        }
+    }
+
+    /// Pick the channels for the next window from the current one
+    fn select(&mut self) {
+        let n = self.channels;
+        let energy = |c: usize| self.products[c * n + c];
+        let Some(loudest) = (0..n).max_by(|&a, &b| energy(a).total_cmp(&energy(b))) else {
+            return;
+        };
+        // Silence says nothing about the channels
+        if energy(loudest) <= 0.0 {
+            return;
+        }
+
+        let selected: Vec<usize> = (0..n)
+            .filter(|&c| {
+                if c == loudest {
+                    return true;
+                }
+                let (i, j) = (c.min(loudest), c.max(loudest));
+                let correlation = self.products[i * n + j] / (energy(c) * energy(loudest)).sqrt();
+                let relative_rms = (energy(c) / energy(loudest)).sqrt();
+                correlation as f32 >= MIN_CORRELATION && relative_rms as f32 >= MIN_RELATIVE_RMS
+            })
+            .collect();
+
+        if selected != self.selected {
+            info!(
+                "🎧 Auto channel selection: mixing channel(s) {:?} of {}",
+                selected.iter().map(|c| c + 1).collect::<Vec<_>>(),
+                n
+            );
+            self.selected = selected;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Interleave per-channel signals
+    fn interleave(channels: &[Vec<f32>]) -> Vec<f32> {
+        (0..channels[0].len())
+            .flat_map(|i| channels.iter().map(move |c| c[i]))
+            .collect()
+    }
+
+    fn tone(len: usize, amplitude: f32) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 200.0 * i as f32 / 48000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_mix_averages_any_channel_count() {
+        let data = interleave(&[
+            vec![0.4, 0.8],
+            vec![0.0, 0.0],
+            vec![0.2, -0.4],
+            vec![0.2, 0.0],
+        ]);
+        let mut downmixer = Downmixer::new(4, ChannelMode::Mix);
+
+        assert_eq!(downmixer.process(&data), vec![0.2, 0.1]);
+        assert_eq!(downmixer.process(&[1.0; 6]).len(), 1);
+    }
+
+    #[test]
+    fn test_single_channel() {
+        let data = interleave(&[vec![0.1, 0.2], vec![0.5, 0.6], vec![0.9, 1.0]]);
+
+        let mut downmixer = Downmixer::new(3, ChannelMode::Single(1));
+        assert_eq!(downmixer.process(&data), vec![0.5, 0.6]);
+        assert_eq!(downmixer.selected_channels(), vec![1]);
+
+        // Unknown channels fall back to mixing
+        let downmixer = Downmixer::new(3, ChannelMode::Single(7));
+        assert_eq!(downmixer.selected_channels(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_auto_drops_inverted_and_silent_channels() {
+        let len = AUTO_WINDOW_FRAMES;
+        let voice = tone(len, 0.3);
+        let inverted: Vec<f32> = voice.iter().map(|s| -s).collect();
+        let data = interleave(&[voice.clone(), inverted, vec![0.0; len], voice.clone()]);
+
+        // Plain mixing loses most of the signal
+        let mixed = Downmixer::new(4, ChannelMode::Mix).process(&data);
+        assert!(mixed.iter().all(|s| s.abs() <= 0.3 / 4.0 + 1e-6));
+
+        let mut downmixer = Downmixer::new(4, ChannelMode::Auto);
+        assert_eq!(downmixer.selected_channels(), vec![0, 1, 2, 3]);
+        assert_eq!(downmixer.process(&data), voice);
+        assert_eq!(downmixer.selected_channels(), vec![0, 3]);
+    }
+
+    #[test]
+    fn test_auto_keeps_selection_during_silence() {
+        let len = AUTO_WINDOW_FRAMES;
+        let voice = tone(len, 0.3);
+        let mut downmixer = Downmixer::new(2, ChannelMode::Auto);
+
+        downmixer.process(&interleave(&[vec![0.0; len], voice]));
+        assert_eq!(downmixer.selected_channels(), vec![1]);
+
+        downmixer.process(&vec![0.0; len * 2]);
+        assert_eq!(downmixer.selected_channels(), vec![1]);
+    }
+}
@@ -0,0 +1,191 @@
+use tracing::debug;
+
+use super::vad::{rms, NoiseFloor};
+
+/// Loudness speech is normalized to (about -20dBFS)
+pub const DEFAULT_TARGET_RMS: f32 = 0.1;
+
+/// Largest boost, enough for quiet laptop microphones
+const MAX_GAIN_DB: f32 = 24.0;
+
+/// Largest cut
+const MIN_GAIN_DB: f32 = -12.0;
+
+/// How fast the gain may drop when the input gets louder
+const ATTACK_DB_PER_SEC: f32 = 30.0;
+
+/// How fast the gain may rise when the input gets quieter
+const RELEASE_DB_PER_SEC: f32 = 6.0;
+
+/// The gain only adapts to chunks this far above the background, so
+/// pauses and noise are never pumped up to the target
+const GATE_SNR_DB: f32 = 15.0;
+
+/// Gain steps are spread over blocks of 10ms at 16kHz
+const BLOCK_SIZE: usize = 160;
+
+/// Samples above this are compressed by [`soft_limit`]
+pub const LIMITER_THRESHOLD: f32 = 0.8;
+
+/// Automatic gain control for 16kHz mono chunks
+///
+/// Normalizes speech toward a target RMS. The gain falls quickly when the
+/// input gets louder and rises slowly when it gets quieter, and holds
+/// between words.
+pub struct AutoGain {
+    target_db: f32,
+    gain_db: f32,
+    noise: NoiseFloor,
+}
+
+impl AutoGain {
+    /// Create a new AGC
+    ///
+    /// # Arguments
+    /// * `target_rms` - Loudness of speech after the gain
+    pub fn new(target_rms: f32) -> Self {
+        debug!("Creating AGC with target RMS {:.3}", target_rms);
+        Self {
+            target_db: to_db(target_rms),
+            gain_db: 0.0,
+            noise: NoiseFloor::new(),
+        }
+    }
+
+    /// Current gain in dB
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    /// Apply the gain to a chunk in place
+    pub fn process(&mut self, samples: &mut [f32]) {
+        let level = rms(samples);
+        let snr = self.noise.snr_db(level);
+        self.noise.update(level);
+
+        let desired = if snr >= GATE_SNR_DB {
+            (self.target_db - to_db(level)).clamp(MIN_GAIN_DB, MAX_GAIN_DB)
+        } else {
+            self.gain_db
+        };
+
+        for block in samples.chunks_mut(BLOCK_SIZE) {
+            let seconds = block.len() as f32 / 16000.0;
+            let step = if desired < self.gain_db {
+                (desired - self.gain_db).max(-ATTACK_DB_PER_SEC * seconds)
+            } else {
+                (desired - self.gain_db).min(RELEASE_DB_PER_SEC * seconds)
+            };
+
+            // Ramp across the block so gain changes don't click
+            let from = from_db(self.gain_db);
+            self.gain_db += step;
+            let to = from_db(self.gain_db);
+            let len = block.len() as f32;
+            for (i, sample) in block.iter_mut().enumerate() {
+                *sample *= from + (to - from) * (i + 1) as f32 / len;
+            }
+        }
+    }
+}
+
+impl Default for AutoGain {
+    fn default() -> Self {
+        Self::new(DEFAULT_TARGET_RMS)
+    }
+}
+
+/// Compress peaks above [`LIMITER_THRESHOLD`] so the output never clips
+///
+/// Samples below the threshold are untouched, louder ones approach 1.0
+/// smoothly instead of being cut off.
+pub fn soft_limit(samples: &mut [f32]) {
+    let headroom = 1.0 - LIMITER_THRESHOLD;
+    for sample in samples.iter_mut() {
+        let magnitude = sample.abs();
+        if magnitude > LIMITER_THRESHOLD {
+            let over = (magnitude - LIMITER_THRESHOLD) / headroom;
+            *sample = sample.signum() * (LIMITER_THRESHOLD + headroom * over.tanh());
+        }
+    }
+}
+
+fn to_db(rms: f32) -> f32 {
+    20.0 * rms.max(1e-6).log10()
+}
+
+fn from_db(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(len: usize, amplitude: f32) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 200.0 * i as f32 / 16000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_quiet_speech_is_raised_to_target() {
+        let mut agc = AutoGain::default();
+        for _ in 0..10 {
+            agc.process(&mut vec![0.0005; 1600]);
+        }
+
+        // Quiet speech, 20dB under the target
+        let mut last = Vec::new();
+        for _ in 0..50 {
+            last = tone(1600, 0.01 * 2f32.sqrt());
+            agc.process(&mut last);
+        }
+        assert!(
+            (rms(&last) - DEFAULT_TARGET_RMS).abs() < 0.01,
+            "{}",
+            rms(&last)
+        );
+        assert!((agc.gain_db() - 20.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_gain_rises_slowly_and_falls_fast() {
+        let mut agc = AutoGain::default();
+        agc.process(&mut vec![0.0005; 1600]);
+
+        // Rising is limited to the release rate
+        agc.process(&mut tone(1600, 0.02));
+        assert!((agc.gain_db() - RELEASE_DB_PER_SEC * 0.1).abs() < 1e-3);
+
+        for _ in 0..50 {
+            agc.process(&mut tone(1600, 0.02));
+        }
+        let boosted = agc.gain_db();
+
+        // A loud chunk brings it down at the attack rate
+        agc.process(&mut tone(1600, 0.5));
+        assert!((boosted - agc.gain_db() - ATTACK_DB_PER_SEC * 0.1).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_background_noise_is_not_boosted() {
+        let mut agc = AutoGain::default();
+        for _ in 0..50 {
+            agc.process(&mut tone(1600, 0.002));
+        }
+        assert_eq!(agc.gain_db(), 0.0);
+    }
+
+    #[test]
+    fn test_soft_limit() {
+        let mut samples = vec![0.5, -0.79, 0.9, -1.5, 4.0];
+        soft_limit(&mut samples);
+
+        assert_eq!(samples[0], 0.5);
+        assert_eq!(samples[1], -0.79);
+        assert!(samples[2] > 0.8 && samples[2] < 0.9);
+        assert!(samples[3] < -0.8 && samples[3] > -1.0);
+        assert!(samples[4] <= 1.0 && samples[4] > samples[2]);
+    }
+}
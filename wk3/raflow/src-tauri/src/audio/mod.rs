@@ -1,12 +1,22 @@
 pub mod buffer;
 pub mod capture;
+pub mod denoise;
+pub mod downmix;
+pub mod gain;
+pub mod monitor;
 pub mod resample;
+pub mod silero;
 pub mod vad;
 
 #[cfg(test)]
 mod tests;
 
-pub use buffer::AudioBuffer;
+pub use buffer::{AudioBuffer, BatchProcessor};
 pub use capture::{AudioCapture, AudioPacket, DeviceInfo};
+pub use denoise::{NoiseSuppressor, SuppressionStats};
+pub use downmix::ChannelMode;
+pub use gain::AutoGain;
+pub use monitor::DeviceMonitor;
 pub use resample::AudioResampler;
-pub use vad::VoiceActivityDetector;
+pub use silero::SileroVad;
+pub use vad::{AdaptiveVad, NoiseFloor, Vad, VadEngine, VoiceActivityDetector};
@@ -0,0 +1,119 @@
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use super::capture::{AudioCapture, DeviceInfo};
+
+/// How often the device list is checked, cpal has no change notifications
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Devices added and removed between two device lists
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl DeviceChanges {
+    pub fn between(old: &[String], new: &[String]) -> Self {
+        Self {
+            added: new.iter().filter(|n| !old.contains(n)).cloned().collect(),
+            removed: old.iter().filter(|n| !new.contains(n)).cloned().collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Watches the input devices on a background thread
+///
+/// Polling stops when the monitor is dropped.
+pub struct DeviceMonitor {
+    stop: Arc<AtomicBool>,
+}
+
+impl DeviceMonitor {
+    /// Start watching
+    ///
+    /// # Arguments
+    /// * `on_change` - Called with all devices whenever one was plugged in or removed
+    pub fn start<F>(mut on_change: F) -> Result<Self>
+    where
+        F: FnMut(Vec<DeviceInfo>) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+
+        std::thread::Builder::new()
+            .name("device-monitor".to_string())
+            .spawn(move || {
+                let capture = match AudioCapture::new() {
+                    Ok(capture) => capture,
+                    Err(e) => {
+                        warn!("Device monitor not started: {}", e);
+                        return;
+                    }
+                };
+                let mut known = capture.device_names().unwrap_or_default();
+
+                while !stopped.load(Ordering::Relaxed) {
+                    std::thread::sleep(POLL_INTERVAL);
+
+                    let names = match capture.device_names() {
+                        Ok(names) => names,
+                        Err(e) => {
+                            debug!("Failed to poll input devices: {}", e);
+                            continue;
+                        }
+                    };
+                    let changes = DeviceChanges::between(&known, &names);
+                    if changes.is_empty() {
+                        continue;
+                    }
+
+                    info!(
+                        "🎙️ Input devices changed, added: {:?}, removed: {:?}",
+                        changes.added, changes.removed
+                    );
+                    known = names;
+                    match capture.list_devices() {
+                        Ok(devices) => on_change(devices),
+                        Err(e) => warn!("Failed to list input devices: {}", e),
+                    }
+                }
+            })?;
+
+        Ok(Self { stop })
+    }
+}
+
+impl Drop for DeviceMonitor {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_device_changes() {
+        let old = names(&["Built-in", "USB Mic"]);
+
+        let changes = DeviceChanges::between(&old, &names(&["Built-in", "Headset"]));
+        assert_eq!(changes.added, names(&["Headset"]));
+        assert_eq!(changes.removed, names(&["USB Mic"]));
+
+        // Order doesn't matter
+        assert!(DeviceChanges::between(&old, &names(&["USB Mic", "Built-in"])).is_empty());
+    }
+}
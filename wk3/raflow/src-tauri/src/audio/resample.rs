@@ -1,6 +1,6 @@
 use anyhow::Result;
 use rubato::{
-    SincInterpolationParameters, SincInterpolationType, Resampler, SincFixedIn, WindowFunction,
+    Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
 };
 use tracing::debug;
 
@@ -78,8 +78,7 @@ impl AudioResampler {
 
     /// Get the expected output size for a given input size
     pub fn output_size(&self, input_size: usize) -> usize {
-        ((input_size as f64) * self.resampler.output_frames_max() as f64
-            / self.chunk_size as f64)
+        ((input_size as f64) * self.resampler.output_frames_max() as f64 / self.chunk_size as f64)
             .ceil() as usize
     }
 }
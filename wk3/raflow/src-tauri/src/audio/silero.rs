@@ -0,0 +1,209 @@
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+use tract_onnx::prelude::*;
+
+use super::vad::{SpeechGate, Vad};
+use crate::utils::download_file;
+
+/// Location of the model inside the app data directory
+pub const SILERO_MODEL_FILE: &str = "models/silero_vad.onnx";
+
+/// Silero VAD v5 release the model is downloaded from
+const SILERO_MODEL_URL: &str =
+    "https://github.com/snakers4/silero-vad/raw/v5.1.2/src/silero_vad/data/silero_vad.onnx";
+
+/// Samples per model window at 16kHz
+pub const WINDOW_SIZE: usize = 512;
+
+/// Samples of the previous window the model sees in front of each window
+const CONTEXT_SIZE: usize = 64;
+
+/// Size of the recurrent state carried between windows
+const STATE_SHAPE: [usize; 3] = [2, 1, 128];
+
+/// Speech probability above which a chunk counts as speech
+pub const DEFAULT_SPEECH_THRESHOLD: f32 = 0.5;
+
+/// Below `threshold - margin` a chunk counts as silence; in between the
+/// current state is kept, which stops the detector from flapping
+const THRESHOLD_MARGIN: f32 = 0.15;
+
+type Plan = TypedRunnableModel<TypedModel>;
+
+/// Neural voice activity detector running Silero VAD on the CPU
+///
+/// The model classifies 32ms windows, so every chunk is split into windows
+/// and classified by its most speech-like window. Leftover samples carry
+/// over to the next chunk.
+pub struct SileroVad {
+    plan: Plan,
+    state: Tensor,
+    sample_rate: Tensor,
+    context: Vec<f32>,
+    pending: Vec<f32>,
+    threshold: f32,
+    probability: f32,
+    gate: SpeechGate,
+}
+
+impl SileroVad {
+    /// Load the ONNX model
+    ///
+    /// # Arguments
+    /// * `path` - Silero VAD v5 model, see [`ensure_model`](Self::ensure_model)
+    pub fn load(path: &Path) -> Result<Self> {
+        let plan = tract_onnx::onnx()
+            .model_for_path(path)
+            .map_err(|e| anyhow!("Failed to read VAD model {}: {}", path.display(), e))?
+            .with_input_fact(0, f32::fact([1, CONTEXT_SIZE + WINDOW_SIZE]).into())?
+            .with_input_fact(1, f32::fact(STATE_SHAPE).into())?
+            .with_input_fact(2, i64::scalar_fact().into())?
+            .into_optimized()?
+            .into_runnable()?;
+
+        info!("✅ Silero VAD loaded from {}", path.display());
+        Ok(Self {
+            plan,
+            state: Tensor::zero::<f32>(&STATE_SHAPE)?,
+            sample_rate: tensor0(16000i64),
+            context: vec![0.0; CONTEXT_SIZE],
+            pending: Vec::with_capacity(WINDOW_SIZE * 4),
+            threshold: DEFAULT_SPEECH_THRESHOLD,
+            probability: 0.0,
+            // 2 chunks (~200ms) to start, 10 chunks (~1s) of silence to end
+            gate: SpeechGate::new(2, 10),
+        })
+    }
+
+    /// Set the speech probability threshold
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Where the model is kept
+    pub fn model_path(data_dir: &Path) -> PathBuf {
+        data_dir.join(SILERO_MODEL_FILE)
+    }
+
+    /// Download the model unless a usable copy is already at `path`
+    ///
+    /// A stored copy that fails to load is replaced, and a download that
+    /// doesn't load is removed again.
+    pub async fn ensure_model(path: &Path) -> Result<()> {
+        if path.is_file() {
+            if Self::load(path).is_ok() {
+                return Ok(());
+            }
+            warn!("Silero VAD model is unreadable, downloading it again");
+            tokio::fs::remove_file(path).await?;
+        }
+
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        info!("⬇️ Downloading Silero VAD model");
+        download_file(SILERO_MODEL_URL, path, |_, _| {}).await?;
+
+        if let Err(e) = Self::load(path) {
+            let _ = tokio::fs::remove_file(path).await;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Speech probability of one window
+    fn infer(&mut self, window: &[f32]) -> Result<f32> {
+        let mut input = Vec::with_capacity(CONTEXT_SIZE + WINDOW_SIZE);
+        input.extend_from_slice(&self.context);
+        input.extend_from_slice(window);
+        self.context
+            .copy_from_slice(&window[WINDOW_SIZE - CONTEXT_SIZE..]);
+
+        let input = tract_ndarray::Array2::from_shape_vec((1, input.len()), input)?.into_tensor();
+        let outputs = self.plan.run(tvec!(
+            input.into(),
+            self.state.clone().into(),
+            self.sample_rate.clone().into()
+        ))?;
+
+        self.state = outputs[1].clone().into_tensor();
+        let probability = outputs[0]
+            .to_array_view::<f32>()?
+            .iter()
+            .next()
+            .copied()
+            .unwrap_or(0.0);
+        Ok(probability)
+    }
+}
+
+impl Vad for SileroVad {
+    fn is_speech(&mut self, audio: &[f32]) -> bool {
+        self.pending.extend_from_slice(audio);
+
+        let mut best: Option<f32> = None;
+        while self.pending.len() >= WINDOW_SIZE {
+            let window: Vec<f32> = self.pending.drain(..WINDOW_SIZE).collect();
+            match self.infer(&window) {
+                Ok(p) => best = Some(best.map_or(p, |b| b.max(p))),
+                Err(e) => debug!("Silero VAD inference failed: {}", e),
+            }
+        }
+        // Chunks shorter than a window keep the last probability
+        if let Some(probability) = best {
+            self.probability = probability;
+        }
+
+        let speech = if self.probability >= self.threshold {
+            true
+        } else if self.probability < self.threshold - THRESHOLD_MARGIN {
+            false
+        } else {
+            self.gate.is_speaking()
+        };
+        self.gate.update(speech)
+    }
+
+    fn speech_probability(&self) -> f32 {
+        self.probability
+    }
+
+    fn is_currently_speaking(&self) -> bool {
+        self.gate.is_speaking()
+    }
+
+    fn reset(&mut self) {
+        self.state = Tensor::zero::<f32>(&STATE_SHAPE).unwrap_or_else(|_| self.state.clone());
+        self.context.fill(0.0);
+        self.pending.clear();
+        self.probability = 0.0;
+        self.gate.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_missing_model_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = SileroVad::model_path(dir.path());
+        assert!(path.ends_with("models/silero_vad.onnx"));
+
+        let err = SileroVad::load(&path).err().unwrap();
+        assert!(err.to_string().contains("Failed to read VAD model"));
+    }
+
+    #[test]
+    fn test_load_corrupt_model_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("silero_vad.onnx");
+        std::fs::write(&path, b"not an onnx model").unwrap();
+
+        let err = SileroVad::load(&path).err().unwrap();
+        assert!(err.to_string().contains("Failed to read VAD model"));
+    }
+}
@@ -1,9 +1,5 @@
-use super::*;
-use crate::utils::RAFlowError;
-
 #[cfg(test)]
 mod resample_tests {
-    use super::*;
     use crate::audio::resample::AudioResampler;
 
     #[test]
@@ -20,20 +16,22 @@ mod resample_tests {
         let input_rate = 48000;
         let chunk_size = 4800;
         let freq = 440.0;
-        let mut input: Vec<f32> = (0..chunk_size)
+        let input: Vec<f32> = (0..chunk_size)
             .map(|i| {
                 let t = i as f32 / input_rate as f32;
                 (2.0 * std::f32::consts::PI * freq * t).sin()
             })
             .collect();
 
+        // The first chunk comes out short by the filter delay
+        resampler.process(&input).unwrap();
         let output = resampler.process(&input).unwrap();
 
         // Output should be ~1/3 the size (48kHz -> 16kHz)
         let expected_size = chunk_size / 3;
         let tolerance = 10; // Allow some variance
         assert!(
-            (output.len() as i32 - expected_size as i32).abs() < tolerance,
+            (output.len() as i32 - expected_size).abs() < tolerance,
             "Output size {} not close to expected {}",
             output.len(),
             expected_size
@@ -53,8 +51,7 @@ mod resample_tests {
         let input_energy: f32 = input.iter().map(|x| x * x).sum::<f32>() / input.len() as f32;
 
         let output = resampler.process(&input).unwrap();
-        let output_energy: f32 =
-            output.iter().map(|x| x * x).sum::<f32>() / output.len() as f32;
+        let output_energy: f32 = output.iter().map(|x| x * x).sum::<f32>() / output.len() as f32;
 
         // Energy should be preserved within 20% tolerance
         let ratio = output_energy / input_energy;
@@ -68,7 +65,6 @@ mod resample_tests {
 
 #[cfg(test)]
 mod vad_tests {
-    use super::*;
     use crate::audio::vad::VoiceActivityDetector;
 
     #[test]
@@ -109,8 +105,8 @@ mod vad_tests {
 
         // Test different signal levels
         let silence: Vec<f32> = vec![0.0; 1600];
-        let weak_signal: Vec<f32> = vec![0.1; 1600];
-        let strong_signal: Vec<f32> = vec![0.5; 1600];
+        let weak_signal: Vec<f32> = vec![0.01; 1600];
+        let strong_signal: Vec<f32> = vec![0.05; 1600];
 
         let silence_level = vad.get_audio_level(&silence);
         let weak_level = vad.get_audio_level(&weak_signal);
@@ -151,14 +147,13 @@ mod vad_tests {
 
 #[cfg(test)]
 mod buffer_tests {
-    use super::*;
     use crate::audio::buffer::AudioBuffer;
 
     #[test]
     fn test_buffer_creation() {
-        let buffer = AudioBuffer::new(16000, 1600);
+        let mut buffer = AudioBuffer::new(16000, 1600);
         assert_eq!(buffer.len(), 0);
-        assert_eq!(buffer.capacity(), 16000);
+        assert_eq!(buffer.push(&vec![0.0; 16001]), 1);
     }
 
     #[test]
@@ -167,7 +162,7 @@ mod buffer_tests {
 
         // Push samples
         let samples: Vec<f32> = (0..1600).map(|i| i as f32).collect();
-        buffer.push_samples(&samples);
+        buffer.push(&samples);
 
         assert_eq!(buffer.len(), 1600);
 
@@ -184,7 +179,7 @@ mod buffer_tests {
 
         // Push less than chunk size
         let samples: Vec<f32> = (0..800).map(|i| i as f32).collect();
-        buffer.push_samples(&samples);
+        buffer.push(&samples);
 
         // Should not pop chunk yet
         assert!(buffer.pop_chunk().is_none());
@@ -198,7 +193,7 @@ mod buffer_tests {
         // Try to push more than capacity
         for _ in 0..20 {
             let samples: Vec<f32> = (0..100).map(|i| i as f32).collect();
-            buffer.push_samples(&samples);
+            buffer.push(&samples);
         }
 
         // Buffer should not exceed capacity
@@ -210,7 +205,7 @@ mod buffer_tests {
         let mut buffer = AudioBuffer::new(16000, 1600);
 
         let samples: Vec<f32> = (0..3200).map(|i| i as f32).collect();
-        buffer.push_samples(&samples);
+        buffer.push(&samples);
 
         assert_eq!(buffer.len(), 3200);
 
@@ -224,16 +219,12 @@ mod buffer_tests {
 
         // Push 3 chunks worth of data
         let samples: Vec<f32> = (0..4800).map(|i| i as f32).collect();
-        buffer.push_samples(&samples);
+        buffer.push(&samples);
 
         // Should be able to pop 3 chunks
         for i in 0..3 {
             let chunk = buffer.pop_chunk();
-            assert!(
-                chunk.is_some(),
-                "Should be able to pop chunk {}",
-                i + 1
-            );
+            assert!(chunk.is_some(), "Should be able to pop chunk {}", i + 1);
         }
 
         // No more complete chunks
@@ -243,7 +234,6 @@ mod buffer_tests {
 
 #[cfg(test)]
 mod integration_tests {
-    use super::*;
     use crate::audio::{AudioBuffer, AudioResampler, VoiceActivityDetector};
 
     #[test]
@@ -256,16 +246,20 @@ mod integration_tests {
         // Generate test audio (48kHz)
         let input: Vec<f32> = (0..4800).map(|i| (i as f32 / 100.0).sin() * 0.5).collect();
 
-        // Resample to 16kHz
-        let resampled = resampler.process(&input).unwrap();
-        assert!(!resampled.is_empty(), "Resampled audio should not be empty");
+        // Speech takes a few frames to confirm
+        let mut is_speech = false;
+        for _ in 0..3 {
+            // Resample to 16kHz
+            let resampled = resampler.process(&input).unwrap();
+            assert!(!resampled.is_empty(), "Resampled audio should not be empty");
 
-        // Check if it's speech
-        let is_speech = vad.is_speech(&resampled);
-        assert!(is_speech, "Should detect speech in signal");
+            // Check if it's speech
+            is_speech = vad.is_speech(&resampled);
 
-        // Buffer the audio
-        buffer.push_samples(&resampled);
+            // Buffer the audio
+            buffer.push(&resampled);
+        }
+        assert!(is_speech, "Should detect speech in signal");
 
         // Pop chunk
         let chunk = buffer.pop_chunk();
@@ -1,8 +1,50 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use tracing::debug;
 
-/// Voice Activity Detector using simple energy-based approach
-pub struct VoiceActivityDetector {
-    energy_threshold: f32,
+/// Default energy threshold
+///
+/// Increased from 0.02 to 0.03 to reduce false positives from background noise.
+pub const DEFAULT_ENERGY_THRESHOLD: f32 = 0.03;
+
+/// Voice activity detection on 16kHz mono chunks
+pub trait Vad: Send {
+    /// Check if the audio chunk contains speech
+    ///
+    /// Detectors smooth their per-chunk decisions, so the result is the
+    /// speaking state after this chunk rather than the raw classification.
+    fn is_speech(&mut self, audio: &[f32]) -> bool;
+
+    /// Likelihood that the last chunk contained speech (0.0 - 1.0)
+    fn speech_probability(&self) -> f32;
+
+    /// Get current speaking state
+    fn is_currently_speaking(&self) -> bool;
+
+    /// Reset the detector state
+    fn reset(&mut self);
+}
+
+/// Available voice activity detectors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VadEngine {
+    /// RMS energy threshold, see [`VoiceActivityDetector`]
+    #[default]
+    Energy,
+    /// Silero neural network, see [`super::SileroVad`]
+    Silero,
+    /// Energy threshold following the background noise, see [`AdaptiveVad`]
+    Adaptive,
+}
+
+/// Hangover smoothing shared by the detectors
+///
+/// Speaking starts after `min_speech_frames` consecutive speech frames and
+/// ends after `min_silence_frames` consecutive silent ones, so short clicks
+/// don't start a segment and short pauses don't end one.
+#[derive(Debug, Clone)]
+pub struct SpeechGate {
     speech_frames: usize,
     silence_frames: usize,
     min_speech_frames: usize,
@@ -10,40 +52,29 @@ pub struct VoiceActivityDetector {
     is_speaking: bool,
 }
 
-impl VoiceActivityDetector {
-    /// Create a new VAD with specified threshold
-    ///
-    /// # Arguments
-    /// * `threshold` - Energy threshold for speech detection (typically 0.01 - 0.1)
-    pub fn new(threshold: f32) -> Self {
+impl SpeechGate {
+    pub fn new(min_speech_frames: usize, min_silence_frames: usize) -> Self {
         Self {
-            energy_threshold: threshold,
             speech_frames: 0,
             silence_frames: 0,
-            min_speech_frames: 3,   // ~30ms at 100ms frames - faster response
-            min_silence_frames: 15, // ~150ms at 100ms frames - reduced from 300ms to avoid cutting sentences
+            min_speech_frames,
+            min_silence_frames,
             is_speaking: false,
         }
     }
 
-    /// Check if the audio frame contains speech
-    ///
-    /// # Arguments
-    /// * `audio` - Audio samples to analyze
+    /// Feed the classification of one frame
     ///
     /// # Returns
-    /// `true` if speech is detected, `false` otherwise
-    pub fn is_speech(&mut self, audio: &[f32]) -> bool {
-        let rms = self.calculate_rms(audio);
-
-        if rms > self.energy_threshold {
+    /// The speaking state after this frame
+    pub fn update(&mut self, speech: bool) -> bool {
+        if speech {
             self.speech_frames += 1;
             self.silence_frames = 0;
 
             // Start speaking if we have enough speech frames
             if !self.is_speaking && self.speech_frames >= self.min_speech_frames {
                 self.is_speaking = true;
-                debug!("Speech started (RMS: {:.4})", rms);
             }
         } else {
             self.silence_frames += 1;
@@ -52,32 +83,89 @@ impl VoiceActivityDetector {
             // Stop speaking if we have enough silence frames
             if self.is_speaking && self.silence_frames >= self.min_silence_frames {
                 self.is_speaking = false;
-                debug!("Speech ended");
             }
         }
 
         self.is_speaking
     }
 
-    /// Calculate RMS (Root Mean Square) energy of audio
-    fn calculate_rms(&self, audio: &[f32]) -> f32 {
-        if audio.is_empty() {
-            return 0.0;
+    pub fn is_speaking(&self) -> bool {
+        self.is_speaking
+    }
+
+    pub fn reset(&mut self) {
+        self.speech_frames = 0;
+        self.silence_frames = 0;
+        self.is_speaking = false;
+    }
+}
+
+/// Calculate RMS (Root Mean Square) energy of audio
+pub fn rms(audio: &[f32]) -> f32 {
+    if audio.is_empty() {
+        return 0.0;
+    }
+    let sum: f32 = audio.iter().map(|x| x * x).sum();
+    (sum / audio.len() as f32).sqrt()
+}
+
+/// Audio level for meters (0.0 - 1.0)
+pub fn audio_level(audio: &[f32]) -> f32 {
+    (rms(audio) * 10.0).min(1.0) // Scale and clamp to 0-1
+}
+
+/// Voice Activity Detector using simple energy-based approach
+pub struct VoiceActivityDetector {
+    energy_threshold: f32,
+    gate: SpeechGate,
+    last_rms: f32,
+}
+
+impl VoiceActivityDetector {
+    /// Create a new VAD with specified threshold
+    ///
+    /// # Arguments
+    /// * `threshold` - Energy threshold for speech detection (typically 0.01 - 0.1)
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            energy_threshold: threshold,
+            // 3 frames (~300ms at 100ms chunks) to start for a fast response,
+            // 15 frames (~1.5s) of silence to end so sentences aren't cut
+            gate: SpeechGate::new(3, 15),
+            last_rms: 0.0,
+        }
+    }
+
+    /// Check if the audio frame contains speech
+    ///
+    /// # Arguments
+    /// * `audio` - Audio samples to analyze
+    ///
+    /// # Returns
+    /// `true` if speech is detected, `false` otherwise
+    pub fn is_speech(&mut self, audio: &[f32]) -> bool {
+        let rms = rms(audio);
+        self.last_rms = rms;
+
+        let was_speaking = self.gate.is_speaking();
+        let is_speaking = self.gate.update(rms > self.energy_threshold);
+        if is_speaking && !was_speaking {
+            debug!("Speech started (RMS: {:.4})", rms);
+        } else if was_speaking && !is_speaking {
+            debug!("Speech ended");
         }
-        let sum: f32 = audio.iter().map(|x| x * x).sum();
-        (sum / audio.len() as f32).sqrt()
+
+        is_speaking
     }
 
     /// Get current speaking state
     pub fn is_currently_speaking(&self) -> bool {
-        self.is_speaking
+        self.gate.is_speaking()
     }
 
     /// Reset the VAD state
     pub fn reset(&mut self) {
-        self.speech_frames = 0;
-        self.silence_frames = 0;
-        self.is_speaking = false;
+        self.gate.reset();
     }
 
     /// Update the energy threshold
@@ -87,15 +175,211 @@ impl VoiceActivityDetector {
 
     /// Get current audio level (0.0 - 1.0)
     pub fn get_audio_level(&self, audio: &[f32]) -> f32 {
-        let rms = self.calculate_rms(audio);
-        (rms * 10.0).min(1.0) // Scale and clamp to 0-1
+        audio_level(audio)
     }
 }
 
 impl Default for VoiceActivityDetector {
     fn default() -> Self {
-        // Increased threshold from 0.02 to 0.03 to reduce false positives from background noise
-        Self::new(0.03)
+        Self::new(DEFAULT_ENERGY_THRESHOLD)
+    }
+}
+
+impl Vad for VoiceActivityDetector {
+    fn is_speech(&mut self, audio: &[f32]) -> bool {
+        VoiceActivityDetector::is_speech(self, audio)
+    }
+
+    /// Energy relative to the threshold, saturating at twice the threshold
+    fn speech_probability(&self) -> f32 {
+        (self.last_rms / (2.0 * self.energy_threshold)).min(1.0)
+    }
+
+    fn is_currently_speaking(&self) -> bool {
+        self.gate.is_speaking()
+    }
+
+    fn reset(&mut self) {
+        self.gate.reset();
+    }
+}
+
+/// Chunks the noise floor is the minimum of (5 seconds at 100ms chunks)
+pub const NOISE_WINDOW_CHUNKS: usize = 50;
+
+/// Lowest noise floor, so digital silence doesn't make every hiss speech
+pub const MIN_NOISE_FLOOR: f32 = 0.001;
+
+/// Smoothing factor of the chunk energy before taking the minimum
+///
+/// Kept light so the short pauses between words still reach the background.
+const NOISE_SMOOTHING: f32 = 0.2;
+
+/// The minimum of a noisy signal sits below its mean, this compensates
+const NOISE_BIAS: f32 = 1.5;
+
+/// SNR above which a chunk counts as speech
+pub const SPEECH_START_SNR_DB: f32 = 12.0;
+
+/// SNR below which a chunk counts as silence
+pub const SPEECH_END_SNR_DB: f32 = 6.0;
+
+/// Background noise estimate using minimum statistics
+///
+/// The noise floor is the minimum of the smoothed RMS over the last
+/// [`NOISE_WINDOW_CHUNKS`] chunks. Speech has enough pauses between words
+/// that the minimum stays on the background noise while talking, and it
+/// follows the background up or down within about one window.
+#[derive(Debug, Clone)]
+pub struct NoiseFloor {
+    history: VecDeque<f32>,
+    smoothed: Option<f32>,
+}
+
+impl NoiseFloor {
+    pub fn new() -> Self {
+        Self {
+            history: VecDeque::with_capacity(NOISE_WINDOW_CHUNKS),
+            smoothed: None,
+        }
+    }
+
+    /// Add the RMS of the next chunk
+    ///
+    /// # Returns
+    /// The updated noise floor
+    pub fn update(&mut self, rms: f32) -> f32 {
+        let smoothed = match self.smoothed {
+            Some(prev) => NOISE_SMOOTHING * prev + (1.0 - NOISE_SMOOTHING) * rms,
+            None => rms,
+        };
+        self.smoothed = Some(smoothed);
+
+        if self.history.len() == NOISE_WINDOW_CHUNKS {
+            self.history.pop_front();
+        }
+        self.history.push_back(smoothed);
+
+        self.floor()
+    }
+
+    /// Current noise floor as RMS, [`MIN_NOISE_FLOOR`] before any measurement
+    pub fn floor(&self) -> f32 {
+        self.history
+            .iter()
+            .map(|rms| rms * NOISE_BIAS)
+            .reduce(f32::min)
+            .unwrap_or(0.0)
+            .max(MIN_NOISE_FLOOR)
+    }
+
+    /// Whether a full window has been measured
+    pub fn is_settled(&self) -> bool {
+        self.history.len() == NOISE_WINDOW_CHUNKS
+    }
+
+    /// Signal-to-noise ratio of `rms` in dB
+    pub fn snr_db(&self, rms: f32) -> f32 {
+        20.0 * (rms.max(f32::EPSILON) / self.floor()).log10()
+    }
+
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.smoothed = None;
+    }
+}
+
+impl Default for NoiseFloor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Energy detector with a threshold relative to the background noise
+///
+/// Speech starts above [`SPEECH_START_SNR_DB`] and ends below
+/// [`SPEECH_END_SNR_DB`] over the [`NoiseFloor`]; in between the current
+/// state is kept. Works in a quiet office and a noisy café alike without
+/// tuning a threshold.
+pub struct AdaptiveVad {
+    noise: NoiseFloor,
+    gate: SpeechGate,
+    last_snr_db: f32,
+}
+
+impl AdaptiveVad {
+    pub fn new() -> Self {
+        Self {
+            noise: NoiseFloor::new(),
+            // Same timing as the energy detector
+            gate: SpeechGate::new(3, 15),
+            last_snr_db: 0.0,
+        }
+    }
+
+    /// Current background noise estimate as RMS
+    pub fn noise_floor(&self) -> f32 {
+        self.noise.floor()
+    }
+
+    /// SNR of the last chunk in dB
+    pub fn snr_db(&self) -> f32 {
+        self.last_snr_db
+    }
+}
+
+impl Default for AdaptiveVad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vad for AdaptiveVad {
+    fn is_speech(&mut self, audio: &[f32]) -> bool {
+        let rms = rms(audio);
+        // Measured against the floor before this chunk, so a sudden loud
+        // chunk is compared with the background rather than itself
+        let snr = self.noise.snr_db(rms);
+        self.noise.update(rms);
+        self.last_snr_db = snr;
+
+        let was_speaking = self.gate.is_speaking();
+        let speech = if snr >= SPEECH_START_SNR_DB {
+            true
+        } else if snr < SPEECH_END_SNR_DB {
+            false
+        } else {
+            was_speaking
+        };
+
+        let is_speaking = self.gate.update(speech);
+        if is_speaking && !was_speaking {
+            debug!(
+                "Speech started (RMS: {:.4}, SNR: {:.1} dB, noise floor: {:.4})",
+                rms,
+                snr,
+                self.noise.floor()
+            );
+        } else if was_speaking && !is_speaking {
+            debug!("Speech ended");
+        }
+
+        is_speaking
+    }
+
+    /// SNR relative to the start threshold, 0.5 right at the threshold
+    fn speech_probability(&self) -> f32 {
+        (self.last_snr_db / (2.0 * SPEECH_START_SNR_DB)).clamp(0.0, 1.0)
+    }
+
+    fn is_currently_speaking(&self) -> bool {
+        self.gate.is_speaking()
+    }
+
+    fn reset(&mut self) {
+        self.noise.reset();
+        self.gate.reset();
+        self.last_snr_db = 0.0;
     }
 }
 
@@ -134,6 +418,24 @@ mod tests {
         assert!(detected);
     }
 
+    #[test]
+    fn test_speech_gate_hangover() {
+        let mut gate = SpeechGate::new(2, 3);
+
+        assert!(!gate.update(true));
+        assert!(gate.update(true));
+
+        // A short pause doesn't end the segment
+        assert!(gate.update(false));
+        assert!(gate.update(false));
+        assert!(gate.update(true));
+
+        for _ in 0..2 {
+            assert!(gate.update(false));
+        }
+        assert!(!gate.update(false));
+    }
+
     #[test]
     fn test_audio_level() {
         let vad = VoiceActivityDetector::new(0.02);
@@ -144,4 +446,91 @@ mod tests {
         let loud: Vec<f32> = vec![0.5; 160];
         assert!(vad.get_audio_level(&loud) > 0.0);
     }
+
+    /// Deterministic white-ish noise with the given RMS
+    fn noise(len: usize, level: f32, seed: &mut u32) -> Vec<f32> {
+        (0..len)
+            .map(|_| {
+                *seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let uniform = (*seed >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0;
+                // Uniform noise in [-1, 1] has an RMS of 1/sqrt(3)
+                uniform * level * 3f32.sqrt()
+            })
+            .collect()
+    }
+
+    fn speech_over(background: &[f32], amplitude: f32) -> Vec<f32> {
+        background
+            .iter()
+            .enumerate()
+            .map(|(i, n)| {
+                n + amplitude * (2.0 * std::f32::consts::PI * 200.0 * i as f32 / 16000.0).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_noise_floor_follows_background() {
+        let mut seed = 1;
+        let mut floor = NoiseFloor::new();
+        assert_eq!(floor.floor(), MIN_NOISE_FLOOR);
+
+        for _ in 0..NOISE_WINDOW_CHUNKS {
+            floor.update(rms(&noise(1600, 0.002, &mut seed)));
+        }
+        assert!(floor.is_settled());
+        assert!((0.002..0.004).contains(&floor.floor()), "{}", floor.floor());
+
+        // Louder background is picked up once the quiet part leaves the window
+        for _ in 0..NOISE_WINDOW_CHUNKS + 10 {
+            floor.update(rms(&noise(1600, 0.02, &mut seed)));
+        }
+        assert!((0.02..0.04).contains(&floor.floor()), "{}", floor.floor());
+
+        // Speech with short pauses between words doesn't raise it
+        for i in 0..NOISE_WINDOW_CHUNKS {
+            let level = if i % 10 < 7 { 0.3 } else { 0.02 };
+            floor.update(rms(&noise(1600, level, &mut seed)));
+        }
+        assert!(floor.floor() < 0.04, "{}", floor.floor());
+    }
+
+    #[test]
+    fn test_adaptive_vad_ignores_steady_noise() {
+        let mut seed = 7;
+        let mut vad = AdaptiveVad::new();
+
+        // Café noise louder than the default energy threshold
+        for _ in 0..200 {
+            let chunk = noise(1600, 0.04, &mut seed);
+            assert!(!Vad::is_speech(&mut vad, &chunk));
+        }
+        assert!(vad.noise_floor() > 0.04);
+    }
+
+    #[test]
+    fn test_adaptive_vad_detects_speech_over_noise() {
+        for level in [0.002, 0.03] {
+            let mut seed = 3;
+            let mut vad = AdaptiveVad::new();
+            for _ in 0..NOISE_WINDOW_CHUNKS {
+                Vad::is_speech(&mut vad, &noise(1600, level, &mut seed));
+            }
+
+            // Speech 20dB over the background
+            let mut detected = false;
+            for _ in 0..5 {
+                let chunk = speech_over(&noise(1600, level, &mut seed), level * 10.0 * 2f32.sqrt());
+                detected |= Vad::is_speech(&mut vad, &chunk);
+            }
+            assert!(detected, "no speech at noise level {}", level);
+            assert!(vad.speech_probability() > 0.5);
+
+            // Ends after the hangover once only the background is left
+            for _ in 0..15 {
+                Vad::is_speech(&mut vad, &noise(1600, level, &mut seed));
+            }
+            assert!(!vad.is_currently_speaking());
+        }
+    }
 }
@@ -1,15 +1,26 @@
 use serde::{Deserialize, Serialize};
-use tauri::{command, AppHandle, Emitter, State};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{command, AppHandle, Emitter, Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tokio::sync::mpsc;
 use tracing::{debug, error, info};
 
-use crate::audio::{AudioCapture, AudioResampler, DeviceInfo};
-use crate::input::{get_active_window, InjectionStrategy, WindowInfo};
-use crate::network::{ServerMessage, WebSocketClient};
+use crate::audio::{AudioCapture, DeviceInfo, VadEngine};
+use crate::history::{HistoryEntry, HistoryStore, NewSegment};
+use crate::hotkeys::HotkeyManager;
+use crate::input::{
+    get_active_window, InjectionStrategy, SmartSpacer, TextInjectorService, WindowInfo,
+};
+use crate::network::{ProviderKind, SessionOptions, SpeechToTextProvider};
+use crate::session::{DictationSession, SessionConfig, SessionEvent};
+use crate::settings::Settings;
 use crate::state::AppState;
 use crate::utils::{
     check_accessibility_permission, check_microphone_permission, open_system_preferences,
     PerformanceMetrics, PermissionStatus,
 };
+use crate::whisper::{ModelStatus, WhisperModel};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PermissionInfo {
@@ -18,6 +29,12 @@ pub struct PermissionInfo {
     pub all_granted: bool,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiKeyStatus {
+    pub configured: bool,
+    pub backend: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TranscriptStatus {
     pub is_recording: bool,
@@ -42,391 +59,356 @@ pub async fn list_audio_devices() -> Result<Vec<DeviceInfo>, String> {
 }
 
 /// Start audio recording and transcription
+///
+/// The API key is read from the credential store. Arguments that are not
+/// given fall back to the saved settings.
 #[command]
 pub async fn start_recording(
     app: AppHandle,
     state: State<'_, AppState>,
-    api_key: String,
     device_name: Option<String>,
+    options: Option<SessionOptions>,
 ) -> Result<(), String> {
-    info!("Starting recording with device: {:?}", device_name);
-    info!("API key length: {}", api_key.len());
-
-    // Store API key
-    *state.api_key.lock().await = Some(api_key.clone());
-
-    // Check if already recording
-    let is_recording = *state.is_recording.lock().await;
-    if is_recording {
-        error!("Already recording");
-        return Err("Already recording".to_string());
-    }
+    start_dictation(&app, &state, device_name, options).await
+}
 
-    // Initialize audio capture
-    info!("Creating audio capture...");
-    let mut capture = AudioCapture::new().map_err(|e| {
-        error!("Failed to create audio capture: {}", e);
+/// Start a dictation session and forward its events to the frontend
+///
+/// Shared by the `start_recording` command and the global hotkeys.
+pub(crate) async fn start_dictation(
+    app: &AppHandle,
+    state: &AppState,
+    device_name: Option<String>,
+    options: Option<SessionOptions>,
+) -> Result<(), String> {
+    state.begin_session_start().await.map_err(|e| {
+        error!("{}", e);
         e.to_string()
     })?;
+    start_reserved_dictation(app, state, device_name, options).await
+}
 
-    if let Some(device) = device_name {
-        info!("Setting device: {}", device);
-        capture.set_device(&device).map_err(|e| {
-            error!("Failed to set device: {}", e);
-            e.to_string()
-        })?;
-    } else {
-        info!("Using default device...");
-        capture.use_default_device().map_err(|e| {
-            error!("Failed to use default device: {}", e);
-            e.to_string()
-        })?;
-    }
-
-    let sample_rate = capture.sample_rate().ok_or_else(|| {
-        error!("No sample rate available");
-        "No sample rate".to_string()
-    })?;
-    info!("Sample rate: {}", sample_rate);
+/// Start a session reserved with [`AppState::begin_session_start`]
+///
+/// A stop while the session connects is applied once it started.
+pub(crate) async fn start_reserved_dictation(
+    app: &AppHandle,
+    state: &AppState,
+    device_name: Option<String>,
+    options: Option<SessionOptions>,
+) -> Result<(), String> {
+    let settings = state.settings.lock().await.clone();
+    let auto_inject = settings.auto_inject;
+    let injection_strategy = settings.injection_strategy;
 
-    // Create channel for audio packets (increased capacity for better performance)
-    let (audio_tx, mut audio_rx) = tokio::sync::mpsc::channel(500);
+    let (session, events) = match connect_dictation(state, settings, device_name, options).await {
+        Ok(started) => started,
+        Err(e) => {
+            state.abort_session_start().await;
+            return Err(e);
+        }
+    };
+    let device = session.device_name().await.unwrap_or_default();
+    state
+        .metrics
+        .start_session(session.provider_name(), &device);
+    // Only sessions that actually started show up in the history
+    let history_session = state.start_history_session(&session.config().options).await;
+
+    let cancelled = state.finish_session_start(session).await;
+
+    // Auto-inject runs in its own task so slow typing never holds up events
+    let inject_tx = if auto_inject {
+        match state.text_injector_service.lock().await.clone() {
+            Some(service) => {
+                let (inject_tx, inject_rx) = mpsc::unbounded_channel();
+                tokio::spawn(auto_inject_transcripts(
+                    service,
+                    injection_strategy,
+                    inject_rx,
+                ));
+                Some(inject_tx)
+            }
+            None => {
+                error!("Text injector service not initialized, auto-inject disabled");
+                None
+            }
+        }
+    } else {
+        None
+    };
 
-    // Start audio stream
-    info!("Starting audio stream...");
-    capture.start_stream(audio_tx).map_err(|e| {
-        error!("Failed to start audio stream: {}", e);
-        e.to_string()
-    })?;
-    info!("Audio stream started");
+    tokio::spawn(forward_session_events(
+        app.clone(),
+        state.clone(),
+        events,
+        inject_tx,
+        history_session,
+    ));
+    let _ = app.emit("recording-started", ());
+
+    if cancelled {
+        info!("Recording was stopped while connecting");
+        return stop_dictation(app, state).await;
+    }
+    Ok(())
+}
 
-    // Initialize resampler (device sample rate -> 16kHz)
-    info!("Creating resampler...");
-    let resampler = AudioResampler::new(sample_rate as usize, 16000, 1600)
-        .map_err(|e| {
-            error!("Failed to create resampler: {}", e);
+/// Create and start the session described by the settings
+async fn connect_dictation(
+    state: &AppState,
+    settings: Settings,
+    device_name: Option<String>,
+    options: Option<SessionOptions>,
+) -> Result<(DictationSession, mpsc::UnboundedReceiver<SessionEvent>), String> {
+    let device_name = device_name.or(settings.device_name);
+    let mut options = options.unwrap_or(settings.transcription);
+
+    let provider: Arc<dyn SpeechToTextProvider> = if settings.offline.enabled {
+        let whisper = state.whisper().await.map_err(|e| {
+            error!("Offline transcription unavailable: {}", e);
             e.to_string()
         })?;
-    info!("Resampler created");
-
-    // Store in state
-    *state.audio_capture.lock().await = Some(capture);
-    *state.resampler.lock().await = Some(resampler);
-
-    // Initialize WebSocket client BEFORE setting is_recording to true
-    info!("🌐 Attempting WebSocket connection...");
-    info!("🔧 Model: scribe_v2_realtime, Language: zho (Mandarin Chinese - explicitly specified)");
-    let mut ws_client = WebSocketClient::new(api_key);
-    let (mut ws_sink, ws_stream) = match ws_client.connect().await {
-        Ok(streams) => {
-            info!("✅ WebSocket connected successfully!");
-            streams
-        }
-        Err(e) => {
-            error!("❌ WebSocket connection FAILED: {}", e);
-            error!("💡 This might mean scribe_v1 or language_code=cmn is not supported");
-            // Clean up on error
-            *state.audio_capture.lock().await = None;
-            *state.resampler.lock().await = None;
-            return Err(format!("WebSocket connection failed: {}. Please check your API key and model availability.", e));
-        }
+        whisper.select_model(settings.offline.model);
+        // Recorded in the history instead of the provider model
+        options = options.with_model(settings.offline.model.id());
+        whisper
+    } else {
+        let api_key = state.api_key.lock().await.clone().ok_or_else(|| {
+            error!("No API key configured");
+            "API key not configured".to_string()
+        })?;
+        ProviderKind::default().create(
+            api_key,
+            settings.endpoint.clone(),
+            state.metrics.as_ref().clone(),
+        )
     };
 
-    // Only set is_recording to true AFTER successful WebSocket connection
-    *state.is_recording.lock().await = true;
+    info!("Starting recording with device: {:?}", device_name);
+    info!(
+        "Session options: model={}, language={}",
+        options.model_id,
+        options.language().unwrap_or("auto-detect")
+    );
 
-    // Start WebSocket receive loop
-    let (server_tx, mut server_rx) = tokio::sync::mpsc::channel(100);
-    let state_for_ws_close = state.inner().clone();
-    let app_for_ws_close = app.clone();
+    let silero_model = match settings.vad_engine {
+        VadEngine::Energy | VadEngine::Adaptive => None,
+        VadEngine::Silero => Some(state.silero_model().await.map_err(|e| {
+            error!("Silero VAD unavailable: {}", e);
+            e.to_string()
+        })?),
+    };
 
-    tokio::spawn(async move {
-        if let Err(e) = WebSocketClient::receive_loop(ws_stream, server_tx).await {
-            error!("WebSocket receive loop error: {}", e);
-        }
+    let config = SessionConfig::new()
+        .with_device(device_name)
+        .with_device_priority(settings.device_priority)
+        .with_channel_mode(settings.channel_mode)
+        .with_buffer_size(settings.buffer_size)
+        .with_vad_engine(settings.vad_engine)
+        .with_vad_threshold(settings.vad_threshold)
+        .with_pre_roll(Duration::from_millis(settings.pre_roll_ms as u64))
+        .with_auto_gain(settings.auto_gain)
+        .with_noise_suppression(
+            settings
+                .noise_suppression
+                .enabled
+                .then_some(settings.noise_suppression.strength),
+        )
+        .with_silero_vad(silero_model)
+        .with_options(options)
+        .with_metrics(state.metrics.as_ref().clone());
+    let (mut session, events) = DictationSession::new(config, provider);
+    session.start().await.map_err(|e| {
+        error!("Failed to start dictation session: {}", e);
+        e.to_string()
+    })?;
+    Ok((session, events))
+}
 
-        // WebSocket closed, stop recording
-        info!("WebSocket closed, stopping recording");
-        *state_for_ws_close.is_recording.lock().await = false;
-
-        // Notify frontend
-        let _ = app_for_ws_close.emit("recording-stopped", "WebSocket connection closed");
-    });
-
-    // Store WebSocket client
-    *state.ws_client.lock().await = Some(ws_client);
-
-    // Clone state for async task
-    let state_clone = state.inner().clone();
-    let app_clone = app.clone();
-
-    // Audio processing task
-    tokio::spawn(async move {
-        let mut buffer = Vec::new();
-        const CHUNK_SIZE: usize = 1600; // 100ms at 16kHz
-        let mut was_speaking = false; // Track previous speaking state
-        let mut audio_chunk_count = 0;
-        let mut background_noise_samples: Vec<f32> = Vec::new();
-        let mut noise_baseline = 0.0_f32;
-        let mut chunk_count = 0; // Track processed chunks for noise baseline
-        let mut silence_chunks_since_last_send = 0; // Track silence duration
-        const MAX_SILENCE_CHUNKS_BEFORE_KEEPALIVE: usize = 50; // 5 seconds of silence
-
-        info!("🎤 Audio processing task started");
-
-        while let Some(audio_packet) = audio_rx.recv().await {
-            audio_chunk_count += 1;
-
-            // Check audio signal every 100 packets
-            if audio_chunk_count % 100 == 0 {
-                let rms: f32 = audio_packet.iter().map(|x| x * x).sum::<f32>() / audio_packet.len() as f32;
-                let rms = rms.sqrt();
-                info!("📊 Audio input RMS: {:.6} (packet #{})", rms, audio_chunk_count);
+/// Forward session events to the frontend
+async fn forward_session_events(
+    app: AppHandle,
+    state: AppState,
+    mut events: mpsc::UnboundedReceiver<SessionEvent>,
+    inject_tx: Option<mpsc::UnboundedSender<String>>,
+    history_session: Option<i64>,
+) {
+    while let Some(event) = events.recv().await {
+        match event {
+            SessionEvent::AudioLevel { level, input_level } => {
+                let _ = app.emit(
+                    "audio-level",
+                    serde_json::json!({
+                        "level": level,
+                        "input_level": input_level,
+                    }),
+                );
             }
-
-            // Check if still recording
-            if !*state_clone.is_recording.lock().await {
-                break;
+            SessionEvent::PartialTranscript { text } => {
+                *state.current_transcript.lock().await = text.clone();
+                let _ = app.emit(
+                    "transcript-update",
+                    serde_json::json!({
+                        "text": text,
+                        "is_final": false,
+                    }),
+                );
             }
-
-            // Resample to 16kHz
-            let mut resampler_guard = state_clone.resampler.lock().await;
-            if let Some(resampler) = resampler_guard.as_mut() {
-                match resampler.process(&audio_packet) {
-                    Ok(resampled) => {
-                        buffer.extend_from_slice(&resampled);
-
-                        // Process in chunks
-                        while buffer.len() >= CHUNK_SIZE {
-                            // Check if still recording INSIDE the loop
-                            if !*state_clone.is_recording.lock().await {
-                                info!("Recording stopped during chunk processing");
-                                break;  // Exit inner loop
-                            }
-
-                            let chunk: Vec<f32> = buffer.drain(..CHUNK_SIZE).collect();
-                            chunk_count += 1;
-
-                            // Calculate background noise level (before VAD)
-                            let chunk_rms = (chunk.iter().map(|x| x * x).sum::<f32>() / chunk.len() as f32).sqrt();
-
-                            // Collect background noise samples before speech starts (first 3 seconds = 30 chunks)
-                            if chunk_count <= 30 && !was_speaking {
-                                background_noise_samples.push(chunk_rms);
-                                if chunk_count == 30 {
-                                    noise_baseline = background_noise_samples.iter().sum::<f32>() / 30.0;
-                                    info!("📊 Background noise baseline calculated: RMS = {:.6}", noise_baseline);
-                                    info!("   Samples collected: {} chunks over 3 seconds", background_noise_samples.len());
-                                    if noise_baseline > 0.01 {
-                                        info!("⚠️  High background noise! May cause API false positives.");
-                                        info!("   Recommendation: Reduce system mic volume or improve environment");
-                                    } else if noise_baseline > 0.005 {
-                                        info!("⚠️  Moderate background noise detected");
-                                    } else {
-                                        info!("✅ Low background noise - good recording environment");
-                                    }
-                                }
-                            }
-
-                            // VAD detection for audio level monitoring and speech detection
-                            let mut vad = state_clone.vad.lock().await;
-                            let is_speech = vad.is_speech(&chunk);
-                            let audio_level = vad.get_audio_level(&chunk);
-                            drop(vad);
-
-                            // Emit audio level to frontend
-                            let _ = app_clone.emit("audio-level", audio_level);
-
-                            // Detect speech end (transition from speaking to silence)
-                            let speech_ended = was_speaking && !is_speech;
-
-                            // Log VAD transitions with noise analysis
-                            if !was_speaking && is_speech {
-                                let snr = if noise_baseline > 0.0 {
-                                    20.0 * (chunk_rms / noise_baseline).log10()
-                                } else {
-                                    999.0
-                                };
-                                info!(
-                                    "🎙️  VAD: Speech STARTED | RMS: {:.6} | Audio Level: {:.4} | SNR: {:.1} dB",
-                                    chunk_rms, audio_level, snr
-                                );
-                                if snr < 10.0 {
-                                    info!("⚠️  Low SNR! Background noise may interfere with recognition.");
-                                }
-                            } else if speech_ended {
-                                info!("🔚 VAD: Speech ENDED (sending commit) | RMS: {:.6}", chunk_rms);
-                            }
-
-                            was_speaking = is_speech;
-
-                            // CRITICAL FIX: Only send audio when speech is detected or just ended
-                            // This prevents API from receiving and misinterpreting background noise
-                            let should_send_audio = is_speech || speech_ended;
-
-                            // Keep-alive mechanism: send silence if no audio sent for too long
-                            // This prevents API from closing idle connections
-                            let mut send_keepalive = false;
-                            if !should_send_audio {
-                                silence_chunks_since_last_send += 1;
-                                if silence_chunks_since_last_send >= MAX_SILENCE_CHUNKS_BEFORE_KEEPALIVE {
-                                    send_keepalive = true;
-                                    silence_chunks_since_last_send = 0;
-                                    info!("🔄 Sending keep-alive silence chunk to maintain WebSocket connection");
-                                }
-                            } else {
-                                silence_chunks_since_last_send = 0;
-                            }
-
-                            if !should_send_audio && !send_keepalive {
-                                // Skip sending this chunk - it's just background noise
-                                continue;
-                            }
-
-                            // Send audio to WebSocket with commit flag when speech ends
-                            // For keep-alive, send silence without commit
-                            if let Err(e) =
-                                WebSocketClient::send_audio(&mut ws_sink, &chunk, speech_ended).await
-                            {
-                                error!("Failed to send audio: {}", e);
-                                // Stop recording on send error
-                                *state_clone.is_recording.lock().await = false;
-                                break;  // Exit inner loop, outer loop will also exit
-                            }
-
-                            // Log audio transmission
-                            if speech_ended {
-                                info!("📤 Sent audio chunk with COMMIT flag");
-                            } else if send_keepalive {
-                                info!("📤 Sent keep-alive chunk");
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        error!("Resampling error: {}", e);
+            SessionEvent::CommittedTranscript {
+                text,
+                language_code,
+                confidence,
+                words,
+            } => {
+                *state.current_transcript.lock().await = text.clone();
+                if let Some(inject_tx) = &inject_tx {
+                    let _ = inject_tx.send(text.clone());
+                }
+                if let Some(session_id) = history_session {
+                    let segment = NewSegment::new(text.clone())
+                        .with_language(language_code.clone())
+                        .with_confidence(confidence);
+                    if !segment.is_blank() {
+                        let segment = segment.with_window(get_active_window().ok());
+                        state.record_history_segment(session_id, segment).await;
                     }
                 }
+                let _ = app.emit(
+                    "transcript-update",
+                    serde_json::json!({
+                        "text": text,
+                        "is_final": true,
+                        "language_code": language_code,
+                        "confidence": confidence,
+                        "words": words,
+                    }),
+                );
             }
-
-            // Check again if we should stop (in case inner loop set it to false)
-            if !*state_clone.is_recording.lock().await {
-                info!("Recording stopped, exiting audio processing loop");
-                break;
+            SessionEvent::Error { message } => {
+                let _ = app.emit("transcript-error", message);
             }
+            SessionEvent::Reconnecting { reason } => {
+                let _ = app.emit("connection-lost", reason);
+            }
+            SessionEvent::Reconnected => {
+                let _ = app.emit("connection-restored", ());
+            }
+            SessionEvent::DeviceChanged { device } => {
+                state.metrics.set_session_device(&device);
+                let _ = app.emit("audio-device-changed", device);
+            }
+            SessionEvent::DeviceLost { reason } => {
+                let _ = app.emit("audio-device-lost", reason);
+            }
+            SessionEvent::Stopped { reason } => {
+                *state.is_recording.lock().await = false;
+                state.metrics.end_session();
+                let _ = app.emit("recording-stopped", reason);
+            }
+            SessionEvent::Connected { .. }
+            | SessionEvent::SpeechStarted
+            | SessionEvent::SpeechEnded => {}
         }
+    }
 
-        info!("🔇 Audio processing task ended");
+    if let Some(session_id) = history_session {
+        state.end_history_session(session_id).await;
+    }
+}
 
-        // Stop the audio capture stream
-        let mut capture_guard = state_clone.audio_capture.lock().await;
-        if let Some(mut capture) = capture_guard.take() {
-            if let Err(e) = capture.stop_stream() {
-                error!("Failed to stop audio stream: {}", e);
-            } else {
-                info!("✅ Audio capture stream stopped cleanly");
-            }
-        }
-    });
-
-    // Transcript processing task
-    let app_clone = app.clone();
-    let state_clone = state.inner().clone();
-    tokio::spawn(async move {
-        info!("=== Transcript processing task started ===");
-        while let Some(msg) = server_rx.recv().await {
-            info!("Received server message: {:?}", msg);
-
-            match msg {
-                ServerMessage::PartialTranscript { text, .. } => {
-                    info!("📝 PARTIAL TRANSCRIPT: \"{}\"", text);
-                    *state_clone.current_transcript.lock().await = text.clone();
-
-                    let _ = app_clone.emit(
-                        "transcript-update",
-                        serde_json::json!({
-                            "text": text,
-                            "is_final": false,
-                        }),
-                    );
-                }
-                ServerMessage::CommittedTranscript { text, .. } => {
-                    info!("✅ COMMITTED TRANSCRIPT: \"{}\"", text);
-                    *state_clone.current_transcript.lock().await = text.clone();
-
-                    let _ = app_clone.emit(
-                        "transcript-update",
-                        serde_json::json!({
-                            "text": text,
-                            "is_final": true,
-                        }),
-                    );
-                }
-                ServerMessage::CommittedTranscriptWithTimestamps { text, .. } => {
-                    info!("✅ COMMITTED TRANSCRIPT (with timestamps): \"{}\"", text);
-                    *state_clone.current_transcript.lock().await = text.clone();
-
-                    let _ = app_clone.emit(
-                        "transcript-update",
-                        serde_json::json!({
-                            "text": text,
-                            "is_final": true,
-                        }),
-                    );
-                }
-                ServerMessage::InputError { error_message, .. } => {
-                    error!("❌ API Error: {}", error_message);
-                    let _ = app_clone.emit("transcript-error", error_message);
-                }
-                ServerMessage::InvalidRequest { error } => {
-                    error!("❌ Invalid Request: {}", error);
-                    let _ = app_clone.emit("transcript-error", error);
-                    // Stop recording on invalid request
-                    *state_clone.is_recording.lock().await = false;
-                }
-                ServerMessage::SessionStarted { session_id, model_id } => {
-                    info!("🎬 Session started: {} (model: {})", session_id, model_id);
-                }
-                _ => {
-                    info!("Other message type received: {:?}", msg);
-                }
-            }
+/// Inject committed transcripts one after another, in arrival order
+///
+/// Ends once the session is gone and every queued segment was injected.
+async fn auto_inject_transcripts(
+    service: TextInjectorService,
+    strategy: Option<InjectionStrategy>,
+    mut segments: mpsc::UnboundedReceiver<String>,
+) {
+    let mut spacer = SmartSpacer::new();
+    let mut target_pid = None;
+
+    while let Some(segment) = segments.recv().await {
+        // Switching apps starts a new sentence, not a continuation
+        let pid = get_active_window().ok().map(|window| window.process_id);
+        if pid != target_pid {
+            spacer.reset();
+            target_pid = pid;
         }
 
-        info!("=== Transcript processing task ended ===");
-    });
+        let Some(text) = spacer.next(&segment) else {
+            continue;
+        };
 
-    info!("Recording started successfully");
-    Ok(())
+        debug!("Auto-injecting {} chars", text.len());
+        if let Err(e) = service.inject_text(text, strategy).await {
+            error!("Failed to auto-inject transcript: {}", e);
+        }
+    }
 }
 
 /// Stop audio recording
 #[command]
-pub async fn stop_recording(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn stop_recording(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    stop_dictation(&app, &state).await
+}
+
+/// Stop the running dictation session, committing any pending speech
+pub(crate) async fn stop_dictation(app: &AppHandle, state: &AppState) -> Result<(), String> {
     info!("Stopping recording");
 
     *state.is_recording.lock().await = false;
+    // A session that is still connecting stops as soon as it started
+    state.cancel_session_start().await;
 
-    // Stop audio capture
-    if let Some(mut capture) = state.audio_capture.lock().await.take() {
-        capture.stop_stream().map_err(|e| e.to_string())?;
+    if let Some(mut session) = state.session.lock().await.take() {
+        session.stop().await.map_err(|e| e.to_string())?;
     }
+    state.metrics.end_session();
 
     // Clear state
-    *state.resampler.lock().await = None;
-    *state.ws_client.lock().await = None;
     *state.current_transcript.lock().await = String::new();
+    let _ = app.emit("recording-stopped", "Stopped");
 
     info!("Recording stopped successfully");
     Ok(())
 }
 
+/// Pause the running recording without closing the connection
+#[command]
+pub async fn pause_recording(state: State<'_, AppState>) -> Result<(), String> {
+    match state.session.lock().await.as_ref() {
+        Some(session) if session.is_running() => {
+            session.pause();
+            Ok(())
+        }
+        _ => Err("Not recording".to_string()),
+    }
+}
+
+/// Resume a paused recording
+#[command]
+pub async fn resume_recording(state: State<'_, AppState>) -> Result<(), String> {
+    match state.session.lock().await.as_ref() {
+        Some(session) if session.is_running() => {
+            session.resume();
+            Ok(())
+        }
+        _ => Err("Not recording".to_string()),
+    }
+}
+
 /// Get current transcript status
 #[command]
 pub async fn get_transcript_status(state: State<'_, AppState>) -> Result<TranscriptStatus, String> {
     let is_recording = *state.is_recording.lock().await;
     let transcript = state.current_transcript.lock().await.clone();
-    let vad = state.vad.lock().await;
-    let is_speaking = vad.is_currently_speaking();
+    let is_speaking = state
+        .session
+        .lock()
+        .await
+        .as_ref()
+        .is_some_and(|s| s.is_speaking());
 
     Ok(TranscriptStatus {
         is_recording,
@@ -438,31 +420,30 @@ pub async fn get_transcript_status(state: State<'_, AppState>) -> Result<Transcr
 /// Inject text into the active application
 #[command]
 pub async fn inject_text(
-    app: AppHandle,
     state: State<'_, AppState>,
     text: String,
     strategy: Option<String>,
 ) -> Result<(), String> {
     info!("Injecting text: {} chars", text.len());
 
-    // Parse strategy
-    let strategy = strategy.and_then(|s| match s.as_str() {
-        "keyboard" => Some(InjectionStrategy::Keyboard),
-        "clipboard" => Some(InjectionStrategy::Clipboard),
-        _ => None,
-    });
+    // Parse strategy, falling back to the configured one
+    let strategy = match strategy {
+        Some(s) => match s.as_str() {
+            "keyboard" => Some(InjectionStrategy::Keyboard),
+            "clipboard" => Some(InjectionStrategy::Clipboard),
+            _ => None,
+        },
+        None => state.settings.lock().await.injection_strategy,
+    };
 
     // Get the text injector service
     let service_guard = state.text_injector_service.lock().await;
 
     if let Some(service) = service_guard.as_ref() {
-        service
-            .inject_text(text, strategy)
-            .await
-            .map_err(|e| {
-                error!("Failed to inject text: {}", e);
-                e.to_string()
-            })?;
+        service.inject_text(text, strategy).await.map_err(|e| {
+            error!("Failed to inject text: {}", e);
+            e.to_string()
+        })?;
     } else {
         return Err("Text injector service not initialized".to_string());
     }
@@ -471,6 +452,243 @@ pub async fn inject_text(
     Ok(())
 }
 
+/// Get the current settings
+#[command]
+pub async fn get_settings(state: State<'_, AppState>) -> Result<Settings, String> {
+    Ok(state.settings.lock().await.clone())
+}
+
+/// Save new settings and apply them to the running app
+#[command]
+pub async fn update_settings(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    settings: Settings,
+) -> Result<Settings, String> {
+    info!("Updating settings");
+
+    // Rebind first so a conflicting hotkey is rejected before it gets saved
+    settings.validate().map_err(|e| e.to_string())?;
+    let hotkeys = app.try_state::<HotkeyManager>();
+    let previous_hotkeys = state.settings.lock().await.hotkeys.clone();
+    if let Some(hotkeys) = &hotkeys {
+        hotkeys.rebind(&app, &settings.hotkeys).map_err(|e| {
+            error!("Failed to register hotkeys: {}", e);
+            e.to_string()
+        })?;
+    }
+
+    if let Err(e) = state.update_settings(settings).await {
+        error!("Failed to update settings: {}", e);
+        // Keep the shortcuts in line with the settings that are still in effect
+        if let Some(hotkeys) = &hotkeys {
+            if let Err(e) = hotkeys.rebind(&app, &previous_hotkeys) {
+                error!("Failed to restore previous hotkeys: {}", e);
+            }
+        }
+        return Err(e.to_string());
+    }
+
+    let settings = state.settings.lock().await.clone();
+    let _ = app.emit("settings-changed", &settings);
+
+    Ok(settings)
+}
+
+/// Save the API key in the credential store
+///
+/// After this the key never needs to cross the IPC boundary again.
+#[command]
+pub async fn set_api_key(state: State<'_, AppState>, api_key: String) -> Result<(), String> {
+    info!("Saving API key");
+
+    state.set_api_key(&api_key).await.map_err(|e| {
+        error!("Failed to save API key: {}", e);
+        e.to_string()
+    })
+}
+
+/// Remove the API key from the credential store
+#[command]
+pub async fn clear_api_key(state: State<'_, AppState>) -> Result<(), String> {
+    info!("Clearing API key");
+
+    state.clear_api_key().await.map_err(|e| {
+        error!("Failed to clear API key: {}", e);
+        e.to_string()
+    })
+}
+
+/// Check whether an API key is configured, without revealing it
+#[command]
+pub async fn get_api_key_status(state: State<'_, AppState>) -> Result<ApiKeyStatus, String> {
+    let configured = state.api_key.lock().await.is_some();
+    let backend = state
+        .secrets
+        .lock()
+        .await
+        .as_ref()
+        .map(|secrets| secrets.backend().to_string());
+
+    Ok(ApiKeyStatus {
+        configured,
+        backend,
+    })
+}
+
+/// Default page size of history queries
+const HISTORY_PAGE_SIZE: u32 = 50;
+
+/// Run a query against the history database
+async fn with_history<T>(
+    state: &AppState,
+    query: impl FnOnce(&HistoryStore) -> anyhow::Result<T>,
+) -> Result<T, String> {
+    let history = state.history.lock().await;
+    let history = history
+        .as_ref()
+        .ok_or_else(|| "History not available".to_string())?;
+
+    query(history).map_err(|e| {
+        error!("History query failed: {}", e);
+        e.to_string()
+    })
+}
+
+/// Look up a history entry that must exist
+async fn history_entry(state: &AppState, id: i64) -> Result<HistoryEntry, String> {
+    with_history(state, |history| history.get(id))
+        .await?
+        .ok_or_else(|| format!("History entry {} not found", id))
+}
+
+/// List transcript history, newest first
+#[command]
+pub async fn list_history(
+    state: State<'_, AppState>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Vec<HistoryEntry>, String> {
+    let limit = limit.unwrap_or(HISTORY_PAGE_SIZE);
+    with_history(&state, |history| history.list(limit, offset.unwrap_or(0))).await
+}
+
+/// Full-text search of the transcript history
+#[command]
+pub async fn search_history(
+    state: State<'_, AppState>,
+    query: String,
+    limit: Option<u32>,
+) -> Result<Vec<HistoryEntry>, String> {
+    let limit = limit.unwrap_or(HISTORY_PAGE_SIZE);
+    with_history(&state, |history| history.search(&query, limit)).await
+}
+
+/// Copy a history entry to the clipboard
+#[command]
+pub async fn copy_history_entry(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<(), String> {
+    let entry = history_entry(&state, id).await?;
+
+    app.clipboard().write_text(entry.text).map_err(|e| {
+        error!("Failed to copy history entry: {}", e);
+        e.to_string()
+    })
+}
+
+/// Inject a history entry into the active application again
+#[command]
+pub async fn reinject_history_entry(
+    state: State<'_, AppState>,
+    id: i64,
+    strategy: Option<InjectionStrategy>,
+) -> Result<(), String> {
+    let entry = history_entry(&state, id).await?;
+    let strategy = match strategy {
+        Some(strategy) => Some(strategy),
+        None => state.settings.lock().await.injection_strategy,
+    };
+
+    let service = state
+        .text_injector_service
+        .lock()
+        .await
+        .clone()
+        .ok_or_else(|| "Text injector service not initialized".to_string())?;
+
+    info!("Re-injecting history entry {}", id);
+    service
+        .inject_text(entry.text, strategy)
+        .await
+        .map_err(|e| {
+            error!("Failed to inject history entry: {}", e);
+            e.to_string()
+        })
+}
+
+/// Delete a history entry
+#[command]
+pub async fn delete_history_entry(state: State<'_, AppState>, id: i64) -> Result<(), String> {
+    if !with_history(&state, |history| history.delete(id)).await? {
+        return Err(format!("History entry {} not found", id));
+    }
+    Ok(())
+}
+
+/// Delete the whole transcript history
+#[command]
+pub async fn clear_history(state: State<'_, AppState>) -> Result<(), String> {
+    info!("Clearing transcript history");
+    with_history(&state, |history| history.clear()).await
+}
+
+/// List the offline models and whether they're downloaded
+#[command]
+pub async fn list_whisper_models(state: State<'_, AppState>) -> Result<Vec<ModelStatus>, String> {
+    let whisper = state.whisper().await.map_err(|e| e.to_string())?;
+    Ok(whisper.models().list())
+}
+
+/// Download an offline model into the app data directory
+///
+/// Progress is emitted as `model-download-progress` events.
+#[command]
+pub async fn download_whisper_model(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    model: WhisperModel,
+) -> Result<(), String> {
+    let whisper = state.whisper().await.map_err(|e| e.to_string())?;
+
+    whisper
+        .models()
+        .download(model, |progress| {
+            let _ = app.emit("model-download-progress", &progress);
+        })
+        .await
+        .map_err(|e| {
+            error!("Failed to download model {}: {}", model.id(), e);
+            e.to_string()
+        })
+}
+
+/// Delete a downloaded offline model
+#[command]
+pub async fn delete_whisper_model(
+    state: State<'_, AppState>,
+    model: WhisperModel,
+) -> Result<(), String> {
+    let whisper = state.whisper().await.map_err(|e| e.to_string())?;
+
+    whisper.models().delete(model).await.map_err(|e| {
+        error!("Failed to delete model {}: {}", model.id(), e);
+        e.to_string()
+    })
+}
+
 /// Get information about the currently active window
 #[command]
 pub fn get_active_window_info() -> Result<WindowInfo, String> {
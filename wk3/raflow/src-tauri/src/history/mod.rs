@@ -0,0 +1,8 @@
+pub mod model;
+pub mod store;
+
+#[cfg(test)]
+mod tests;
+
+pub use model::{HistoryEntry, NewSegment};
+pub use store::HistoryStore;
@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::input::WindowInfo;
+
+/// A committed transcript segment stored in the history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    /// Dictation session the segment belongs to
+    pub session_id: i64,
+    pub created_at: DateTime<Utc>,
+    pub text: String,
+    /// Language detected by the service, if reported
+    pub language_code: Option<String>,
+    /// Service confidence for the segment, if reported
+    pub confidence: Option<f32>,
+    /// Window that was focused when the segment was committed
+    pub window: Option<WindowInfo>,
+}
+
+/// A segment to be added to the history
+#[derive(Debug, Clone)]
+pub struct NewSegment {
+    pub created_at: DateTime<Utc>,
+    pub text: String,
+    pub language_code: Option<String>,
+    pub confidence: Option<f32>,
+    pub window: Option<WindowInfo>,
+}
+
+impl NewSegment {
+    /// Create a segment committed now
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            created_at: Utc::now(),
+            text: text.into(),
+            language_code: None,
+            confidence: None,
+            window: None,
+        }
+    }
+
+    /// Set the detected language
+    pub fn with_language(mut self, language_code: Option<String>) -> Self {
+        self.language_code = language_code;
+        self
+    }
+
+    /// Set the service confidence
+    pub fn with_confidence(mut self, confidence: Option<f32>) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    /// Set the focused window
+    pub fn with_window(mut self, window: Option<WindowInfo>) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Whether there's no text worth keeping, as in the commits sent for
+    /// silence or when recording stops
+    pub fn is_blank(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+}
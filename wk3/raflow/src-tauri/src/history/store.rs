@@ -0,0 +1,311 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use std::path::Path;
+use tracing::info;
+
+use super::model::{HistoryEntry, NewSegment};
+use crate::input::WindowInfo;
+
+/// File name of the history database inside the app data directory
+pub const HISTORY_FILE: &str = "history.db";
+
+/// Current schema version, kept in `PRAGMA user_version`
+const SCHEMA_VERSION: i32 = 1;
+
+/// Shortest term the trigram index can match, shorter ones use `LIKE`
+const MIN_FTS_TERM_CHARS: usize = 3;
+
+const SCHEMA_V1: &str = "
+    CREATE TABLE sessions (
+        id INTEGER PRIMARY KEY,
+        started_at INTEGER NOT NULL,
+        ended_at INTEGER,
+        model_id TEXT NOT NULL,
+        language_code TEXT
+    );
+
+    CREATE TABLE segments (
+        id INTEGER PRIMARY KEY,
+        session_id INTEGER NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        created_at INTEGER NOT NULL,
+        text TEXT NOT NULL,
+        language_code TEXT,
+        confidence REAL,
+        app_name TEXT,
+        window_title TEXT,
+        process_id INTEGER
+    );
+
+    CREATE INDEX segments_created_at ON segments(created_at);
+    CREATE INDEX segments_session_id ON segments(session_id);
+
+    -- Trigram tokens match inside words, which also covers CJK text
+    CREATE VIRTUAL TABLE segments_fts USING fts5(
+        text, content='segments', content_rowid='id', tokenize='trigram'
+    );
+
+    CREATE TRIGGER segments_ai AFTER INSERT ON segments BEGIN
+        INSERT INTO segments_fts(rowid, text) VALUES (new.id, new.text);
+    END;
+    CREATE TRIGGER segments_ad AFTER DELETE ON segments BEGIN
+        INSERT INTO segments_fts(segments_fts, rowid, text) VALUES ('delete', old.id, old.text);
+    END;
+    CREATE TRIGGER segments_au AFTER UPDATE ON segments BEGIN
+        INSERT INTO segments_fts(segments_fts, rowid, text) VALUES ('delete', old.id, old.text);
+        INSERT INTO segments_fts(rowid, text) VALUES (new.id, new.text);
+    END;
+";
+
+const SELECT_ENTRY: &str = "
+    SELECT s.id, s.session_id, s.created_at, s.text, s.language_code, s.confidence,
+           s.app_name, s.window_title, s.process_id
+    FROM segments s";
+
+/// Transcript history kept in a local SQLite database
+pub struct HistoryStore {
+    conn: Connection,
+}
+
+impl HistoryStore {
+    /// Open (or create) the history database
+    ///
+    /// # Arguments
+    /// * `data_dir` - Directory the database file lives in
+    pub fn open(data_dir: &Path) -> Result<Self> {
+        let path = data_dir.join(HISTORY_FILE);
+        let conn = Connection::open(&path)
+            .map_err(|e| anyhow!("Failed to open history {}: {}", path.display(), e))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+
+        let store = Self::init(conn)?;
+        info!("History database opened: {}", path.display());
+        Ok(store)
+    }
+
+    /// Open a throwaway in-memory database
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+
+        let version: i32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            return Err(anyhow!(
+                "History database version {} is newer than supported ({})",
+                version,
+                SCHEMA_VERSION
+            ));
+        }
+        if version < 1 {
+            conn.execute_batch(SCHEMA_V1)?;
+            conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        }
+
+        Ok(Self { conn })
+    }
+
+    /// Record the start of a dictation session
+    ///
+    /// # Returns
+    /// Id of the new session
+    pub fn start_session(&self, model_id: &str, language_code: Option<&str>) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO sessions (started_at, model_id, language_code) VALUES (?1, ?2, ?3)",
+            params![Utc::now().timestamp_millis(), model_id, language_code],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Record the end of a dictation session
+    ///
+    /// Sessions without any segment are removed.
+    pub fn end_session(&self, session_id: i64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE sessions SET ended_at = ?1 WHERE id = ?2",
+            params![Utc::now().timestamp_millis(), session_id],
+        )?;
+        self.conn.execute(
+            "DELETE FROM sessions WHERE id = ?1
+             AND NOT EXISTS (SELECT 1 FROM segments WHERE session_id = ?1)",
+            params![session_id],
+        )?;
+        Ok(())
+    }
+
+    /// Add a committed segment to a session
+    ///
+    /// # Returns
+    /// Id of the new entry
+    pub fn add_segment(&self, session_id: i64, segment: &NewSegment) -> Result<i64> {
+        let window = segment.window.as_ref();
+        self.conn.execute(
+            "INSERT INTO segments (session_id, created_at, text, language_code, confidence,
+                                   app_name, window_title, process_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                session_id,
+                segment.created_at.timestamp_millis(),
+                segment.text,
+                segment.language_code,
+                segment.confidence,
+                window.map(|w| w.app_name.as_str()),
+                window.map(|w| w.title.as_str()),
+                window.map(|w| w.process_id),
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Get a single entry
+    pub fn get(&self, id: i64) -> Result<Option<HistoryEntry>> {
+        let sql = format!("{} WHERE s.id = ?1", SELECT_ENTRY);
+        Ok(self
+            .conn
+            .query_row(&sql, params![id], entry_from_row)
+            .optional()?)
+    }
+
+    /// List entries, newest first
+    pub fn list(&self, limit: u32, offset: u32) -> Result<Vec<HistoryEntry>> {
+        let sql = format!(
+            "{} ORDER BY s.created_at DESC, s.id DESC LIMIT ?1 OFFSET ?2",
+            SELECT_ENTRY
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let entries = stmt
+            .query_map(params![limit, offset], entry_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+
+    /// Full-text search, matching entries that contain every term
+    ///
+    /// Results are ranked by relevance when the full-text index can be
+    /// used and ordered newest first otherwise.
+    pub fn search(&self, query: &str, limit: u32) -> Result<Vec<HistoryEntry>> {
+        let terms: Vec<&str> = query.split_whitespace().collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let use_fts = terms
+            .iter()
+            .all(|term| term.chars().count() >= MIN_FTS_TERM_CHARS);
+
+        let (sql, args) = if use_fts {
+            let query = terms
+                .iter()
+                .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let sql = format!(
+                "{} JOIN segments_fts ON segments_fts.rowid = s.id
+                 WHERE segments_fts MATCH ?1 ORDER BY segments_fts.rank LIMIT ?2",
+                SELECT_ENTRY
+            );
+            (sql, vec![query])
+        } else {
+            let conditions = (1..=terms.len())
+                .map(|i| format!("s.text LIKE ?{} ESCAPE '\\'", i))
+                .collect::<Vec<_>>()
+                .join(" AND ");
+            let sql = format!(
+                "{} WHERE {} ORDER BY s.created_at DESC, s.id DESC LIMIT ?{}",
+                SELECT_ENTRY,
+                conditions,
+                terms.len() + 1
+            );
+            let args = terms
+                .iter()
+                .map(|term| format!("%{}%", escape_like(term)))
+                .collect();
+            (sql, args)
+        };
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let params = args
+            .iter()
+            .map(|arg| arg as &dyn rusqlite::ToSql)
+            .chain(std::iter::once(&limit as &dyn rusqlite::ToSql));
+        let entries = stmt
+            .query_map(params_from_iter(params), entry_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+
+    /// Delete an entry
+    ///
+    /// # Returns
+    /// `false` if no entry had this id
+    pub fn delete(&self, id: i64) -> Result<bool> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM segments WHERE id = ?1", params![id])?;
+        self.delete_empty_sessions()?;
+        Ok(deleted > 0)
+    }
+
+    /// Delete all entries and finished sessions
+    pub fn clear(&self) -> Result<()> {
+        self.conn.execute("DELETE FROM segments", [])?;
+        self.delete_empty_sessions()
+    }
+
+    /// Delete entries committed before `cutoff`
+    ///
+    /// # Returns
+    /// Number of deleted entries
+    pub fn prune(&self, cutoff: DateTime<Utc>) -> Result<usize> {
+        let deleted = self.conn.execute(
+            "DELETE FROM segments WHERE created_at < ?1",
+            params![cutoff.timestamp_millis()],
+        )?;
+        self.delete_empty_sessions()?;
+        Ok(deleted)
+    }
+
+    // Running sessions are kept, they may still receive segments
+    fn delete_empty_sessions(&self) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM sessions WHERE ended_at IS NOT NULL
+             AND NOT EXISTS (SELECT 1 FROM segments WHERE session_id = sessions.id)",
+            [],
+        )?;
+        Ok(())
+    }
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<HistoryEntry> {
+    let created_at: i64 = row.get(2)?;
+    let app_name: Option<String> = row.get(6)?;
+    let window = match app_name {
+        Some(app_name) => Some(WindowInfo {
+            app_name,
+            title: row.get::<_, Option<String>>(7)?.unwrap_or_default(),
+            process_id: row.get::<_, Option<u32>>(8)?.unwrap_or_default(),
+        }),
+        None => None,
+    };
+
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        created_at: Utc
+            .timestamp_millis_opt(created_at)
+            .single()
+            .unwrap_or_default(),
+        text: row.get(3)?,
+        language_code: row.get(4)?,
+        confidence: row.get(5)?,
+        window,
+    })
+}
+
+fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
@@ -0,0 +1,154 @@
+use super::*;
+
+#[cfg(test)]
+mod store_tests {
+    use super::*;
+    use crate::input::WindowInfo;
+    use chrono::{Duration, Utc};
+
+    fn store_with(texts: &[&str]) -> (HistoryStore, i64) {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let session = store
+            .start_session("scribe_v2_realtime", Some("eng"))
+            .unwrap();
+        for text in texts {
+            store.add_segment(session, &NewSegment::new(*text)).unwrap();
+        }
+        (store, session)
+    }
+
+    fn texts(entries: &[HistoryEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_segment_roundtrip() {
+        let (store, session) = store_with(&[]);
+        let window = WindowInfo {
+            app_name: "Notes".to_string(),
+            title: "Groceries".to_string(),
+            process_id: 42,
+        };
+
+        let id = store
+            .add_segment(
+                session,
+                &NewSegment::new("buy milk")
+                    .with_language(Some("eng".to_string()))
+                    .with_confidence(Some(0.75))
+                    .with_window(Some(window.clone())),
+            )
+            .unwrap();
+
+        let entry = store.get(id).unwrap().unwrap();
+        assert_eq!(entry.session_id, session);
+        assert_eq!(entry.text, "buy milk");
+        assert_eq!(entry.language_code.as_deref(), Some("eng"));
+        assert_eq!(entry.confidence, Some(0.75));
+        assert_eq!(entry.window, Some(window));
+        assert!(store.get(id + 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_list_is_newest_first() {
+        let (store, _) = store_with(&["first", "second", "third"]);
+
+        assert_eq!(
+            texts(&store.list(10, 0).unwrap()),
+            ["third", "second", "first"]
+        );
+        assert_eq!(texts(&store.list(1, 1).unwrap()), ["second"]);
+    }
+
+    #[test]
+    fn test_search_matches_all_terms() {
+        let (store, _) = store_with(&[
+            "schedule the meeting for Monday",
+            "the meeting moved to Tuesday",
+            "lunch on Monday",
+        ]);
+
+        assert_eq!(
+            texts(&store.search("meeting monday", 10).unwrap()),
+            ["schedule the meeting for Monday"]
+        );
+        assert_eq!(store.search("meet", 10).unwrap().len(), 2);
+        assert!(store.search("   ", 10).unwrap().is_empty());
+        // Query syntax is treated as plain text
+        assert!(store.search("NEAR(meeting \"the", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_short_and_cjk_terms() {
+        let (store, _) = store_with(&["今天天气不错", "明天下雨", "50% off"]);
+
+        assert_eq!(texts(&store.search("天气", 10).unwrap()), ["今天天气不错"]);
+        assert_eq!(
+            texts(&store.search("今天天气", 10).unwrap()),
+            ["今天天气不错"]
+        );
+        assert_eq!(texts(&store.search("0%", 10).unwrap()), ["50% off"]);
+    }
+
+    #[test]
+    fn test_delete_removes_from_search() {
+        let (store, session) = store_with(&["keep this", "remove this"]);
+        let removed = store.search("remove", 10).unwrap()[0].id;
+
+        assert!(store.delete(removed).unwrap());
+        assert!(!store.delete(removed).unwrap());
+        assert!(store.search("remove", 10).unwrap().is_empty());
+        assert_eq!(texts(&store.list(10, 0).unwrap()), ["keep this"]);
+
+        store.end_session(session).unwrap();
+        store.clear().unwrap();
+        assert!(store.list(10, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_prune_applies_retention() {
+        let (store, session) = store_with(&["recent"]);
+        let old = NewSegment {
+            created_at: Utc::now() - Duration::days(40),
+            ..NewSegment::new("old")
+        };
+        store.add_segment(session, &old).unwrap();
+
+        let deleted = store.prune(Utc::now() - Duration::days(30)).unwrap();
+        assert_eq!(deleted, 1);
+        assert_eq!(texts(&store.list(10, 0).unwrap()), ["recent"]);
+    }
+
+    #[test]
+    fn test_empty_sessions_are_dropped() {
+        let (store, session) = store_with(&[]);
+        store.end_session(session).unwrap();
+
+        // The session row is gone, so adding to it violates the foreign key
+        assert!(store
+            .add_segment(session, &NewSegment::new("late"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_blank_segments() {
+        assert!(NewSegment::new("").is_blank());
+        assert!(NewSegment::new(" \n\t").is_blank());
+        assert!(!NewSegment::new(" ok ").is_blank());
+    }
+
+    #[test]
+    fn test_reopen_keeps_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let store = HistoryStore::open(dir.path()).unwrap();
+            let session = store.start_session("scribe_v2_realtime", None).unwrap();
+            store
+                .add_segment(session, &NewSegment::new("persisted"))
+                .unwrap();
+        }
+
+        let store = HistoryStore::open(dir.path()).unwrap();
+        assert_eq!(texts(&store.search("persist", 10).unwrap()), ["persisted"]);
+    }
+}
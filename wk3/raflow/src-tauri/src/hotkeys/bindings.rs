@@ -0,0 +1,91 @@
+use std::str::FromStr;
+use tauri_plugin_global_shortcut::Shortcut;
+
+use crate::settings::HotkeySettings;
+use crate::utils::RAFlowError;
+
+/// What a global shortcut does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    /// Record while the keys are held down
+    PushToTalk,
+    /// Start recording on first press, stop on the second
+    Toggle,
+}
+
+impl HotkeyAction {
+    /// Human readable name used in error messages
+    pub fn label(&self) -> &'static str {
+        match self {
+            HotkeyAction::PushToTalk => "push-to-talk",
+            HotkeyAction::Toggle => "toggle",
+        }
+    }
+}
+
+/// Parsed shortcuts of the hotkey settings
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HotkeyBindings {
+    bindings: Vec<(HotkeyAction, Shortcut)>,
+}
+
+impl HotkeyBindings {
+    /// Parse the hotkey settings
+    ///
+    /// # Returns
+    /// A config error if a shortcut can't be parsed or both actions use
+    /// the same keys
+    pub fn from_settings(settings: &HotkeySettings) -> Result<Self, RAFlowError> {
+        let mut bindings: Vec<(HotkeyAction, Shortcut)> = Vec::new();
+
+        let entries = [
+            (HotkeyAction::PushToTalk, &settings.push_to_talk),
+            (HotkeyAction::Toggle, &settings.toggle),
+        ];
+        for (action, accelerator) in entries {
+            let Some(accelerator) = accelerator.as_deref() else {
+                continue;
+            };
+
+            let shortcut = Shortcut::from_str(accelerator.trim()).map_err(|e| {
+                RAFlowError::Config(format!(
+                    "Invalid {} hotkey \"{}\": {}",
+                    action.label(),
+                    accelerator,
+                    e
+                ))
+            })?;
+
+            if let Some((other, _)) = bindings.iter().find(|(_, s)| *s == shortcut) {
+                return Err(RAFlowError::Config(format!(
+                    "The {} and {} hotkeys both use {}",
+                    other.label(),
+                    action.label(),
+                    accelerator
+                )));
+            }
+
+            bindings.push((action, shortcut));
+        }
+
+        Ok(Self { bindings })
+    }
+
+    /// Find the action bound to a shortcut
+    pub fn action_for(&self, shortcut: &Shortcut) -> Option<HotkeyAction> {
+        self.bindings
+            .iter()
+            .find(|(_, s)| s == shortcut)
+            .map(|(action, _)| *action)
+    }
+
+    /// All bound actions and their shortcuts
+    pub fn iter(&self) -> impl Iterator<Item = &(HotkeyAction, Shortcut)> {
+        self.bindings.iter()
+    }
+
+    /// Whether no shortcut is bound
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+}
@@ -0,0 +1,190 @@
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
+
+use super::bindings::{HotkeyAction, HotkeyBindings};
+use crate::commands::{start_reserved_dictation, stop_dictation};
+use crate::settings::HotkeySettings;
+use crate::state::AppState;
+use crate::utils::RAFlowError;
+
+/// Global push-to-talk and toggle shortcuts
+///
+/// Shortcut events arrive on the main thread and are handed to a single
+/// worker task, so a key release can never overtake the press that
+/// started the session.
+pub struct HotkeyManager {
+    bindings: Mutex<HotkeyBindings>,
+    events: mpsc::UnboundedSender<(HotkeyAction, ShortcutState)>,
+}
+
+impl HotkeyManager {
+    /// Create the manager and spawn its worker task
+    ///
+    /// No shortcut is registered until [`HotkeyManager::rebind`] is called.
+    pub fn new(app: AppHandle, state: AppState) -> Self {
+        let (events, event_rx) = mpsc::unbounded_channel();
+        tauri::async_runtime::spawn(run_worker(app, state, event_rx));
+
+        Self {
+            bindings: Mutex::new(HotkeyBindings::default()),
+            events,
+        }
+    }
+
+    /// Called by the global shortcut plugin for every key event
+    pub fn handle_shortcut(&self, shortcut: &Shortcut, key_state: ShortcutState) {
+        let action = self.bindings.lock().unwrap().action_for(shortcut);
+        if let Some(action) = action {
+            let _ = self.events.send((action, key_state));
+        }
+    }
+
+    /// Register the shortcuts of the given settings, replacing the old ones
+    ///
+    /// When a shortcut can't be registered (usually because another
+    /// application owns it) the previous shortcuts are restored.
+    ///
+    /// # Returns
+    /// A config error describing the conflict
+    pub fn rebind(&self, app: &AppHandle, settings: &HotkeySettings) -> Result<(), RAFlowError> {
+        let new = HotkeyBindings::from_settings(settings)?;
+
+        // Don't hold the lock while registering: the plugin dispatches to the
+        // main thread, which may be waiting on it in `handle_shortcut`
+        let old = self.bindings.lock().unwrap().clone();
+        if old == new {
+            return Ok(());
+        }
+
+        unregister(app, &old);
+        if let Err(e) = register(app, &new) {
+            if let Err(restore) = register(app, &old) {
+                error!("Failed to restore previous hotkeys: {}", restore);
+            }
+            return Err(e);
+        }
+
+        *self.bindings.lock().unwrap() = new;
+        Ok(())
+    }
+}
+
+/// Register all shortcuts, rolling back the ones already registered on failure
+fn register(app: &AppHandle, bindings: &HotkeyBindings) -> Result<(), RAFlowError> {
+    let shortcuts = app.global_shortcut();
+    let mut registered = Vec::new();
+
+    for (action, shortcut) in bindings.iter() {
+        if let Err(e) = shortcuts.register(*shortcut) {
+            warn!(
+                "Failed to register {} hotkey {}: {}",
+                action.label(),
+                shortcut,
+                e
+            );
+            for shortcut in registered {
+                let _ = shortcuts.unregister(shortcut);
+            }
+            return Err(RAFlowError::Config(format!(
+                "The {} hotkey {} is already in use by another application",
+                action.label(),
+                shortcut
+            )));
+        }
+        registered.push(*shortcut);
+        info!("⌨️  Registered {} hotkey: {}", action.label(), shortcut);
+    }
+
+    Ok(())
+}
+
+fn unregister(app: &AppHandle, bindings: &HotkeyBindings) {
+    let shortcuts = app.global_shortcut();
+    for (action, shortcut) in bindings.iter() {
+        if let Err(e) = shortcuts.unregister(*shortcut) {
+            warn!(
+                "Failed to unregister {} hotkey {}: {}",
+                action.label(),
+                shortcut,
+                e
+            );
+        }
+    }
+}
+
+/// Turn shortcut events into recording starts and stops
+async fn run_worker(
+    app: AppHandle,
+    state: AppState,
+    mut events: mpsc::UnboundedReceiver<(HotkeyAction, ShortcutState)>,
+) {
+    // Whether the running session was started by holding push-to-talk,
+    // so releasing it doesn't stop a session started with the toggle key
+    let mut push_to_talk_active = false;
+
+    while let Some((action, key_state)) = events.recv().await {
+        // A session that is still connecting counts as recording, so
+        // stopping it cancels the start
+        let recording = *state.is_recording.lock().await || state.is_session_starting().await;
+        debug!(
+            "Hotkey {:?} {:?} (recording: {})",
+            action, key_state, recording
+        );
+
+        let result = match (action, key_state) {
+            // Key repeat sends more presses while the keys are held
+            (HotkeyAction::PushToTalk, ShortcutState::Pressed) if !recording => {
+                push_to_talk_active = true;
+                spawn_start(&app, &state).await
+            }
+            (HotkeyAction::PushToTalk, ShortcutState::Released) if push_to_talk_active => {
+                push_to_talk_active = false;
+                if recording {
+                    stop_dictation(&app, &state).await
+                } else {
+                    Ok(())
+                }
+            }
+            (HotkeyAction::Toggle, ShortcutState::Pressed) => {
+                push_to_talk_active = false;
+                if recording {
+                    stop_dictation(&app, &state).await
+                } else {
+                    spawn_start(&app, &state).await
+                }
+            }
+            _ => Ok(()),
+        };
+
+        if let Err(e) = result {
+            error!("Hotkey {} failed: {}", action.label(), e);
+            push_to_talk_active = false;
+            let _ = app.emit("transcript-error", e);
+        }
+    }
+}
+
+/// Start a session without waiting for it to connect
+///
+/// The start is reserved right away, so the next shortcut event already
+/// sees it, while the connection is made in the background so a release
+/// arriving meanwhile can cancel it.
+async fn spawn_start(app: &AppHandle, state: &AppState) -> Result<(), String> {
+    state
+        .begin_session_start()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let app = app.clone();
+    let state = state.clone();
+    tokio::spawn(async move {
+        if let Err(e) = start_reserved_dictation(&app, &state, None, None).await {
+            error!("Hotkey failed to start recording: {}", e);
+            let _ = app.emit("transcript-error", e);
+        }
+    });
+    Ok(())
+}
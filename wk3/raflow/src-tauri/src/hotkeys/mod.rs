@@ -0,0 +1,8 @@
+pub mod bindings;
+pub mod manager;
+
+#[cfg(test)]
+mod tests;
+
+pub use bindings::{HotkeyAction, HotkeyBindings};
+pub use manager::HotkeyManager;
@@ -0,0 +1,61 @@
+use super::*;
+
+#[cfg(test)]
+mod bindings_tests {
+    use super::*;
+    use crate::settings::HotkeySettings;
+    use std::str::FromStr;
+    use tauri_plugin_global_shortcut::Shortcut;
+
+    fn hotkeys(push_to_talk: Option<&str>, toggle: Option<&str>) -> HotkeySettings {
+        HotkeySettings {
+            push_to_talk: push_to_talk.map(str::to_string),
+            toggle: toggle.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_default_hotkeys_parse() {
+        let bindings = HotkeyBindings::from_settings(&HotkeySettings::default()).unwrap();
+        assert_eq!(bindings.iter().count(), 2);
+
+        let push_to_talk = Shortcut::from_str("Alt+Space").unwrap();
+        assert_eq!(
+            bindings.action_for(&push_to_talk),
+            Some(HotkeyAction::PushToTalk)
+        );
+    }
+
+    #[test]
+    fn test_unbound_actions_are_skipped() {
+        let bindings = HotkeyBindings::from_settings(&hotkeys(None, Some("F9"))).unwrap();
+        assert_eq!(bindings.iter().count(), 1);
+        assert_eq!(
+            bindings.action_for(&Shortcut::from_str("F9").unwrap()),
+            Some(HotkeyAction::Toggle)
+        );
+        assert_eq!(
+            bindings.action_for(&Shortcut::from_str("F10").unwrap()),
+            None
+        );
+
+        assert!(HotkeyBindings::from_settings(&hotkeys(None, None))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_invalid_hotkey_is_rejected() {
+        let err = HotkeyBindings::from_settings(&hotkeys(Some("Alt+Nope"), None)).unwrap_err();
+        assert!(err.to_string().contains("push-to-talk"));
+    }
+
+    #[test]
+    fn test_conflicting_hotkeys_are_rejected() {
+        // Same keys written differently still conflict
+        let err =
+            HotkeyBindings::from_settings(&hotkeys(Some("Shift+Alt+KeyD"), Some("alt+shift+d")))
+                .unwrap_err();
+        assert!(err.to_string().contains("both use"));
+    }
+}
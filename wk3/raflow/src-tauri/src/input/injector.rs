@@ -1,5 +1,6 @@
 use anyhow::{anyhow, Result};
 use enigo::{Enigo, Key, Keyboard, Settings};
+use serde::{Deserialize, Serialize};
 use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
@@ -19,7 +20,8 @@ pub enum InjectionRequest {
     },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum InjectionStrategy {
     /// Type text character by character
     Keyboard,
@@ -63,11 +65,7 @@ impl TextInjector {
     }
 
     /// Inject text using clipboard (copy-paste)
-    pub async fn inject_via_clipboard(
-        &mut self,
-        app: &AppHandle,
-        text: &str,
-    ) -> Result<()> {
+    pub async fn inject_via_clipboard(&mut self, app: &AppHandle, text: &str) -> Result<()> {
         info!("Injecting text via clipboard: {} chars", text.len());
 
         // 1. Backup current clipboard content
@@ -275,7 +273,6 @@ impl TextInjectorService {
     }
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
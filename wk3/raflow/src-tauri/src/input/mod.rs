@@ -1,8 +1,10 @@
 pub mod injector;
+pub mod spacing;
 pub mod window;
 
 #[cfg(test)]
 mod tests;
 
-pub use injector::{InjectionStrategy, TextInjector, TextInjectorService};
+pub use injector::{InjectionStrategy, TextInjectorService};
+pub use spacing::SmartSpacer;
 pub use window::{get_active_window, WindowInfo};
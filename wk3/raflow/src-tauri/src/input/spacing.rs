@@ -0,0 +1,79 @@
+/// Joins committed transcript segments the way a person would type them
+///
+/// Latin words get a single space between segments, while CJK text,
+/// punctuation and brackets are joined without one.
+#[derive(Debug, Default, Clone)]
+pub struct SmartSpacer {
+    last_char: Option<char>,
+}
+
+impl SmartSpacer {
+    /// Create a spacer for a new dictation
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget the previous segment, e.g. when the focused field changes
+    pub fn reset(&mut self) {
+        self.last_char = None;
+    }
+
+    /// Prepare the next segment for injection
+    ///
+    /// # Arguments
+    /// * `segment` - Committed transcript text
+    ///
+    /// # Returns
+    /// The text to inject, with a leading space if one is needed, or
+    /// `None` if the segment is blank
+    pub fn next(&mut self, segment: &str) -> Option<String> {
+        let segment = segment.trim();
+        let first = segment.chars().next()?;
+
+        let needs_space = self
+            .last_char
+            .is_some_and(|last| needs_space_between(last, first));
+        self.last_char = segment.chars().last();
+
+        Some(if needs_space {
+            format!(" {}", segment)
+        } else {
+            segment.to_string()
+        })
+    }
+}
+
+fn needs_space_between(last: char, first: char) -> bool {
+    if last.is_whitespace() || is_cjk(last) || is_cjk(first) {
+        return false;
+    }
+    if is_opening(last) || is_closing(first) {
+        return false;
+    }
+    true
+}
+
+/// Scripts written without spaces between words
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3000}'..='\u{303F}'     // CJK symbols and punctuation
+        | '\u{3040}'..='\u{30FF}'   // Hiragana, Katakana
+        | '\u{3400}'..='\u{4DBF}'   // CJK extension A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK unified ideographs
+        | '\u{F900}'..='\u{FAFF}'   // CJK compatibility ideographs
+        | '\u{FF00}'..='\u{FFEF}'   // Full-width forms
+    )
+}
+
+/// Characters that attach to the text after them
+fn is_opening(c: char) -> bool {
+    matches!(c, '(' | '[' | '{' | '“' | '‘' | '¿' | '¡' | '/' | '-')
+}
+
+/// Characters that attach to the text before them
+fn is_closing(c: char) -> bool {
+    matches!(
+        c,
+        '.' | ',' | '!' | '?' | ';' | ':' | ')' | ']' | '}' | '\'' | '”' | '’' | '%' | '…'
+    )
+}
@@ -2,7 +2,6 @@ use super::*;
 
 #[cfg(test)]
 mod window_tests {
-    use super::*;
     use crate::input::window::WindowInfo;
 
     #[test]
@@ -43,13 +42,11 @@ mod window_tests {
 
 #[cfg(test)]
 mod injector_tests {
-    use super::*;
-    use crate::input::injector::InjectionStrategy;
     use crate::input::window::WindowInfo;
 
     #[test]
     fn test_injection_strategy_selection_short_text() {
-        let window = WindowInfo {
+        let _window = WindowInfo {
             app_name: "Safari".to_string(),
             title: "Google".to_string(),
             process_id: 1234,
@@ -62,7 +59,7 @@ mod injector_tests {
 
     #[test]
     fn test_injection_strategy_selection_long_text() {
-        let window = WindowInfo {
+        let _window = WindowInfo {
             app_name: "Safari".to_string(),
             title: "Google".to_string(),
             process_id: 1234,
@@ -102,7 +99,6 @@ mod injector_tests {
 
 #[cfg(test)]
 mod strategy_tests {
-    use super::*;
     use crate::input::injector::InjectionStrategy;
 
     #[test]
@@ -152,3 +148,54 @@ mod text_sanitization_tests {
         assert_eq!(text.trim().len(), 0);
     }
 }
+
+#[cfg(test)]
+mod spacing_tests {
+    use super::*;
+
+    fn join(segments: &[&str]) -> String {
+        let mut spacer = SmartSpacer::new();
+        segments.iter().filter_map(|s| spacer.next(s)).collect()
+    }
+
+    #[test]
+    fn test_first_segment_has_no_leading_space() {
+        let mut spacer = SmartSpacer::new();
+        assert_eq!(
+            spacer.next("  Hello world "),
+            Some("Hello world".to_string())
+        );
+    }
+
+    #[test]
+    fn test_latin_segments_are_separated() {
+        assert_eq!(
+            join(&["Hello world.", "How are you?"]),
+            "Hello world. How are you?"
+        );
+        assert_eq!(join(&["so I said", "yes"]), "so I said yes");
+    }
+
+    #[test]
+    fn test_punctuation_attaches_to_previous_segment() {
+        assert_eq!(join(&["Hello", ", world", "!"]), "Hello, world!");
+        assert_eq!(join(&["call me (", "maybe", ")"]), "call me (maybe)");
+    }
+
+    #[test]
+    fn test_cjk_segments_are_not_separated() {
+        assert_eq!(join(&["你好。", "今天天气不错"]), "你好。今天天气不错");
+        assert_eq!(join(&["我用", "Rust", "写代码"]), "我用Rust写代码");
+    }
+
+    #[test]
+    fn test_blank_segments_are_skipped() {
+        let mut spacer = SmartSpacer::new();
+        assert_eq!(spacer.next("one"), Some("one".to_string()));
+        assert_eq!(spacer.next("   "), None);
+        assert_eq!(spacer.next("two"), Some(" two".to_string()));
+
+        spacer.reset();
+        assert_eq!(spacer.next("three"), Some("three".to_string()));
+    }
+}
@@ -1,8 +1,9 @@
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+#[cfg(target_os = "macos")]
 use tracing::debug;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WindowInfo {
     pub app_name: String,
     pub title: String,
@@ -27,15 +28,15 @@ pub fn get_active_window() -> Result<WindowInfo> {
                     process_id: window.process_id as u32,
                 })
             }
-            Err(e) => {
-                Err(anyhow!("Failed to get active window: {:?}", e))
-            }
+            Err(e) => Err(anyhow!("Failed to get active window: {:?}", e)),
         }
     }
 
     #[cfg(not(target_os = "macos"))]
     {
-        Err(anyhow!("Active window detection is only supported on macOS"))
+        Err(anyhow!(
+            "Active window detection is only supported on macOS"
+        ))
     }
 }
 
@@ -61,9 +62,7 @@ pub fn is_code_editor(window: &WindowInfo) -> bool {
         "Vim",
         "Emacs",
     ];
-    code_apps
-        .iter()
-        .any(|&app| window.app_name.contains(app))
+    code_apps.iter().any(|&app| window.app_name.contains(app))
 }
 
 #[cfg(test)]
@@ -1,18 +1,56 @@
 mod audio;
 mod commands;
+mod history;
+mod hotkeys;
 mod input;
 mod network;
+mod secrets;
+mod session;
+mod settings;
 mod state;
 mod utils;
+mod whisper;
 
 use anyhow::Result;
-use tauri::{menu::{Menu, MenuItem}, tray::TrayIconBuilder, Manager};
-use tracing_subscriber::{EnvFilter, fmt, prelude::*};
+use tauri::{
+    menu::{Menu, MenuItem},
+    tray::TrayIconBuilder,
+    Emitter, Manager,
+};
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
+pub use audio::{
+    AdaptiveVad, AudioBuffer, AudioResampler, BatchProcessor, ChannelMode, DeviceMonitor,
+    NoiseFloor, SileroVad, Vad, VadEngine, VoiceActivityDetector,
+};
+pub use history::{HistoryEntry, HistoryStore, NewSegment};
+pub use hotkeys::{HotkeyAction, HotkeyBindings, HotkeyManager};
+pub use network::{
+    AudioEncoding, AudioSink, ClientMessage, ConnectionState, ElevenLabsProvider, MessageBatcher,
+    ProviderConnection, ProviderKind, RetryPolicy, ServerMessage, SessionOptions,
+    SpeechToTextProvider, TranscriptEvent, WebSocketClient, WordKind, WordTiming,
+};
+#[cfg(feature = "test-support")]
+pub use network::{
+    MockConnection, MockReply, MockScribeServer, MockScribeServerBuilder, MockTrigger,
+    ReceivedChunk,
+};
+pub use secrets::{EncryptedFileStore, KeychainStore, SecretManager, SecretStore};
+pub use session::{DictationSession, SessionConfig, SessionEvent};
+pub use settings::{
+    HistorySettings, HotkeySettings, MetricsExporterSettings, NoiseSuppressionSettings,
+    OfflineSettings, Settings, SettingsStore,
+};
 pub use state::AppState;
-pub use utils::{RAFlowError, RecoveryStrategy, Metrics, PerformanceMetrics};
-pub use network::RetryPolicy;
-pub use audio::AudioBuffer;
+#[cfg(feature = "prometheus")]
+pub use utils::MetricsExporter;
+pub use utils::{
+    ErrorContext, Metrics, PerformanceMetrics, RAFlowError, RecoveryStrategy, ResourceSampler,
+};
+pub use whisper::{
+    DownloadProgress, ModelManager, ModelStatus, Transcriber, Transcription, WhisperEngine,
+    WhisperModel, WhisperProvider,
+};
 
 const APP_PATH: &str = "raflow";
 
@@ -37,15 +75,40 @@ pub fn run() -> Result<()> {
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(|app, shortcut, event| {
+                    if let Some(hotkeys) = app.try_state::<HotkeyManager>() {
+                        hotkeys.handle_shortcut(shortcut, event.state());
+                    }
+                })
+                .build(),
+        )
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_store::Builder::default().build())
         .invoke_handler(tauri::generate_handler![
             commands::list_audio_devices,
             commands::start_recording,
             commands::stop_recording,
+            commands::pause_recording,
+            commands::resume_recording,
             commands::get_transcript_status,
+            commands::get_settings,
+            commands::update_settings,
+            commands::set_api_key,
+            commands::clear_api_key,
+            commands::get_api_key_status,
+            commands::list_history,
+            commands::search_history,
+            commands::copy_history_entry,
+            commands::reinject_history_entry,
+            commands::delete_history_entry,
+            commands::clear_history,
+            commands::list_whisper_models,
+            commands::download_whisper_model,
+            commands::delete_whisper_model,
             commands::inject_text,
             commands::get_active_window_info,
             commands::check_permissions,
@@ -54,7 +117,20 @@ pub fn run() -> Result<()> {
             commands::log_performance_metrics,
             commands::check_system_health,
         ])
-        .setup(|app| {
+        .setup(move |app| {
+            // Load credentials and saved settings before the frontend asks for them
+            tauri::async_runtime::block_on(state.init_secrets(&app_path));
+            if let Err(e) =
+                tauri::async_runtime::block_on(state.init_settings(app.handle(), &app_path))
+            {
+                tracing::error!("Failed to load settings, using defaults: {}", e);
+            }
+            if let Err(e) = tauri::async_runtime::block_on(state.init_history(&app_path)) {
+                tracing::error!("Failed to open transcript history: {}", e);
+            }
+            tauri::async_runtime::block_on(state.init_whisper(&app_path));
+            tauri::async_runtime::block_on(state.init_silero_model_path(&app_path));
+
             let state_handle = state.clone();
             let app_handle = app.handle().clone();
 
@@ -63,6 +139,65 @@ pub fn run() -> Result<()> {
                 state_handle.init_text_injector_service(app_handle).await;
             });
 
+            // Bind the saved hotkeys; a conflict shouldn't keep the app from starting
+            let hotkeys = HotkeyManager::new(app.handle().clone(), state.clone());
+            let hotkey_settings = tauri::async_runtime::block_on(state.settings.lock())
+                .hotkeys
+                .clone();
+            if let Err(e) = hotkeys.rebind(app.handle(), &hotkey_settings) {
+                tracing::error!("Failed to register hotkeys: {}", e);
+            }
+            app.manage(hotkeys);
+
+            // Tell the frontend when microphones are plugged in or removed
+            let app_handle = app.handle().clone();
+            match DeviceMonitor::start(move |devices| {
+                let _ = app_handle.emit("devices-changed", devices);
+            }) {
+                Ok(monitor) => {
+                    app.manage(monitor);
+                }
+                Err(e) => tracing::error!("Failed to start the device monitor: {}", e),
+            }
+
+            // Keep the memory and CPU gauges current and report health changes
+            let app_handle = app.handle().clone();
+            let sample_interval_ms =
+                tauri::async_runtime::block_on(state.settings.lock()).sample_interval_ms;
+            match ResourceSampler::start(
+                state.metrics.as_ref().clone(),
+                std::time::Duration::from_millis(sample_interval_ms as u64),
+                move |healthy| {
+                    let _ = app_handle.emit("health-changed", healthy);
+                },
+            ) {
+                Ok(sampler) => {
+                    app.manage(sampler);
+                }
+                Err(e) => tracing::error!("Failed to start the resource sampler: {}", e),
+            }
+
+            // Serve the metrics to Prometheus when enabled
+            let exporter_settings = tauri::async_runtime::block_on(state.settings.lock())
+                .metrics_exporter
+                .clone();
+            if exporter_settings.enabled {
+                #[cfg(feature = "prometheus")]
+                match tauri::async_runtime::block_on(MetricsExporter::start(
+                    state.metrics.as_ref().clone(),
+                    exporter_settings.port,
+                )) {
+                    Ok(exporter) => {
+                        app.manage(exporter);
+                    }
+                    Err(e) => tracing::error!("Failed to start the metrics exporter: {}", e),
+                }
+                #[cfg(not(feature = "prometheus"))]
+                tracing::warn!(
+                    "Metrics exporter enabled but not built in, enable the `prometheus` feature"
+                );
+            }
+
             app.manage(state);
             setup_tray(app)?;
             Ok(())
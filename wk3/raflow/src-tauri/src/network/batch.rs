@@ -4,28 +4,33 @@ use std::time::{Duration, Instant};
 use tracing::debug;
 
 /// Message batcher for efficient network transmission
-pub struct MessageBatcher {
-    batch: VecDeque<ClientMessage>,
+///
+/// A batch is due once it holds `max_batch_size` messages or its oldest
+/// message has waited `max_batch_age`.
+pub struct MessageBatcher<T = ClientMessage> {
+    batch: VecDeque<T>,
     max_batch_size: usize,
     max_batch_age: Duration,
-    last_flush: Instant,
+    /// When the oldest message in the batch was added
+    oldest: Option<Instant>,
 }
 
-impl MessageBatcher {
+impl<T> MessageBatcher<T> {
     pub fn new(max_batch_size: usize, max_batch_age: Duration) -> Self {
         Self {
             batch: VecDeque::with_capacity(max_batch_size),
             max_batch_size,
             max_batch_age,
-            last_flush: Instant::now(),
+            oldest: None,
         }
     }
 
     /// Add a message to the batch
     ///
     /// Returns true if the batch should be flushed
-    pub fn add(&mut self, message: ClientMessage) -> bool {
+    pub fn add(&mut self, message: T) -> bool {
         self.batch.push_back(message);
+        self.oldest.get_or_insert_with(Instant::now);
 
         // Check if batch should be flushed
         self.should_flush()
@@ -34,13 +39,20 @@ impl MessageBatcher {
     /// Check if the batch should be flushed
     pub fn should_flush(&self) -> bool {
         self.batch.len() >= self.max_batch_size
-            || self.last_flush.elapsed() >= self.max_batch_age
+            || self
+                .oldest
+                .is_some_and(|oldest| oldest.elapsed() >= self.max_batch_age)
+    }
+
+    /// When the batch becomes due by age, `None` while it's empty
+    pub fn deadline(&self) -> Option<Instant> {
+        self.oldest.map(|oldest| oldest + self.max_batch_age)
     }
 
     /// Get all messages and clear the batch
-    pub fn flush(&mut self) -> Vec<ClientMessage> {
-        let messages: Vec<ClientMessage> = self.batch.drain(..).collect();
-        self.last_flush = Instant::now();
+    pub fn flush(&mut self) -> Vec<T> {
+        let messages: Vec<T> = self.batch.drain(..).collect();
+        self.oldest = None;
 
         if !messages.is_empty() {
             debug!("Flushing {} batched messages", messages.len());
@@ -70,12 +82,12 @@ mod tests {
 
         // Add messages
         for i in 0..2 {
-            let msg = ClientMessage::audio_chunk(&vec![i as f32]);
+            let msg = ClientMessage::audio_chunk(&[i as f32]);
             assert!(!batcher.add(msg)); // Should not flush yet
         }
 
         // Third message should trigger flush
-        let msg = ClientMessage::audio_chunk(&vec![2.0]);
+        let msg = ClientMessage::audio_chunk(&[2.0]);
         assert!(batcher.add(msg));
 
         let messages = batcher.flush();
@@ -86,7 +98,7 @@ mod tests {
     fn test_batcher_age_limit() {
         let mut batcher = MessageBatcher::new(10, Duration::from_millis(50));
 
-        let msg = ClientMessage::audio_chunk(&vec![1.0]);
+        let msg = ClientMessage::audio_chunk(&[1.0]);
         batcher.add(msg);
 
         // Should not flush immediately
@@ -98,4 +110,19 @@ mod tests {
         // Should flush now
         assert!(batcher.should_flush());
     }
+
+    #[test]
+    fn test_batcher_age_starts_with_first_message() {
+        let mut batcher = MessageBatcher::new(10, Duration::from_millis(50));
+        assert_eq!(batcher.deadline(), None);
+
+        // An idle batcher doesn't flush a new message right away
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(!batcher.add(1));
+        assert!(batcher.deadline().is_some());
+
+        assert_eq!(batcher.flush(), vec![1]);
+        assert_eq!(batcher.deadline(), None);
+        assert!(!batcher.should_flush());
+    }
 }
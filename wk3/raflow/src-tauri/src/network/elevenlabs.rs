@@ -0,0 +1,550 @@
+use anyhow::{anyhow, Result};
+use futures_util::future::BoxFuture;
+use futures_util::{FutureExt, SinkExt, StreamExt};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use super::encoding::{AudioEncoder, AudioEncoding};
+use super::options::SessionOptions;
+use super::protocol::{ClientMessage, ServerMessage};
+use super::provider::{AudioSink, ProviderConnection, SpeechToTextProvider, TranscriptEvent};
+use super::websocket::{WebSocketClient, WsSink};
+use crate::utils::Metrics;
+
+/// How long a plain commit waits for its copy with timestamps
+const TIMESTAMPS_WAIT: Duration = Duration::from_millis(200);
+
+/// ElevenLabs Scribe v2 Realtime provider
+pub struct ElevenLabsProvider {
+    api_key: String,
+    endpoint: Option<String>,
+    metrics: Option<Metrics>,
+}
+
+impl ElevenLabsProvider {
+    /// Create a new ElevenLabs provider
+    ///
+    /// # Arguments
+    /// * `api_key` - ElevenLabs API key
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            endpoint: None,
+            metrics: None,
+        }
+    }
+
+    /// Connect to a different endpoint instead of the public API
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Record uploaded messages and bytes
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// The encoding used for a requested one
+    ///
+    /// The public API only accepts JSON messages, binary frames need an
+    /// endpoint override that takes them, e.g. a relay. Anything else falls
+    /// back to base64 PCM.
+    fn encoding_for(&self, requested: AudioEncoding) -> AudioEncoding {
+        let supported = match requested {
+            AudioEncoding::PcmBase64 | AudioEncoding::Mulaw => true,
+            AudioEncoding::PcmBinary | AudioEncoding::Opus => self.endpoint.is_some(),
+        };
+        if supported && requested.is_available() {
+            return requested;
+        }
+
+        warn!(
+            "Audio encoding {:?} not supported here, sending base64 PCM",
+            requested
+        );
+        AudioEncoding::PcmBase64
+    }
+}
+
+impl SpeechToTextProvider for ElevenLabsProvider {
+    fn name(&self) -> &str {
+        "elevenlabs"
+    }
+
+    fn connect<'a>(
+        &'a self,
+        options: &'a SessionOptions,
+    ) -> BoxFuture<'a, Result<ProviderConnection>> {
+        async move {
+            let encoding = self.encoding_for(options.audio_encoding);
+            let encoder = AudioEncoder::new(encoding)?;
+            info!("Uploading audio as {}", encoding.audio_format());
+
+            // Word timings and the detected language only come with timestamps
+            let options = options
+                .clone()
+                .with_audio_encoding(encoding)
+                .with_param("include_timestamps", "true");
+            let mut client = WebSocketClient::new(self.api_key.clone()).with_options(options);
+            if let Some(endpoint) = &self.endpoint {
+                client = client.with_url(endpoint.clone());
+            }
+            let (sink, stream) = client.connect().await?;
+            let metrics = self.metrics.clone();
+            let stream = stream.inspect(move |msg| {
+                if let (Some(metrics), Ok(msg)) = (&metrics, msg) {
+                    metrics.record_ws_message_received(msg.len());
+                }
+            });
+
+            let (server_tx, server_rx) = mpsc::channel(100);
+            tokio::spawn(async move {
+                if let Err(e) = WebSocketClient::receive_loop(stream, server_tx).await {
+                    error!("WebSocket receive loop error: {}", e);
+                }
+            });
+
+            let (event_tx, event_rx) = mpsc::channel(100);
+            tokio::spawn(forward_events(server_rx, event_tx));
+
+            Ok(ProviderConnection {
+                sink: Box::new(ElevenLabsSink {
+                    sink,
+                    encoder,
+                    metrics: self.metrics.clone(),
+                    previous_text: None,
+                }),
+                events: event_rx,
+            })
+        }
+        .boxed()
+    }
+}
+
+/// Turn server messages into transcript events
+///
+/// With timestamps requested every segment is committed twice, first
+/// plain and then with timestamps. A plain commit is held back until its
+/// timestamped copy replaces it, and only forwarded when no copy follows.
+async fn forward_events(
+    mut server_rx: mpsc::Receiver<ServerMessage>,
+    event_tx: mpsc::Sender<TranscriptEvent>,
+) {
+    let mut pending: Option<TranscriptEvent> = None;
+
+    loop {
+        let msg = match pending.take() {
+            Some(event) => match tokio::time::timeout(TIMESTAMPS_WAIT, server_rx.recv()).await {
+                Ok(msg) => {
+                    pending = Some(event);
+                    msg
+                }
+                Err(_) => {
+                    if event_tx.send(event).await.is_err() {
+                        return;
+                    }
+                    continue;
+                }
+            },
+            None => server_rx.recv().await,
+        };
+        let Some(msg) = msg else {
+            break;
+        };
+
+        let plain_commit = matches!(msg, ServerMessage::CommittedTranscript { .. });
+        if matches!(msg, ServerMessage::CommittedTranscriptWithTimestamps { .. }) {
+            pending = None;
+        }
+        if let Some(event) = pending.take() {
+            if event_tx.send(event).await.is_err() {
+                return;
+            }
+        }
+
+        let Some(event) = msg.into_event() else {
+            continue;
+        };
+        if plain_commit {
+            pending = Some(event);
+        } else if event_tx.send(event).await.is_err() {
+            return;
+        }
+    }
+
+    if let Some(event) = pending {
+        let _ = event_tx.send(event).await;
+    }
+}
+
+/// Write half of an ElevenLabs connection
+struct ElevenLabsSink {
+    sink: WsSink,
+    encoder: AudioEncoder,
+    metrics: Option<Metrics>,
+    // Context for the next chunk, sent once
+    previous_text: Option<String>,
+}
+
+impl ElevenLabsSink {
+    /// Send a chunk as JSON messages with base64 audio
+    async fn send_json(&mut self, payloads: Vec<Vec<u8>>, commit: bool) -> Result<usize> {
+        let msg = ClientMessage::encoded_chunk(
+            &payloads.concat(),
+            self.encoder.encoding().sample_rate(),
+            commit,
+        )
+        .with_previous_text(self.previous_text.take());
+        let bytes = WebSocketClient::send_message(&mut self.sink, &msg).await?;
+        self.record_message(bytes);
+        Ok(bytes)
+    }
+
+    /// Send a chunk as binary frames
+    ///
+    /// The commit flag and context follow the audio in a JSON message
+    /// without audio.
+    async fn send_binary(&mut self, payloads: Vec<Vec<u8>>, commit: bool) -> Result<usize> {
+        let mut bytes = 0;
+        for payload in payloads.into_iter().filter(|payload| !payload.is_empty()) {
+            let sent = WebSocketClient::send_binary(&mut self.sink, payload).await?;
+            self.record_message(sent);
+            bytes += sent;
+        }
+
+        if commit || self.previous_text.is_some() {
+            bytes += self.send_json(Vec::new(), commit).await?;
+        }
+        Ok(bytes)
+    }
+
+    fn record_message(&self, bytes: usize) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_ws_message_sent(bytes);
+        }
+    }
+}
+
+impl AudioSink for ElevenLabsSink {
+    fn send_audio<'a>(&'a mut self, samples: &'a [f32], commit: bool) -> BoxFuture<'a, Result<()>> {
+        async move {
+            let payloads = self.encoder.encode(samples, commit)?;
+            let bytes = if self.encoder.encoding().is_binary() {
+                self.send_binary(payloads, commit).await?
+            } else {
+                self.send_json(payloads, commit).await?
+            };
+
+            if let Some(metrics) = &self.metrics {
+                metrics.record_audio_sent(samples.len() * 2, bytes);
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn set_previous_text(&mut self, text: String) {
+        self.previous_text = Some(text).filter(|text| !text.is_empty());
+    }
+
+    fn close(&mut self) -> BoxFuture<'_, Result<()>> {
+        async move {
+            self.sink
+                .close()
+                .await
+                .map_err(|e| anyhow!("Failed to close WebSocket: {}", e))
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::mock_server::{MockConnection, MockReply, MockScribeServer, MockTrigger};
+    use crate::network::protocol::{WordKind, WordTiming};
+
+    fn parse(json: &str) -> Option<TranscriptEvent> {
+        serde_json::from_str::<ServerMessage>(json)
+            .unwrap()
+            .into_event()
+    }
+
+    #[test]
+    fn test_normalize_transcripts() {
+        assert_eq!(
+            parse(r#"{"message_type":"partial_transcript","text":"Hel"}"#),
+            Some(TranscriptEvent::Partial {
+                text: "Hel".to_string()
+            })
+        );
+        assert_eq!(
+            parse(r#"{"message_type":"committed_transcript","text":"Hello"}"#),
+            Some(TranscriptEvent::Committed {
+                text: "Hello".to_string(),
+                language_code: None,
+                confidence: None,
+                words: Vec::new()
+            })
+        );
+        assert_eq!(
+            parse(
+                r#"{"message_type":"committed_transcript_with_timestamps","text":"Hello","language_code":"eng","confidence":0.9,"words":[{"text":"Hello","start":0.0,"end":0.4}]}"#
+            ),
+            Some(TranscriptEvent::Committed {
+                text: "Hello".to_string(),
+                language_code: Some("eng".to_string()),
+                confidence: Some(0.9),
+                words: vec![WordTiming {
+                    text: "Hello".to_string(),
+                    start: 0.0,
+                    end: 0.4,
+                    kind: WordKind::Word,
+                    speaker: None,
+                    logprob: None,
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_normalize_errors() {
+        assert_eq!(
+            parse(r#"{"message_type":"input_error","error_message":"bad audio"}"#),
+            Some(TranscriptEvent::Error {
+                message: "bad audio".to_string(),
+                fatal: false
+            })
+        );
+        assert_eq!(
+            parse(r#"{"message_type":"invalid_request","error":"bad key"}"#),
+            Some(TranscriptEvent::Error {
+                message: "bad key".to_string(),
+                fatal: true
+            })
+        );
+    }
+
+    #[test]
+    fn test_normalize_ignores_session_config() {
+        assert_eq!(parse(r#"{"message_type":"session_config"}"#), None);
+    }
+
+    async fn next(connection: &mut ProviderConnection) -> Option<TranscriptEvent> {
+        tokio::time::timeout(std::time::Duration::from_secs(2), connection.events.recv())
+            .await
+            .expect("no event in time")
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_with_mock_server() {
+        let server = MockScribeServer::builder()
+            .connection(MockConnection::new().on(
+                MockTrigger::EveryCommit,
+                [MockReply::partial("hel"), MockReply::committed("hello")],
+            ))
+            .start()
+            .await
+            .unwrap();
+        let provider = ElevenLabsProvider::new("test-key".to_string()).with_endpoint(server.url());
+        let mut connection = provider.connect(&SessionOptions::default()).await.unwrap();
+
+        assert!(matches!(
+            next(&mut connection).await,
+            Some(TranscriptEvent::SessionStarted { .. })
+        ));
+
+        connection.sink.set_previous_text("before".to_string());
+        connection
+            .sink
+            .send_audio(&[0.1; 160], false)
+            .await
+            .unwrap();
+        connection.sink.send_audio(&[0.1; 80], true).await.unwrap();
+
+        assert_eq!(
+            next(&mut connection).await,
+            Some(TranscriptEvent::Partial {
+                text: "hel".to_string()
+            })
+        );
+        assert!(matches!(
+            next(&mut connection).await,
+            Some(TranscriptEvent::Committed { text, .. }) if text == "hello"
+        ));
+
+        let received = server.received();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].samples, 160);
+        assert_eq!(received[0].previous_text.as_deref(), Some("before"));
+        assert_eq!(received[1].previous_text, None);
+        assert!(received[1].commit);
+
+        connection.sink.close().await.unwrap();
+        assert_eq!(next(&mut connection).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_timestamped_commit_replaces_plain_commit() {
+        let server = MockScribeServer::builder()
+            .connection(MockConnection::new().on(
+                MockTrigger::EveryCommit,
+                [
+                    MockReply::committed_with_timestamps("hello world", "eng"),
+                    MockReply::committed("plain only"),
+                ],
+            ))
+            .start()
+            .await
+            .unwrap();
+        let provider = ElevenLabsProvider::new("test-key".to_string()).with_endpoint(server.url());
+        let mut connection = provider.connect(&SessionOptions::default()).await.unwrap();
+        next(&mut connection).await;
+
+        connection.sink.send_audio(&[0.1; 160], true).await.unwrap();
+        let Some(TranscriptEvent::Committed { text, words, .. }) = next(&mut connection).await
+        else {
+            panic!("expected a commit");
+        };
+        assert_eq!(text, "hello world");
+        assert_eq!(words.len(), 2);
+
+        // Without a timestamped copy the plain commit still comes through
+        assert!(matches!(
+            next(&mut connection).await,
+            Some(TranscriptEvent::Committed { text, words, .. })
+                if text == "plain only" && words.is_empty()
+        ));
+
+        connection.sink.close().await.unwrap();
+        assert_eq!(next(&mut connection).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_input_error_and_disconnect() {
+        let server = MockScribeServer::builder()
+            .connection(MockConnection::new().on(
+                MockTrigger::Chunk(1),
+                [MockReply::input_error("bad audio"), MockReply::Disconnect],
+            ))
+            .latency(std::time::Duration::from_millis(50))
+            .start()
+            .await
+            .unwrap();
+        let provider = ElevenLabsProvider::new("test-key".to_string()).with_endpoint(server.url());
+        let mut connection = provider.connect(&SessionOptions::default()).await.unwrap();
+        next(&mut connection).await;
+
+        let sent = std::time::Instant::now();
+        connection.sink.send_audio(&[0.0; 16], false).await.unwrap();
+        assert_eq!(
+            next(&mut connection).await,
+            Some(TranscriptEvent::Error {
+                message: "bad audio".to_string(),
+                fatal: false
+            })
+        );
+        assert!(sent.elapsed() >= std::time::Duration::from_millis(50));
+
+        // The dropped connection closes the event stream
+        assert_eq!(next(&mut connection).await, None);
+        assert_eq!(server.connections(), 1);
+    }
+
+    #[test]
+    fn test_encoding_fallback() {
+        let public = ElevenLabsProvider::new("test-key".to_string());
+        assert_eq!(
+            public.encoding_for(AudioEncoding::Mulaw),
+            AudioEncoding::Mulaw
+        );
+        assert_eq!(
+            public.encoding_for(AudioEncoding::PcmBinary),
+            AudioEncoding::PcmBase64
+        );
+
+        let relay = ElevenLabsProvider::new("test-key".to_string()).with_endpoint("ws://relay");
+        assert_eq!(
+            relay.encoding_for(AudioEncoding::PcmBinary),
+            AudioEncoding::PcmBinary
+        );
+        assert_eq!(
+            relay.encoding_for(AudioEncoding::Opus).is_binary(),
+            cfg!(feature = "opus")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_binary_frames_and_wire_bytes() {
+        let server = MockScribeServer::start().await.unwrap();
+        let metrics = Metrics::new();
+        let provider = ElevenLabsProvider::new("test-key".to_string())
+            .with_endpoint(server.url())
+            .with_metrics(metrics.clone());
+        let options = SessionOptions::default().with_audio_encoding(AudioEncoding::PcmBinary);
+        let mut connection = provider.connect(&options).await.unwrap();
+        next(&mut connection).await;
+
+        connection
+            .sink
+            .send_audio(&[0.1; 1600], false)
+            .await
+            .unwrap();
+        connection
+            .sink
+            .send_audio(&[0.1; 1600], true)
+            .await
+            .unwrap();
+        connection.sink.close().await.unwrap();
+        assert_eq!(next(&mut connection).await, None);
+
+        // The commit follows the audio in a JSON message
+        let received = server.received();
+        assert_eq!(received.len(), 3);
+        assert!(received[0].binary && received[1].binary);
+        assert_eq!(received[1].samples, 1600);
+        assert_eq!(received[1].bytes, 3200);
+        assert!(!received[1].commit);
+        assert!(!received[2].binary && received[2].commit);
+        assert_eq!(received[2].samples, 0);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.ws_messages_sent, 3);
+        assert_eq!(snapshot.audio_pcm_bytes, 6400);
+        assert_eq!(snapshot.audio_wire_bytes, snapshot.ws_bytes_sent);
+        assert!(snapshot.audio_wire_bytes > 6400 && snapshot.audio_wire_bytes < 6500);
+        assert!(snapshot.ws_messages_received >= 1 && snapshot.ws_bytes_received > 0);
+    }
+
+    #[tokio::test]
+    async fn test_mulaw_upload() {
+        let server = MockScribeServer::start().await.unwrap();
+        let metrics = Metrics::new();
+        let provider = ElevenLabsProvider::new("test-key".to_string())
+            .with_endpoint(server.url())
+            .with_metrics(metrics.clone());
+        let options = SessionOptions::default().with_audio_encoding(AudioEncoding::Mulaw);
+        let mut connection = provider.connect(&options).await.unwrap();
+        next(&mut connection).await;
+
+        connection
+            .sink
+            .send_audio(&[0.1; 1600], true)
+            .await
+            .unwrap();
+        connection.sink.close().await.unwrap();
+        assert_eq!(next(&mut connection).await, None);
+
+        let received = server.received();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].samples, 800);
+        assert!(received[0].commit);
+
+        // A quarter of the PCM, plus base64 and JSON
+        let snapshot = metrics.snapshot();
+        assert!(snapshot.audio_bandwidth_savings > 60.0);
+    }
+}
@@ -0,0 +1,275 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// How audio is encoded for upload
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioEncoding {
+    /// 16-bit PCM, base64 in JSON messages
+    #[default]
+    PcmBase64,
+    /// 16-bit PCM in binary frames
+    PcmBinary,
+    /// 20ms Opus packets in binary frames, needs the `opus` feature
+    Opus,
+    /// 8kHz G.711 μ-law, base64 in JSON messages
+    Mulaw,
+}
+
+impl AudioEncoding {
+    /// Value of the `audio_format` query parameter
+    pub fn audio_format(self) -> &'static str {
+        match self {
+            AudioEncoding::PcmBase64 | AudioEncoding::PcmBinary => "pcm_16000",
+            AudioEncoding::Opus => "opus_16000",
+            AudioEncoding::Mulaw => "ulaw_8000",
+        }
+    }
+
+    /// Sample rate of the encoded audio
+    pub fn sample_rate(self) -> u32 {
+        match self {
+            AudioEncoding::Mulaw => 8000,
+            _ => 16000,
+        }
+    }
+
+    /// Whether audio goes out in binary frames instead of JSON messages
+    pub fn is_binary(self) -> bool {
+        matches!(self, AudioEncoding::PcmBinary | AudioEncoding::Opus)
+    }
+
+    /// Whether this build can produce the encoding
+    pub fn is_available(self) -> bool {
+        self != AudioEncoding::Opus || cfg!(feature = "opus")
+    }
+}
+
+/// Turns 16kHz mono chunks into payloads of an [`AudioEncoding`]
+pub struct AudioEncoder {
+    encoding: AudioEncoding,
+    #[cfg(feature = "opus")]
+    opus: Option<opus::OpusEncoder>,
+}
+
+impl AudioEncoder {
+    /// Create a new encoder
+    ///
+    /// Fails for Opus when the `opus` feature is not enabled.
+    pub fn new(encoding: AudioEncoding) -> Result<Self> {
+        if !encoding.is_available() {
+            anyhow::bail!("Opus support not built in, enable the `opus` feature");
+        }
+
+        Ok(Self {
+            encoding,
+            #[cfg(feature = "opus")]
+            opus: match encoding {
+                AudioEncoding::Opus => Some(opus::OpusEncoder::new()?),
+                _ => None,
+            },
+        })
+    }
+
+    pub fn encoding(&self) -> AudioEncoding {
+        self.encoding
+    }
+
+    /// Encode a chunk
+    ///
+    /// Opus holds back samples that don't fill a whole packet until the
+    /// next chunk, or pads them with silence when `flush` is set.
+    ///
+    /// # Returns
+    /// The payloads to send in order, one per frame
+    #[cfg_attr(not(feature = "opus"), allow(unused_variables))]
+    pub fn encode(&mut self, samples: &[f32], flush: bool) -> Result<Vec<Vec<u8>>> {
+        match self.encoding {
+            AudioEncoding::PcmBase64 | AudioEncoding::PcmBinary => Ok(vec![pcm_s16le(samples)]),
+            AudioEncoding::Mulaw => Ok(vec![mulaw(samples)]),
+            #[cfg(feature = "opus")]
+            AudioEncoding::Opus => self
+                .opus
+                .as_mut()
+                .expect("Opus encoder is created with the encoder")
+                .encode(samples, flush),
+            #[cfg(not(feature = "opus"))]
+            AudioEncoding::Opus => {
+                unreachable!("Opus encoders are not created without the `opus` feature")
+            }
+        }
+    }
+}
+
+/// Convert samples to 16-bit little-endian PCM
+pub fn pcm_s16le(samples: &[f32]) -> Vec<u8> {
+    samples
+        .iter()
+        .map(|&x| (x.clamp(-1.0, 1.0) * 32767.0) as i16)
+        .flat_map(|sample| sample.to_le_bytes())
+        .collect()
+}
+
+/// Convert 16kHz samples to 8kHz μ-law
+///
+/// Averaging sample pairs is enough filtering for telephone-band speech.
+pub fn mulaw(samples: &[f32]) -> Vec<u8> {
+    samples
+        .chunks(2)
+        .map(|pair| pair.iter().sum::<f32>() / pair.len() as f32)
+        .map(|x| linear_to_mulaw((x.clamp(-1.0, 1.0) * 32767.0) as i16))
+        .collect()
+}
+
+const MULAW_BIAS: i32 = 0x84;
+const MULAW_CLIP: i32 = 32635;
+
+/// G.711 μ-law encoding of a 16-bit sample
+fn linear_to_mulaw(sample: i16) -> u8 {
+    let sign = if sample < 0 { 0x80 } else { 0 };
+    let magnitude = (sample as i32).abs().min(MULAW_CLIP) + MULAW_BIAS;
+
+    // Position of the highest bit above the 7 covered by the bias
+    let exponent = 24 - magnitude.leading_zeros() as i32;
+    let mantissa = (magnitude >> (exponent + 3)) & 0x0F;
+    !(sign | (exponent << 4) as u8 | mantissa as u8)
+}
+
+#[cfg(feature = "opus")]
+mod opus {
+    use anyhow::{anyhow, Result};
+    use audiopus::coder::Encoder;
+    use audiopus::{Application, Bitrate, Channels, SampleRate};
+
+    /// Samples in one packet, 20ms at 16kHz
+    const FRAME_SAMPLES: usize = 320;
+
+    /// Plenty for speech recognition
+    const BITRATE: i32 = 24000;
+
+    /// Largest packet libopus produces
+    const MAX_PACKET_BYTES: usize = 4000;
+
+    pub struct OpusEncoder {
+        encoder: Encoder,
+        pending: Vec<f32>,
+    }
+
+    impl OpusEncoder {
+        pub fn new() -> Result<Self> {
+            let mut encoder = Encoder::new(SampleRate::Hz16000, Channels::Mono, Application::Voip)
+                .map_err(|e| anyhow!("Failed to create Opus encoder: {}", e))?;
+            encoder
+                .set_bitrate(Bitrate::BitsPerSecond(BITRATE))
+                .map_err(|e| anyhow!("Failed to set Opus bitrate: {}", e))?;
+
+            Ok(Self {
+                encoder,
+                pending: Vec::with_capacity(FRAME_SAMPLES),
+            })
+        }
+
+        pub fn encode(&mut self, samples: &[f32], flush: bool) -> Result<Vec<Vec<u8>>> {
+            self.pending.extend_from_slice(samples);
+            if flush && !self.pending.len().is_multiple_of(FRAME_SAMPLES) {
+                let padded = self.pending.len().next_multiple_of(FRAME_SAMPLES);
+                self.pending.resize(padded, 0.0);
+            }
+
+            let frames = self.pending.len() / FRAME_SAMPLES;
+            let packets = self
+                .pending
+                .chunks_exact(FRAME_SAMPLES)
+                .map(|frame| {
+                    let mut packet = vec![0; MAX_PACKET_BYTES];
+                    let len = self
+                        .encoder
+                        .encode_float(frame, &mut packet)
+                        .map_err(|e| anyhow!("Opus encoding failed: {}", e))?;
+                    packet.truncate(len);
+                    Ok(packet)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            self.pending.drain(..frames * FRAME_SAMPLES);
+            Ok(packets)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// G.711 μ-law decoding, the reverse of [`linear_to_mulaw`]
+    fn mulaw_to_linear(byte: u8) -> i16 {
+        let byte = !byte;
+        let exponent = (byte >> 4) & 0x07;
+        let mantissa = (byte & 0x0F) as i32;
+        let magnitude = (((mantissa << 3) + MULAW_BIAS) << exponent) - MULAW_BIAS;
+        if byte & 0x80 != 0 {
+            -magnitude as i16
+        } else {
+            magnitude as i16
+        }
+    }
+
+    #[test]
+    fn test_mulaw_round_trip() {
+        assert_eq!(linear_to_mulaw(0), 0xFF);
+        assert_eq!(linear_to_mulaw(i16::MAX), 0x80);
+        assert_eq!(linear_to_mulaw(i16::MIN), 0x00);
+
+        for sample in (-32768i32..=32767).step_by(7) {
+            let decoded = mulaw_to_linear(linear_to_mulaw(sample as i16)) as i32;
+            // Quantization steps grow with the magnitude, about 1/16 of it
+            let tolerance = sample.abs().min(MULAW_CLIP) / 16 + 8;
+            assert!(
+                (decoded - sample.clamp(-MULAW_CLIP, MULAW_CLIP)).abs() <= tolerance,
+                "{} decoded as {}",
+                sample,
+                decoded
+            );
+        }
+    }
+
+    #[test]
+    fn test_payload_sizes() {
+        let samples = vec![0.25; 1600];
+
+        let mut pcm = AudioEncoder::new(AudioEncoding::PcmBinary).unwrap();
+        let payloads = pcm.encode(&samples, false).unwrap();
+        assert_eq!(payloads.len(), 1);
+        assert_eq!(payloads[0].len(), 3200);
+        assert_eq!(&payloads[0][..2], &8191i16.to_le_bytes());
+
+        // Half the rate at one byte per sample
+        let mut mulaw = AudioEncoder::new(AudioEncoding::Mulaw).unwrap();
+        assert_eq!(mulaw.encode(&samples, false).unwrap()[0].len(), 800);
+        assert_eq!(mulaw.encode(&[0.1; 3], true).unwrap()[0].len(), 2);
+    }
+
+    #[test]
+    fn test_opus_availability() {
+        assert_eq!(AudioEncoding::Opus.is_available(), cfg!(feature = "opus"));
+        assert_eq!(
+            AudioEncoder::new(AudioEncoding::Opus).is_ok(),
+            cfg!(feature = "opus")
+        );
+    }
+
+    #[cfg(feature = "opus")]
+    #[test]
+    fn test_opus_packets() {
+        let mut opus = AudioEncoder::new(AudioEncoding::Opus).unwrap();
+
+        // 100ms makes five packets, much smaller than the PCM
+        let packets = opus.encode(&[0.1; 1600], false).unwrap();
+        assert_eq!(packets.len(), 5);
+        assert!(packets.iter().map(Vec::len).sum::<usize>() < 3200 / 4);
+
+        // Partial packets wait for more audio unless flushed
+        assert!(opus.encode(&[0.1; 100], false).unwrap().is_empty());
+        assert_eq!(opus.encode(&[0.1; 100], true).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,467 @@
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::{accept_hdr_async, tungstenite::Message, WebSocketStream};
+use tracing::{debug, warn};
+
+use super::protocol::ClientMessage;
+
+/// Something the mock server sends or does in reply to the client
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(not(feature = "test-support"), allow(dead_code))]
+pub enum MockReply {
+    /// `session_started` message
+    SessionStarted { session_id: String },
+    /// `partial_transcript` message
+    Partial(String),
+    /// `committed_transcript` message
+    Committed(String),
+    /// `committed_transcript` followed by `committed_transcript_with_timestamps`
+    /// in the detected language, as the API commits when the client asked
+    /// for timestamps. Only the first is sent otherwise.
+    CommittedWithTimestamps { text: String, language_code: String },
+    /// `input_error` message
+    InputError(String),
+    /// Wait before the next reply
+    Delay(Duration),
+    /// Drop the connection without a close frame, like a network failure
+    Disconnect,
+    /// Close the connection gracefully
+    Close,
+}
+
+impl MockReply {
+    pub fn session_started(session_id: &str) -> Self {
+        MockReply::SessionStarted {
+            session_id: session_id.to_string(),
+        }
+    }
+
+    pub fn partial(text: &str) -> Self {
+        MockReply::Partial(text.to_string())
+    }
+
+    pub fn committed(text: &str) -> Self {
+        MockReply::Committed(text.to_string())
+    }
+
+    pub fn committed_with_timestamps(text: &str, language_code: &str) -> Self {
+        MockReply::CommittedWithTimestamps {
+            text: text.to_string(),
+            language_code: language_code.to_string(),
+        }
+    }
+
+    pub fn input_error(message: &str) -> Self {
+        MockReply::InputError(message.to_string())
+    }
+
+    /// The messages to send, none for actions
+    ///
+    /// # Arguments
+    /// * `timestamps` - The client asked for `include_timestamps`
+    fn to_messages(&self, timestamps: bool) -> Vec<Message> {
+        let json = match self {
+            MockReply::SessionStarted { session_id } => json!({
+                "message_type": "session_started",
+                "session_id": session_id,
+                "model_id": "mock",
+            }),
+            MockReply::Partial(text) => json!({
+                "message_type": "partial_transcript",
+                "text": text,
+            }),
+            MockReply::Committed(text) => json!({
+                "message_type": "committed_transcript",
+                "text": text,
+            }),
+            MockReply::CommittedWithTimestamps {
+                text,
+                language_code,
+            } => {
+                let mut messages = MockReply::committed(text).to_messages(timestamps);
+                if timestamps {
+                    // Half a second per word
+                    let words: Vec<_> = text
+                        .split_whitespace()
+                        .enumerate()
+                        .map(|(i, word)| {
+                            json!({
+                                "text": word,
+                                "start": i as f64 * 0.5,
+                                "end": (i + 1) as f64 * 0.5,
+                                "type": "word",
+                            })
+                        })
+                        .collect();
+                    let json = json!({
+                        "message_type": "committed_transcript_with_timestamps",
+                        "text": text,
+                        "language_code": language_code,
+                        "words": words,
+                    });
+                    messages.push(Message::Text(json.to_string().into()));
+                }
+                return messages;
+            }
+            MockReply::InputError(message) => json!({
+                "message_type": "input_error",
+                "error_message": message,
+            }),
+            MockReply::Delay(_) | MockReply::Disconnect | MockReply::Close => return Vec::new(),
+        };
+        vec![Message::Text(json.to_string().into())]
+    }
+}
+
+/// When scripted replies are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockTrigger {
+    /// Right after the WebSocket handshake
+    Connect,
+    /// After the n-th audio chunk of the connection, counting from 1
+    Chunk(usize),
+    /// After the n-th chunk with the commit flag, counting from 1
+    Commit(usize),
+    /// After every chunk with the commit flag
+    EveryCommit,
+}
+
+/// Script for a single connection to the mock server
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockConnection {
+    rules: Vec<(MockTrigger, Vec<MockReply>)>,
+}
+
+impl Default for MockConnection {
+    /// Start the session and nothing else
+    fn default() -> Self {
+        Self::empty().on(
+            MockTrigger::Connect,
+            [MockReply::session_started("mock-session")],
+        )
+    }
+}
+
+impl MockConnection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A script without any replies, not even `session_started`
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Send `replies` when `trigger` fires
+    ///
+    /// When several rules fire for the same chunk they run in the order
+    /// they were added.
+    pub fn on(
+        mut self,
+        trigger: MockTrigger,
+        replies: impl IntoIterator<Item = MockReply>,
+    ) -> Self {
+        self.rules.push((trigger, replies.into_iter().collect()));
+        self
+    }
+
+    /// Replies of all rules matching any of the triggers, in script order
+    fn replies<'a>(&'a self, triggers: &'a [MockTrigger]) -> impl Iterator<Item = &'a MockReply> {
+        self.rules
+            .iter()
+            .filter(move |(trigger, _)| triggers.contains(trigger))
+            .flat_map(|(_, replies)| replies)
+    }
+}
+
+/// An audio chunk received by the mock server
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedChunk {
+    /// Index of the connection, counting from 0
+    pub connection: usize,
+    /// Number of samples in the connection's `audio_format`, 0 for Opus
+    pub samples: usize,
+    /// Size of the audio, after base64 decoding for JSON messages
+    pub bytes: usize,
+    /// Whether the audio came in a binary frame
+    pub binary: bool,
+    pub commit: bool,
+    pub previous_text: Option<String>,
+}
+
+/// Builder for [`MockScribeServer`]
+#[derive(Debug, Clone, Default)]
+pub struct MockScribeServerBuilder {
+    scripts: Vec<MockConnection>,
+    latency: Duration,
+}
+
+impl MockScribeServerBuilder {
+    /// Script for the next connection
+    ///
+    /// Connections past the last script repeat it.
+    pub fn connection(mut self, script: MockConnection) -> Self {
+        self.scripts.push(script);
+        self
+    }
+
+    /// Delay before every batch of replies
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Listen on a free local port
+    pub async fn start(self) -> Result<MockScribeServer> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        let task = tokio::spawn(accept_loop(
+            listener,
+            self,
+            received.clone(),
+            connections.clone(),
+        ));
+
+        Ok(MockScribeServer {
+            addr,
+            received,
+            connections,
+            task,
+        })
+    }
+}
+
+/// Local stand-in for the Scribe realtime API
+///
+/// Speaks enough of the protocol to run the whole pipeline offline:
+/// replies are scripted per connection and can be delayed, and
+/// connections can be dropped to simulate network failures. Audio is
+/// accepted in JSON messages and in binary frames.
+pub struct MockScribeServer {
+    addr: SocketAddr,
+    received: Arc<Mutex<Vec<ReceivedChunk>>>,
+    connections: Arc<AtomicUsize>,
+    task: JoinHandle<()>,
+}
+
+impl MockScribeServer {
+    pub fn builder() -> MockScribeServerBuilder {
+        MockScribeServerBuilder::default()
+    }
+
+    /// Start a server that only starts sessions
+    pub async fn start() -> Result<Self> {
+        Self::builder().start().await
+    }
+
+    /// Endpoint to pass to the provider
+    pub fn url(&self) -> String {
+        format!("ws://{}/v1/speech-to-text/realtime", self.addr)
+    }
+
+    /// Audio chunks received so far, over all connections
+    pub fn received(&self) -> Vec<ReceivedChunk> {
+        self.received.lock().unwrap().clone()
+    }
+
+    /// Number of connections accepted so far
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for MockScribeServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    builder: MockScribeServerBuilder,
+    received: Arc<Mutex<Vec<ReceivedChunk>>>,
+    connections: Arc<AtomicUsize>,
+) {
+    // Aborted together with the accept loop
+    let mut handlers = JoinSet::new();
+
+    while let Ok((stream, peer)) = listener.accept().await {
+        let index = connections.fetch_add(1, Ordering::SeqCst);
+        debug!("Mock server accepted connection {} from {}", index, peer);
+
+        let script = builder
+            .scripts
+            .get(index)
+            .or(builder.scripts.last())
+            .cloned()
+            .unwrap_or_default();
+        let connection = Connection {
+            index,
+            script,
+            latency: builder.latency,
+            received: received.clone(),
+        };
+        handlers.spawn(async move {
+            if let Err(e) = connection.serve(stream).await {
+                warn!("Mock server connection {} failed: {}", connection.index, e);
+            }
+        });
+    }
+}
+
+struct Connection {
+    index: usize,
+    script: MockConnection,
+    latency: Duration,
+    received: Arc<Mutex<Vec<ReceivedChunk>>>,
+}
+
+impl Connection {
+    // The handshake callback's error type is set by tungstenite
+    #[allow(clippy::result_large_err)]
+    async fn serve(&self, stream: TcpStream) -> Result<()> {
+        let mut audio_format = String::from("pcm_16000");
+        let mut timestamps = false;
+        let mut ws = accept_hdr_async(stream, |request: &Request, response: Response| {
+            let query = request.uri().query().unwrap_or_default();
+            for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+                match key.as_ref() {
+                    "audio_format" => audio_format = value.into_owned(),
+                    "include_timestamps" => timestamps = value == "true",
+                    _ => {}
+                }
+            }
+            Ok(response)
+        })
+        .await?;
+        let mut chunks = 0;
+        let mut commits = 0;
+
+        if !self
+            .reply(&mut ws, &[MockTrigger::Connect], timestamps)
+            .await?
+        {
+            return Ok(());
+        }
+
+        while let Some(msg) = ws.next().await {
+            let parsed = match msg? {
+                Message::Text(text) => self.parse(&text, &audio_format),
+                Message::Binary(data) => Ok(self.chunk(&audio_format, data.len(), true)),
+                Message::Close(_) => break,
+                _ => continue,
+            };
+
+            let chunk = match parsed {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    let error = MockReply::input_error(&e.to_string());
+                    for message in error.to_messages(timestamps) {
+                        ws.send(message).await?;
+                    }
+                    continue;
+                }
+            };
+
+            chunks += 1;
+            let mut triggers = vec![MockTrigger::Chunk(chunks)];
+            if chunk.commit {
+                commits += 1;
+                triggers.push(MockTrigger::Commit(commits));
+                triggers.push(MockTrigger::EveryCommit);
+            }
+            self.received.lock().unwrap().push(chunk);
+
+            if !self.reply(&mut ws, &triggers, timestamps).await? {
+                return Ok(());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse(&self, text: &str, audio_format: &str) -> Result<ReceivedChunk> {
+        let ClientMessage::AudioChunk {
+            audio_base_64,
+            commit,
+            previous_text,
+            ..
+        } = serde_json::from_str(text).map_err(|e| anyhow!("Invalid message: {}", e))?;
+        let audio = base64::engine::general_purpose::STANDARD
+            .decode(audio_base_64)
+            .map_err(|e| anyhow!("Invalid audio: {}", e))?;
+
+        Ok(ReceivedChunk {
+            commit: commit.unwrap_or(false),
+            previous_text,
+            ..self.chunk(audio_format, audio.len(), false)
+        })
+    }
+
+    /// A chunk of `bytes` audio without commit flag or context
+    fn chunk(&self, audio_format: &str, bytes: usize, binary: bool) -> ReceivedChunk {
+        let samples = match audio_format {
+            "ulaw_8000" => bytes,
+            format if format.starts_with("pcm_") => bytes / 2,
+            _ => 0,
+        };
+
+        ReceivedChunk {
+            connection: self.index,
+            samples,
+            bytes,
+            binary,
+            commit: false,
+            previous_text: None,
+        }
+    }
+
+    /// Send the replies for the triggers that fired
+    ///
+    /// # Returns
+    /// `false` if the connection was ended
+    async fn reply(
+        &self,
+        ws: &mut WebSocketStream<TcpStream>,
+        triggers: &[MockTrigger],
+        timestamps: bool,
+    ) -> Result<bool> {
+        let mut replies = self.script.replies(triggers).peekable();
+        if replies.peek().is_some() && !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+
+        for reply in replies {
+            match reply {
+                MockReply::Delay(delay) => tokio::time::sleep(*delay).await,
+                MockReply::Disconnect => {
+                    debug!("Mock server dropping connection {}", self.index);
+                    return Ok(false);
+                }
+                MockReply::Close => {
+                    ws.close(None).await?;
+                    return Ok(false);
+                }
+                message => {
+                    for message in message.to_messages(timestamps) {
+                        ws.send(message).await?;
+                    }
+                }
+            }
+        }
+
+        Ok(true)
+    }
+}
@@ -1,5 +1,11 @@
 pub mod batch;
+pub mod elevenlabs;
+pub mod encoding;
+#[cfg(any(test, feature = "test-support"))]
+pub mod mock_server;
+pub mod options;
 pub mod protocol;
+pub mod provider;
 pub mod retry;
 pub mod websocket;
 
@@ -7,6 +13,17 @@ pub mod websocket;
 mod tests;
 
 pub use batch::MessageBatcher;
-pub use protocol::{ClientMessage, ServerMessage};
+pub use elevenlabs::ElevenLabsProvider;
+pub use encoding::AudioEncoding;
+#[cfg(feature = "test-support")]
+pub use mock_server::{
+    MockConnection, MockReply, MockScribeServer, MockScribeServerBuilder, MockTrigger,
+    ReceivedChunk,
+};
+pub use options::SessionOptions;
+pub use protocol::{ClientMessage, ServerMessage, WordKind, WordTiming};
+pub use provider::{
+    AudioSink, ProviderConnection, ProviderKind, SpeechToTextProvider, TranscriptEvent,
+};
 pub use retry::RetryPolicy;
 pub use websocket::{ConnectionState, WebSocketClient};
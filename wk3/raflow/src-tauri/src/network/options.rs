@@ -0,0 +1,187 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use url::Url;
+
+use super::encoding::AudioEncoding;
+
+/// Default realtime model
+pub const DEFAULT_MODEL_ID: &str = "scribe_v2_realtime";
+
+/// Default language (Mandarin Chinese)
+pub const DEFAULT_LANGUAGE_CODE: &str = "zho";
+
+/// Language value the frontend uses for auto-detection
+pub const AUTO_DETECT_LANGUAGE: &str = "auto";
+
+/// Per-session transcription options
+///
+/// Sent by the frontend with `start_recording` and turned into query
+/// parameters when the provider connection is opened.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionOptions {
+    /// Language code such as `zho`, `yue` or `eng`
+    ///
+    /// `None` (or `"auto"`) lets the provider detect the language.
+    pub language_code: Option<String>,
+    /// Model used for transcription
+    pub model_id: String,
+    /// Additional provider-specific query parameters
+    pub extra_params: BTreeMap<String, String>,
+    /// How audio is uploaded, providers fall back to base64 PCM for
+    /// encodings they don't support
+    pub audio_encoding: AudioEncoding,
+}
+
+impl Default for SessionOptions {
+    fn default() -> Self {
+        Self {
+            language_code: Some(DEFAULT_LANGUAGE_CODE.to_string()),
+            model_id: DEFAULT_MODEL_ID.to_string(),
+            extra_params: BTreeMap::new(),
+            audio_encoding: AudioEncoding::default(),
+        }
+    }
+}
+
+impl SessionOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_language(mut self, language_code: impl Into<String>) -> Self {
+        self.language_code = Some(language_code.into());
+        self
+    }
+
+    /// Let the provider detect the spoken language
+    pub fn with_auto_detect(mut self) -> Self {
+        self.language_code = None;
+        self
+    }
+
+    pub fn with_model(mut self, model_id: impl Into<String>) -> Self {
+        self.model_id = model_id.into();
+        self
+    }
+
+    pub fn with_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extra_params.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_audio_encoding(mut self, encoding: AudioEncoding) -> Self {
+        self.audio_encoding = encoding;
+        self
+    }
+
+    /// The requested language, or `None` when auto-detecting
+    pub fn language(&self) -> Option<&str> {
+        self.language_code
+            .as_deref()
+            .map(str::trim)
+            .filter(|code| !code.is_empty() && !code.eq_ignore_ascii_case(AUTO_DETECT_LANGUAGE))
+    }
+
+    /// Build the connection URL for these options
+    ///
+    /// # Arguments
+    /// * `base_url` - Provider endpoint without query string
+    pub fn to_url(&self, base_url: &str) -> Result<String> {
+        let mut url =
+            Url::parse(base_url).map_err(|e| anyhow!("Invalid endpoint {}: {}", base_url, e))?;
+
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("model_id", &self.model_id);
+            if let Some(language) = self.language() {
+                query.append_pair("language_code", language);
+            }
+            // The default format is left to the provider, as before
+            if self.audio_encoding != AudioEncoding::default() {
+                query.append_pair("audio_format", self.audio_encoding.audio_format());
+            }
+            // The encoding decides the format, the audio is sent in no other
+            for (key, value) in &self.extra_params {
+                if !matches!(key.as_str(), "model_id" | "language_code" | "audio_format") {
+                    query.append_pair(key, value);
+                }
+            }
+        }
+
+        Ok(url.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENDPOINT: &str = "wss://api.elevenlabs.io/v1/speech-to-text/realtime";
+
+    #[test]
+    fn test_default_url_matches_previous_behavior() {
+        let url = SessionOptions::default().to_url(ENDPOINT).unwrap();
+        assert_eq!(
+            url,
+            format!("{}?model_id=scribe_v2_realtime&language_code=zho", ENDPOINT)
+        );
+    }
+
+    #[test]
+    fn test_auto_detect_omits_language() {
+        let url = SessionOptions::new()
+            .with_auto_detect()
+            .to_url(ENDPOINT)
+            .unwrap();
+        assert!(!url.contains("language_code"));
+
+        let url = SessionOptions::new()
+            .with_language("auto")
+            .to_url(ENDPOINT)
+            .unwrap();
+        assert!(!url.contains("language_code"));
+    }
+
+    #[test]
+    fn test_extra_params_are_encoded() {
+        let url = SessionOptions::new()
+            .with_language("eng")
+            .with_model("scribe_v3")
+            .with_param("commit_strategy", "vad")
+            .with_param("model_id", "ignored")
+            .with_param("note", "a b&c")
+            .to_url(ENDPOINT)
+            .unwrap();
+
+        assert!(url.contains("model_id=scribe_v3"));
+        assert!(url.contains("language_code=eng"));
+        assert!(url.contains("commit_strategy=vad"));
+        assert!(url.contains("note=a+b%26c"));
+        assert!(!url.contains("ignored"));
+    }
+
+    #[test]
+    fn test_audio_format() {
+        let url = SessionOptions::new()
+            .with_param("audio_format", "ulaw_8000")
+            .to_url(ENDPOINT)
+            .unwrap();
+        assert!(!url.contains("audio_format"));
+
+        let url = SessionOptions::new()
+            .with_audio_encoding(AudioEncoding::Mulaw)
+            .with_param("audio_format", "pcm_44100")
+            .to_url(ENDPOINT)
+            .unwrap();
+        assert!(url.ends_with("language_code=zho&audio_format=ulaw_8000"));
+    }
+
+    #[test]
+    fn test_deserialize_partial_options() {
+        let options: SessionOptions = serde_json::from_str(r#"{"language_code":null}"#).unwrap();
+        assert_eq!(options.language(), None);
+        assert_eq!(options.model_id, DEFAULT_MODEL_ID);
+    }
+}
@@ -1,8 +1,12 @@
 use base64::Engine;
 use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::encoding::pcm_s16le;
+use super::provider::TranscriptEvent;
 
 /// Messages sent from client to ElevenLabs Scribe API
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "message_type")]
 pub enum ClientMessage {
     /// Audio chunk with PCM data encoded in base64
@@ -17,6 +21,47 @@ pub enum ClientMessage {
     },
 }
 
+/// Kind of a timed token in a transcript
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WordKind {
+    /// A spoken word
+    #[default]
+    Word,
+    /// Whitespace between words
+    Spacing,
+    /// Non-speech sound such as laughter
+    AudioEvent,
+    /// Kind not known to this version
+    #[serde(other)]
+    Unknown,
+}
+
+/// Timing of a single word in a committed transcript
+///
+/// `start` and `end` are seconds from the start of the session audio.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordTiming {
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+    #[serde(rename = "type", default)]
+    pub kind: WordKind,
+    /// Speaker label when diarization is enabled
+    #[serde(default, alias = "speaker_id")]
+    pub speaker: Option<String>,
+    /// Log probability of the word
+    #[serde(default)]
+    pub logprob: Option<f64>,
+}
+
+impl WordTiming {
+    /// Confidence of the word in [0, 1], if reported
+    pub fn probability(&self) -> Option<f64> {
+        self.logprob.map(|logprob| logprob.exp().min(1.0))
+    }
+}
+
 /// Messages received from ElevenLabs Scribe API
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "message_type")]
@@ -58,7 +103,7 @@ pub enum ServerMessage {
         #[serde(default)]
         confidence: f32,
         #[serde(default)]
-        words: Vec<serde_json::Value>,
+        words: Vec<WordTiming>,
         #[serde(default)]
         language_code: String,
     },
@@ -73,9 +118,7 @@ pub enum ServerMessage {
 
     /// Invalid request error
     #[serde(rename = "invalid_request")]
-    InvalidRequest {
-        error: String,
-    },
+    InvalidRequest { error: String },
 
     /// Session configuration message
     #[serde(rename = "session_config")]
@@ -95,28 +138,41 @@ impl ClientMessage {
 
     /// Create an audio chunk message with optional commit flag
     pub fn audio_chunk_with_commit(samples: &[f32], commit: bool) -> Self {
-        // Convert f32 samples to i16 PCM
-        let pcm_i16: Vec<i16> = samples
-            .iter()
-            .map(|&x| (x.clamp(-1.0, 1.0) * 32767.0) as i16)
-            .collect();
-
-        // Convert to bytes
-        let bytes: Vec<u8> = pcm_i16
-            .iter()
-            .flat_map(|&sample| sample.to_le_bytes())
-            .collect();
-
-        // Encode as base64
-        let audio_base_64 = base64::engine::general_purpose::STANDARD.encode(&bytes);
+        // Scribe v2 Realtime requires sample_rate
+        Self::encoded_chunk(&pcm_s16le(samples), 16000, commit)
+    }
 
+    /// Create an audio chunk message from already encoded audio
+    ///
+    /// # Arguments
+    /// * `audio` - Audio in the format the session was opened with
+    /// * `sample_rate` - Sample rate of the encoded audio
+    /// * `commit` - Finalize the current segment after this chunk
+    pub fn encoded_chunk(audio: &[u8], sample_rate: u32, commit: bool) -> Self {
         ClientMessage::AudioChunk {
-            audio_base_64,
-            sample_rate: 16000, // Scribe v2 Realtime requires sample_rate
+            audio_base_64: base64::engine::general_purpose::STANDARD.encode(audio),
+            sample_rate,
             commit: if commit { Some(true) } else { None },
             previous_text: None,
         }
     }
+
+    /// Attach text that preceded this audio, used by the model as context
+    pub fn with_previous_text(self, text: Option<String>) -> Self {
+        match self {
+            ClientMessage::AudioChunk {
+                audio_base_64,
+                sample_rate,
+                commit,
+                ..
+            } => ClientMessage::AudioChunk {
+                audio_base_64,
+                sample_rate,
+                commit,
+                previous_text: text,
+            },
+        }
+    }
 }
 
 impl ServerMessage {
@@ -146,6 +202,62 @@ impl ServerMessage {
             ServerMessage::InputError { .. } | ServerMessage::InvalidRequest { .. }
         )
     }
+
+    /// Convert into a normalized transcript event
+    ///
+    /// # Returns
+    /// `None` for messages that carry nothing for the session
+    pub fn into_event(self) -> Option<TranscriptEvent> {
+        match self {
+            ServerMessage::SessionStarted {
+                session_id,
+                model_id,
+            } => Some(TranscriptEvent::SessionStarted {
+                session_id,
+                model_id,
+            }),
+            ServerMessage::PartialTranscript { text, .. } => {
+                Some(TranscriptEvent::Partial { text })
+            }
+            ServerMessage::CommittedTranscript {
+                text, confidence, ..
+            } => Some(TranscriptEvent::Committed {
+                text,
+                language_code: None,
+                confidence: reported_confidence(confidence),
+                words: Vec::new(),
+            }),
+            ServerMessage::CommittedTranscriptWithTimestamps {
+                text,
+                confidence,
+                words,
+                language_code,
+                ..
+            } => Some(TranscriptEvent::Committed {
+                text,
+                language_code: Some(language_code).filter(|code| !code.is_empty()),
+                confidence: reported_confidence(confidence),
+                words,
+            }),
+            ServerMessage::InputError { error_message, .. } => Some(TranscriptEvent::Error {
+                message: error_message,
+                fatal: false,
+            }),
+            ServerMessage::InvalidRequest { error } => Some(TranscriptEvent::Error {
+                message: error,
+                fatal: true,
+            }),
+            other => {
+                info!("Other message type received: {:?}", other);
+                None
+            }
+        }
+    }
+}
+
+/// The API omits the confidence on some messages, which parses as 0
+fn reported_confidence(confidence: f32) -> Option<f32> {
+    Some(confidence).filter(|c| *c > 0.0)
 }
 
 #[cfg(test)]
@@ -162,6 +274,17 @@ mod tests {
         assert!(json.contains("audio_base_64"));
     }
 
+    #[test]
+    fn test_previous_text_serialization() {
+        let msg = ClientMessage::audio_chunk(&[0.0]);
+        let json = serde_json::to_value(&msg).unwrap();
+        assert!(json.get("previous_text").is_none());
+
+        let msg = msg.with_previous_text(Some("earlier words".to_string()));
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["previous_text"], "earlier words");
+    }
+
     #[test]
     fn test_server_message_deserialization() {
         let json = r#"{"message_type":"partial_transcript","text":"Hello","created_at_ms":1234567890,"normalized_text":"hello"}"#;
@@ -179,4 +302,55 @@ mod tests {
         assert!(msg.is_final());
         assert_eq!(msg.text(), Some("Hello world"));
     }
+
+    #[test]
+    fn test_committed_transcript_with_timestamps() {
+        let json = r#"{
+            "message_type": "committed_transcript_with_timestamps",
+            "text": "Hello world",
+            "language_code": "eng",
+            "words": [
+                {"text": "Hello", "start": 0.12, "end": 0.48, "type": "word", "speaker_id": "speaker_0", "logprob": -0.05},
+                {"text": " ", "start": 0.48, "end": 0.52, "type": "spacing"},
+                {"text": "(laughs)", "start": 0.6, "end": 1.1, "type": "audio_event"},
+                {"text": "world", "start": 1.2, "end": 1.5, "type": "something_new", "logprob": 0.0}
+            ]
+        }"#;
+        let msg: ServerMessage = serde_json::from_str(json).unwrap();
+
+        let ServerMessage::CommittedTranscriptWithTimestamps { words, .. } = msg else {
+            panic!("unexpected message: {:?}", msg);
+        };
+        assert_eq!(words.len(), 4);
+        assert_eq!(
+            words[0],
+            WordTiming {
+                text: "Hello".to_string(),
+                start: 0.12,
+                end: 0.48,
+                kind: WordKind::Word,
+                speaker: Some("speaker_0".to_string()),
+                logprob: Some(-0.05),
+            }
+        );
+        assert_eq!(words[1].kind, WordKind::Spacing);
+        assert_eq!(words[1].speaker, None);
+        assert_eq!(words[1].probability(), None);
+        assert_eq!(words[2].kind, WordKind::AudioEvent);
+        assert_eq!(words[3].kind, WordKind::Unknown);
+        assert_eq!(words[3].probability(), Some(1.0));
+    }
+
+    #[test]
+    fn test_word_timing_serializes_for_frontend() {
+        let word: WordTiming =
+            serde_json::from_str(r#"{"text":"Hi","start":1.0,"end":1.25,"speaker_id":"s1"}"#)
+                .unwrap();
+        assert_eq!(word.kind, WordKind::Word);
+
+        let value = serde_json::to_value(&word).unwrap();
+        assert_eq!(value["type"], "word");
+        assert_eq!(value["speaker"], "s1");
+        assert_eq!(value["end"], 1.25);
+    }
 }
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use super::elevenlabs::ElevenLabsProvider;
+use super::options::SessionOptions;
+use super::protocol::WordTiming;
+use crate::utils::Metrics;
+
+/// Transcript event normalized across providers
+#[derive(Debug, Clone, PartialEq)]
+pub enum TranscriptEvent {
+    /// The provider accepted the session
+    SessionStarted {
+        session_id: String,
+        model_id: String,
+    },
+
+    /// Partial (interim) transcription result
+    Partial { text: String },
+
+    /// Final (committed) transcription result
+    ///
+    /// `language_code` and `confidence` are set when the provider reports
+    /// them, `words` is empty unless the provider sent word timestamps.
+    Committed {
+        text: String,
+        language_code: Option<String>,
+        confidence: Option<f32>,
+        words: Vec<WordTiming>,
+    },
+
+    /// Error reported by the provider
+    ///
+    /// Fatal errors end the session, others are only reported.
+    Error { message: String, fatal: bool },
+}
+
+/// An open streaming connection to a provider
+pub struct ProviderConnection {
+    /// Sends audio to the provider
+    pub sink: Box<dyn AudioSink>,
+    /// Transcript events, closed when the connection ends
+    pub events: mpsc::Receiver<TranscriptEvent>,
+}
+
+/// Write half of a provider connection
+pub trait AudioSink: Send {
+    /// Send a chunk of 16kHz mono audio
+    ///
+    /// When `commit` is set the provider should finalize the current
+    /// segment after this chunk.
+    fn send_audio<'a>(&'a mut self, samples: &'a [f32], commit: bool) -> BoxFuture<'a, Result<()>>;
+
+    /// Set text that preceded this connection, e.g. after a reconnect
+    ///
+    /// Sent as context with the next chunk. Providers that don't support
+    /// context ignore it.
+    fn set_previous_text(&mut self, _text: String) {}
+
+    /// Finalize the current segment without sending more audio
+    fn commit(&mut self) -> BoxFuture<'_, Result<()>> {
+        self.send_audio(&[], true)
+    }
+
+    /// Close the connection gracefully
+    fn close(&mut self) -> BoxFuture<'_, Result<()>>;
+}
+
+/// A realtime speech-to-text backend
+pub trait SpeechToTextProvider: Send + Sync {
+    /// Human readable provider name, used for logging
+    fn name(&self) -> &str;
+
+    /// Open a new streaming session
+    ///
+    /// # Arguments
+    /// * `options` - Language, model and query options for the session
+    fn connect<'a>(
+        &'a self,
+        options: &'a SessionOptions,
+    ) -> BoxFuture<'a, Result<ProviderConnection>>;
+}
+
+/// Available speech-to-text backends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    /// ElevenLabs Scribe v2 Realtime
+    #[default]
+    ElevenLabs,
+}
+
+impl ProviderKind {
+    /// Stable identifier, used e.g. as the credential account name
+    pub fn id(self) -> &'static str {
+        match self {
+            ProviderKind::ElevenLabs => "elevenlabs",
+        }
+    }
+
+    /// Create a provider of this kind
+    ///
+    /// # Arguments
+    /// * `api_key` - Credential for the provider
+    /// * `endpoint` - Endpoint override, `None` for the provider default
+    /// * `metrics` - Records the upload traffic
+    pub fn create(
+        self,
+        api_key: String,
+        endpoint: Option<String>,
+        metrics: Metrics,
+    ) -> Arc<dyn SpeechToTextProvider> {
+        match self {
+            ProviderKind::ElevenLabs => {
+                let provider = ElevenLabsProvider::new(api_key).with_metrics(metrics);
+                Arc::new(match endpoint {
+                    Some(endpoint) => provider.with_endpoint(endpoint),
+                    None => provider,
+                })
+            }
+        }
+    }
+}
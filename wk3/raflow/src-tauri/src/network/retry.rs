@@ -32,6 +32,11 @@ impl RetryPolicy {
         self
     }
 
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
     /// Execute an async operation with retry logic
     pub async fn execute<F, Fut, T>(&self, operation: F) -> Result<T, RAFlowError>
     where
@@ -53,10 +58,7 @@ impl RetryPolicy {
                 }
                 Err(err) => {
                     if attempt >= self.max_attempts || !err.is_retryable() {
-                        warn!(
-                            "Operation failed after {} attempts: {}",
-                            attempt, err
-                        );
+                        warn!("Operation failed after {} attempts: {}", attempt, err);
                         return Err(err);
                     }
 
@@ -97,10 +99,7 @@ impl RetryPolicy {
                 }
                 Err(err) => {
                     if attempt >= self.max_attempts || !err.is_retryable() {
-                        warn!(
-                            "Operation failed after {} attempts: {}",
-                            attempt, err
-                        );
+                        warn!("Operation failed after {} attempts: {}", attempt, err);
                         return Err(err);
                     }
 
@@ -131,16 +130,17 @@ impl Default for RetryPolicy {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
 
     #[tokio::test]
     async fn test_retry_success() {
         let policy = RetryPolicy::new().with_max_attempts(3);
-        let mut attempts = 0;
+        let attempts = AtomicU32::new(0);
+        let counter = &attempts;
 
         let result = policy
-            .execute(|| async {
-                attempts += 1;
-                if attempts < 2 {
+            .execute(|| async move {
+                if counter.fetch_add(1, Ordering::SeqCst) + 1 < 2 {
                     Err(RAFlowError::Network("Temporary error".to_string()))
                 } else {
                     Ok(42)
@@ -150,38 +150,40 @@ mod tests {
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), 42);
-        assert_eq!(attempts, 2);
+        assert_eq!(attempts.into_inner(), 2);
     }
 
     #[tokio::test]
     async fn test_retry_max_attempts() {
         let policy = RetryPolicy::new().with_max_attempts(2);
-        let mut attempts = 0;
+        let attempts = AtomicU32::new(0);
+        let counter = &attempts;
 
-        let result = policy
-            .execute(|| async {
-                attempts += 1;
+        let result: Result<(), _> = policy
+            .execute(|| async move {
+                counter.fetch_add(1, Ordering::SeqCst);
                 Err(RAFlowError::Network("Persistent error".to_string()))
             })
             .await;
 
         assert!(result.is_err());
-        assert_eq!(attempts, 2);
+        assert_eq!(attempts.into_inner(), 2);
     }
 
     #[tokio::test]
     async fn test_retry_non_retryable() {
         let policy = RetryPolicy::new();
-        let mut attempts = 0;
+        let attempts = AtomicU32::new(0);
+        let counter = &attempts;
 
-        let result = policy
-            .execute(|| async {
-                attempts += 1;
+        let result: Result<(), _> = policy
+            .execute(|| async move {
+                counter.fetch_add(1, Ordering::SeqCst);
                 Err(RAFlowError::Permission("Access denied".to_string()))
             })
             .await;
 
         assert!(result.is_err());
-        assert_eq!(attempts, 1); // Should not retry
+        assert_eq!(attempts.into_inner(), 1); // Should not retry
     }
 }
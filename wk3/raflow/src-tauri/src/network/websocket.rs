@@ -1,15 +1,22 @@
 use anyhow::{anyhow, Result};
-use futures_util::{SinkExt, StreamExt};
+use futures_util::{SinkExt, Stream, StreamExt};
 use tokio::net::TcpStream;
 use tokio::sync::mpsc;
-use tokio::time::{sleep, Duration};
 use tokio_tungstenite::{
-    connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream,
+    connect_async, tungstenite, tungstenite::Message, MaybeTlsStream, WebSocketStream,
 };
 use tracing::{debug, error, info, warn};
 
+use super::options::SessionOptions;
 use super::protocol::{ClientMessage, ServerMessage};
 
+/// Write half of a Scribe WebSocket connection
+pub type WsSink =
+    futures_util::stream::SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+
+/// Read half of a Scribe WebSocket connection
+pub type WsStream = futures_util::stream::SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>;
+
 // Initialize rustls crypto provider
 fn init_crypto_provider() {
     use std::sync::Once;
@@ -30,12 +37,14 @@ pub enum ConnectionState {
     Reconnecting,
 }
 
+/// Scribe v2 Realtime API endpoint for speech-to-text
+pub const DEFAULT_ENDPOINT: &str = "wss://api.elevenlabs.io/v1/speech-to-text/realtime";
+
 pub struct WebSocketClient {
     url: String,
     api_key: String,
+    options: SessionOptions,
     state: ConnectionState,
-    reconnect_attempts: usize,
-    max_reconnect_attempts: usize,
 }
 
 impl WebSocketClient {
@@ -44,43 +53,48 @@ impl WebSocketClient {
     /// # Arguments
     /// * `api_key` - ElevenLabs API key
     pub fn new(api_key: String) -> Self {
-        // Scribe v2 Realtime API endpoint for speech-to-text
-        let url = "wss://api.elevenlabs.io/v1/speech-to-text/realtime".to_string();
         Self {
-            url,
+            url: DEFAULT_ENDPOINT.to_string(),
             api_key,
+            options: SessionOptions::default(),
             state: ConnectionState::Disconnected,
-            reconnect_attempts: 0,
-            max_reconnect_attempts: 3,
         }
     }
 
+    /// Connect to a different endpoint, e.g. a proxy or a local test server
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = url.into();
+        self
+    }
+
+    /// Set the language, model and query options used when connecting
+    pub fn with_options(mut self, options: SessionOptions) -> Self {
+        self.options = options;
+        self
+    }
+
     /// Connect to the WebSocket server
     ///
     /// # Returns
     /// A tuple of (write_sink, read_stream) for sending and receiving messages
-    pub async fn connect(
-        &mut self,
-    ) -> Result<(
-        futures_util::stream::SplitSink<
-            WebSocketStream<MaybeTlsStream<TcpStream>>,
-            Message,
-        >,
-        futures_util::stream::SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
-    )> {
+    pub async fn connect(&mut self) -> Result<(WsSink, WsStream)> {
         // Initialize crypto provider (idempotent)
         init_crypto_provider();
 
         self.state = ConnectionState::Connecting;
 
-        // Scribe v2 Realtime is the only supported model for WebSocket
         // Supported Chinese language codes from API: zho, yue, nan
         // zho = Mandarin Chinese (Standard Chinese)
         // yue = Cantonese
         // nan = Min Nan (Hokkien/Taiwanese)
-        let url = format!("{}?model_id=scribe_v2_realtime&language_code=zho", self.url);
+        let url = self.options.to_url(&self.url)?;
 
-        info!("Connecting to Scribe v2 Realtime WebSocket with Mandarin Chinese (zho): {}", url);
+        info!(
+            "Connecting to {} WebSocket with language {}: {}",
+            self.options.model_id,
+            self.options.language().unwrap_or("auto-detect"),
+            url
+        );
 
         // Create request with authorization header
         use tokio_tungstenite::tungstenite::client::IntoClientRequest;
@@ -88,8 +102,8 @@ impl WebSocketClient {
         let mut request = url.into_client_request()?;
 
         // Add API key header
-        let header_value = HeaderValue::from_str(&self.api_key)
-            .map_err(|_| anyhow!("Invalid API key format"))?;
+        let header_value =
+            HeaderValue::from_str(&self.api_key).map_err(|_| anyhow!("Invalid API key format"))?;
 
         request.headers_mut().insert("xi-api-key", header_value);
 
@@ -106,7 +120,6 @@ impl WebSocketClient {
         info!("Response headers: {:?}", response.headers());
 
         self.state = ConnectionState::Connected;
-        self.reconnect_attempts = 0;
 
         let (write, read) = ws_stream.split();
 
@@ -115,18 +128,23 @@ impl WebSocketClient {
     }
 
     /// Send audio data through WebSocket
-    pub async fn send_audio(
-        sink: &mut futures_util::stream::SplitSink<
-            WebSocketStream<MaybeTlsStream<TcpStream>>,
-            Message,
-        >,
-        audio_data: &[f32],
-        commit: bool,
-    ) -> Result<()> {
+    pub async fn send_audio(sink: &mut WsSink, audio_data: &[f32], commit: bool) -> Result<()> {
         let msg = ClientMessage::audio_chunk_with_commit(audio_data, commit);
-        let json = serde_json::to_string(&msg)?;
+        Self::send_message(sink, &msg).await.map(|_| ())
+    }
 
-        if commit {
+    /// Send a client message through WebSocket
+    ///
+    /// # Returns
+    /// Bytes sent, without the frame header
+    pub async fn send_message(sink: &mut WsSink, msg: &ClientMessage) -> Result<usize> {
+        let json = serde_json::to_string(msg)?;
+        let bytes = json.len();
+
+        if let ClientMessage::AudioChunk {
+            commit: Some(true), ..
+        } = msg
+        {
             info!("Sending audio with COMMIT flag (speech segment ended)");
         }
 
@@ -135,14 +153,29 @@ impl WebSocketClient {
             anyhow!("Send error: {}", e)
         })?;
 
-        Ok(())
+        Ok(bytes)
+    }
+
+    /// Send encoded audio in a binary frame
+    ///
+    /// # Returns
+    /// Bytes sent, without the frame header
+    pub async fn send_binary(sink: &mut WsSink, data: Vec<u8>) -> Result<usize> {
+        let bytes = data.len();
+        sink.send(Message::Binary(data.into())).await.map_err(|e| {
+            error!("Failed to send audio: {}", e);
+            anyhow!("Send error: {}", e)
+        })?;
+
+        Ok(bytes)
     }
 
     /// Start receiving messages from WebSocket
+    ///
+    /// Takes the [`WsStream`] of a connection, possibly wrapped to observe
+    /// the raw messages.
     pub async fn receive_loop(
-        mut stream: futures_util::stream::SplitStream<
-            WebSocketStream<MaybeTlsStream<TcpStream>>,
-        >,
+        mut stream: impl Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
         tx: mpsc::Sender<ServerMessage>,
     ) -> Result<()> {
         info!("Starting WebSocket receive loop");
@@ -167,7 +200,10 @@ impl WebSocketClient {
                 }
                 Ok(Message::Close(frame)) => {
                     if let Some(cf) = frame {
-                        error!("WebSocket closed by server - Code: {}, Reason: {}", cf.code, cf.reason);
+                        error!(
+                            "WebSocket closed by server - Code: {}, Reason: {}",
+                            cf.code, cf.reason
+                        );
                     } else {
                         info!("WebSocket closed by server (no close frame)");
                     }
@@ -197,45 +233,6 @@ impl WebSocketClient {
         Ok(())
     }
 
-    /// Reconnect with exponential backoff
-    pub async fn reconnect_with_backoff(&mut self) -> Result<(
-        futures_util::stream::SplitSink<
-            WebSocketStream<MaybeTlsStream<TcpStream>>,
-            Message,
-        >,
-        futures_util::stream::SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
-    )> {
-        self.state = ConnectionState::Reconnecting;
-
-        while self.reconnect_attempts < self.max_reconnect_attempts {
-            self.reconnect_attempts += 1;
-
-            let backoff_ms = 1000 * 2_u64.pow(self.reconnect_attempts as u32 - 1);
-            warn!(
-                "Reconnecting... (attempt {}/{}, waiting {}ms)",
-                self.reconnect_attempts, self.max_reconnect_attempts, backoff_ms
-            );
-
-            sleep(Duration::from_millis(backoff_ms)).await;
-
-            match self.connect().await {
-                Ok(streams) => {
-                    info!("Reconnected successfully");
-                    return Ok(streams);
-                }
-                Err(e) => {
-                    error!("Reconnection failed: {}", e);
-                }
-            }
-        }
-
-        self.state = ConnectionState::Disconnected;
-        Err(anyhow!(
-            "Failed to reconnect after {} attempts",
-            self.max_reconnect_attempts
-        ))
-    }
-
     /// Get current connection state
     pub fn state(&self) -> &ConnectionState {
         &self.state
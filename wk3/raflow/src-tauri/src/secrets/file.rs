@@ -0,0 +1,169 @@
+use anyhow::{anyhow, Result};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::info;
+
+use super::store::SecretStore;
+
+/// Encrypted secrets file
+pub const SECRETS_FILE: &str = "secrets.enc";
+
+/// Key used to encrypt the secrets file
+pub const SECRETS_KEY_FILE: &str = "secrets.key";
+
+/// Nonce length of ChaCha20-Poly1305
+const NONCE_LEN: usize = 12;
+
+/// Encrypted-file fallback for machines without a credential store
+///
+/// Secrets are sealed with ChaCha20-Poly1305 using a random key kept next
+/// to the file with owner-only permissions. This keeps the key out of the
+/// settings file and logs, but is weaker than the platform store and only
+/// meant for headless and test environments.
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    key_path: PathBuf,
+    // Serializes read-modify-write cycles
+    lock: Mutex<()>,
+}
+
+impl EncryptedFileStore {
+    /// Create a store in the given directory
+    ///
+    /// Files are created lazily on the first write.
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            path: dir.join(SECRETS_FILE),
+            key_path: dir.join(SECRETS_KEY_FILE),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn cipher(&self, create: bool) -> Result<Option<ChaCha20Poly1305>> {
+        let key = match fs::read(&self.key_path) {
+            Ok(bytes) => {
+                let bytes: [u8; 32] = bytes
+                    .try_into()
+                    .map_err(|_| anyhow!("Corrupt secrets key {}", self.key_path.display()))?;
+                Key::from(bytes)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                if !create {
+                    return Ok(None);
+                }
+                let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+                write_private(&self.key_path, &key)?;
+                info!("Created secrets key {}", self.key_path.display());
+                key
+            }
+            Err(e) => return Err(anyhow!("Failed to read secrets key: {}", e)),
+        };
+
+        Ok(Some(ChaCha20Poly1305::new(&key)))
+    }
+
+    fn read_all(&self) -> Result<BTreeMap<String, String>> {
+        let data = match fs::read(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(e) => return Err(anyhow!("Failed to read secrets file: {}", e)),
+        };
+
+        let cipher = self
+            .cipher(false)?
+            .ok_or_else(|| anyhow!("Secrets key {} is missing", self.key_path.display()))?;
+
+        if data.len() < NONCE_LEN {
+            return Err(anyhow!("Secrets file {} is truncated", self.path.display()));
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let nonce: [u8; NONCE_LEN] = nonce.try_into()?;
+        let plaintext = cipher
+            .decrypt(&Nonce::from(nonce), ciphertext)
+            .map_err(|_| anyhow!("Failed to decrypt secrets file (wrong key or tampered)"))?;
+
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    fn write_all(&self, secrets: &BTreeMap<String, String>) -> Result<()> {
+        let cipher = self
+            .cipher(true)?
+            .ok_or_else(|| anyhow!("Failed to create secrets key"))?;
+
+        let plaintext = serde_json::to_vec(secrets)?;
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_slice())
+            .map_err(|_| anyhow!("Failed to encrypt secrets"))?;
+
+        let mut data = nonce.to_vec();
+        data.extend_from_slice(&ciphertext);
+
+        // Write to a temporary file first so a crash never leaves a half-written file
+        let tmp_path = self.path.with_extension("tmp");
+        write_private(&tmp_path, &data)?;
+        fs::rename(&tmp_path, &self.path)
+            .map_err(|e| anyhow!("Failed to replace secrets file: {}", e))
+    }
+}
+
+impl SecretStore for EncryptedFileStore {
+    fn name(&self) -> &str {
+        "encrypted_file"
+    }
+
+    fn get(&self, account: &str) -> Result<Option<String>> {
+        let _guard = self.lock.lock().unwrap();
+        Ok(self.read_all()?.remove(account))
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut secrets = self.read_all()?;
+        secrets.insert(account.to_string(), secret.to_string());
+        self.write_all(&secrets)
+    }
+
+    fn delete(&self, account: &str) -> Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut secrets = self.read_all()?;
+        if secrets.remove(account).is_some() {
+            self.write_all(&secrets)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write a file readable only by the current user
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)
+            .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?;
+        file.write_all(data)?;
+        file.sync_all()?;
+    }
+
+    #[cfg(not(unix))]
+    {
+        fs::write(path, data).map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?;
+    }
+
+    Ok(())
+}
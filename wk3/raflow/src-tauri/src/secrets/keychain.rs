@@ -0,0 +1,67 @@
+use anyhow::{anyhow, Result};
+use keyring::{Entry, Error as KeyringError};
+use tracing::debug;
+
+use super::store::SecretStore;
+
+/// Platform credential store
+///
+/// macOS Keychain, Windows Credential Manager or the Secret Service
+/// over D-Bus on Linux.
+pub struct KeychainStore {
+    service: String,
+}
+
+impl KeychainStore {
+    /// Create a store for the given service name
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    /// Check whether the platform store can be reached
+    ///
+    /// Fails e.g. on headless Linux machines without a Secret Service.
+    pub fn is_available(&self) -> bool {
+        match self.get("availability-probe") {
+            Ok(_) => true,
+            Err(e) => {
+                debug!("Platform credential store unavailable: {}", e);
+                false
+            }
+        }
+    }
+
+    fn entry(&self, account: &str) -> Result<Entry> {
+        Entry::new(&self.service, account)
+            .map_err(|e| anyhow!("Invalid credential entry {}: {}", account, e))
+    }
+}
+
+impl SecretStore for KeychainStore {
+    fn name(&self) -> &str {
+        "keychain"
+    }
+
+    fn get(&self, account: &str) -> Result<Option<String>> {
+        match self.entry(account)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(KeyringError::NoEntry) => Ok(None),
+            Err(e) => Err(anyhow!("Failed to read credential: {}", e)),
+        }
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<()> {
+        self.entry(account)?
+            .set_password(secret)
+            .map_err(|e| anyhow!("Failed to store credential: {}", e))
+    }
+
+    fn delete(&self, account: &str) -> Result<()> {
+        match self.entry(account)?.delete_credential() {
+            Ok(()) | Err(KeyringError::NoEntry) => Ok(()),
+            Err(e) => Err(anyhow!("Failed to delete credential: {}", e)),
+        }
+    }
+}
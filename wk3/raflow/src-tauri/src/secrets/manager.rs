@@ -0,0 +1,80 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use tracing::{info, warn};
+
+use super::file::EncryptedFileStore;
+use super::keychain::KeychainStore;
+use super::store::SecretStore;
+use crate::network::ProviderKind;
+
+/// Service name credentials are registered under
+pub const KEYCHAIN_SERVICE: &str = "com.raflow.app";
+
+/// Environment variable that forces a backend (`keychain` or `file`)
+pub const BACKEND_ENV: &str = "RAFLOW_SECRETS_BACKEND";
+
+/// Provider credentials backed by the best available secret store
+pub struct SecretManager {
+    store: Box<dyn SecretStore>,
+}
+
+impl SecretManager {
+    /// Create a manager on top of a specific store
+    pub fn new(store: Box<dyn SecretStore>) -> Self {
+        Self { store }
+    }
+
+    /// Use the platform credential store, falling back to an encrypted
+    /// file in `data_dir` when it can't be reached
+    ///
+    /// # Arguments
+    /// * `data_dir` - App data directory used by the file fallback
+    pub fn platform(data_dir: &Path) -> Self {
+        let forced = std::env::var(BACKEND_ENV).ok();
+
+        if forced.as_deref() != Some("file") {
+            let keychain = KeychainStore::new(KEYCHAIN_SERVICE);
+            if forced.as_deref() == Some("keychain") || keychain.is_available() {
+                info!("🔐 Using platform credential store");
+                return Self::new(Box::new(keychain));
+            }
+            warn!("⚠️  Platform credential store unavailable, using encrypted file fallback");
+        }
+
+        info!("🔐 Using encrypted secrets file in {}", data_dir.display());
+        Self::new(Box::new(EncryptedFileStore::new(data_dir)))
+    }
+
+    /// Name of the backend in use
+    pub fn backend(&self) -> &str {
+        self.store.name()
+    }
+
+    /// Read the API key of a provider
+    pub fn api_key(&self, provider: ProviderKind) -> Result<Option<String>> {
+        Ok(self.store.get(provider.id())?.filter(|key| !key.is_empty()))
+    }
+
+    /// Store the API key of a provider
+    pub fn set_api_key(&self, provider: ProviderKind, api_key: &str) -> Result<()> {
+        let api_key = api_key.trim();
+        if api_key.is_empty() {
+            return Err(anyhow!("API key must not be empty"));
+        }
+
+        self.store.set(provider.id(), api_key)?;
+        info!("API key for {} saved to {}", provider.id(), self.backend());
+        Ok(())
+    }
+
+    /// Remove the API key of a provider
+    pub fn clear_api_key(&self, provider: ProviderKind) -> Result<()> {
+        self.store.delete(provider.id())?;
+        info!(
+            "API key for {} removed from {}",
+            provider.id(),
+            self.backend()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,12 @@
+pub mod file;
+pub mod keychain;
+pub mod manager;
+pub mod store;
+
+#[cfg(test)]
+mod tests;
+
+pub use file::EncryptedFileStore;
+pub use keychain::KeychainStore;
+pub use manager::SecretManager;
+pub use store::SecretStore;
@@ -0,0 +1,21 @@
+use anyhow::Result;
+
+/// Storage backend for credentials
+///
+/// Secrets are addressed by an account name, e.g. the provider id.
+pub trait SecretStore: Send + Sync {
+    /// Backend name, used for logging and the settings UI
+    fn name(&self) -> &str;
+
+    /// Read a secret
+    ///
+    /// # Returns
+    /// `None` if no secret is stored for the account
+    fn get(&self, account: &str) -> Result<Option<String>>;
+
+    /// Store (or replace) a secret
+    fn set(&self, account: &str, secret: &str) -> Result<()>;
+
+    /// Remove a secret, succeeding if none was stored
+    fn delete(&self, account: &str) -> Result<()>;
+}
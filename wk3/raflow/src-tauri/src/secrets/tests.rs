@@ -0,0 +1,117 @@
+use super::*;
+
+#[cfg(test)]
+mod file_store_tests {
+    use super::*;
+    use crate::secrets::file::SECRETS_FILE;
+
+    #[test]
+    fn test_missing_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EncryptedFileStore::new(dir.path());
+        assert_eq!(store.get("elevenlabs").unwrap(), None);
+    }
+
+    #[test]
+    fn test_roundtrip_and_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        EncryptedFileStore::new(dir.path())
+            .set("elevenlabs", "sk-secret")
+            .unwrap();
+
+        let store = EncryptedFileStore::new(dir.path());
+        assert_eq!(
+            store.get("elevenlabs").unwrap(),
+            Some("sk-secret".to_string())
+        );
+
+        // The secret is not stored in plain text
+        let raw = std::fs::read(dir.path().join(SECRETS_FILE)).unwrap();
+        assert!(!raw.windows(9).any(|w| w == b"sk-secret"));
+    }
+
+    #[test]
+    fn test_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EncryptedFileStore::new(dir.path());
+        store.set("a", "1").unwrap();
+        store.set("b", "2").unwrap();
+
+        store.delete("a").unwrap();
+        store.delete("missing").unwrap();
+
+        assert_eq!(store.get("a").unwrap(), None);
+        assert_eq!(store.get("b").unwrap(), Some("2".to_string()));
+    }
+
+    #[test]
+    fn test_tampered_file_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EncryptedFileStore::new(dir.path());
+        store.set("elevenlabs", "sk-secret").unwrap();
+
+        let path = dir.path().join(SECRETS_FILE);
+        let mut raw = std::fs::read(&path).unwrap();
+        let last = raw.len() - 1;
+        raw[last] ^= 0xff;
+        std::fs::write(&path, raw).unwrap();
+
+        assert!(store.get("elevenlabs").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_files_are_private() {
+        use crate::secrets::file::SECRETS_KEY_FILE;
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        EncryptedFileStore::new(dir.path())
+            .set("elevenlabs", "sk-secret")
+            .unwrap();
+
+        for file in [SECRETS_FILE, SECRETS_KEY_FILE] {
+            let mode = std::fs::metadata(dir.path().join(file))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600, "{} is not private", file);
+        }
+    }
+}
+
+#[cfg(test)]
+mod manager_tests {
+    use super::*;
+    use crate::network::ProviderKind;
+
+    fn manager() -> (SecretManager, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = SecretManager::new(Box::new(EncryptedFileStore::new(dir.path())));
+        (manager, dir)
+    }
+
+    #[test]
+    fn test_api_key_lifecycle() {
+        let (manager, _dir) = manager();
+        assert_eq!(manager.backend(), "encrypted_file");
+        assert_eq!(manager.api_key(ProviderKind::ElevenLabs).unwrap(), None);
+
+        manager
+            .set_api_key(ProviderKind::ElevenLabs, "  sk-test \n")
+            .unwrap();
+        assert_eq!(
+            manager.api_key(ProviderKind::ElevenLabs).unwrap(),
+            Some("sk-test".to_string())
+        );
+
+        manager.clear_api_key(ProviderKind::ElevenLabs).unwrap();
+        assert_eq!(manager.api_key(ProviderKind::ElevenLabs).unwrap(), None);
+    }
+
+    #[test]
+    fn test_empty_api_key_is_rejected() {
+        let (manager, _dir) = manager();
+        assert!(manager.set_api_key(ProviderKind::ElevenLabs, "  ").is_err());
+    }
+}
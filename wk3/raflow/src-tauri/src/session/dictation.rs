@@ -0,0 +1,1048 @@
+use anyhow::{anyhow, Result};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{debug, error, info, warn};
+
+use super::events::SessionEvent;
+use super::pipeline::{AudioPipeline, ChunkAction, DEFAULT_PRE_ROLL};
+use super::replay::ReplayBuffer;
+use super::sender::AudioSender;
+use crate::audio::gain::DEFAULT_TARGET_RMS;
+use crate::audio::{
+    AdaptiveVad, AudioCapture, AudioPacket, ChannelMode, SileroVad, Vad, VadEngine,
+    VoiceActivityDetector,
+};
+use crate::input::SmartSpacer;
+use crate::network::{
+    AudioSink, ProviderConnection, RetryPolicy, SessionOptions, SpeechToTextProvider,
+    TranscriptEvent,
+};
+use crate::utils::{Metrics, RAFlowError};
+
+/// How long `stop` waits for the provider to deliver its last transcripts
+const TRANSCRIPT_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Audio kept for replay while waiting for transcripts (30 s at 16kHz)
+const REPLAY_BUFFER_SAMPLES: usize = 30 * 16000;
+
+/// Committed text sent as context after a reconnect
+const MAX_CONTEXT_CHARS: usize = 200;
+
+/// A device delivering no audio for this long is considered failed
+const DEVICE_STALL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Configuration for a dictation session
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    /// Input device name, `None` for the system default
+    pub device_name: Option<String>,
+    /// Devices to fail over to, in order, before the system default
+    pub device_priority: Vec<String>,
+    /// How multichannel devices are mixed down to mono
+    pub channel_mode: ChannelMode,
+    /// Capture buffer size in frames, `None` for the device default
+    pub buffer_size: Option<u32>,
+    /// Voice activity detector, Silero also needs `silero_model`
+    pub vad_engine: VadEngine,
+    /// VAD energy threshold, `None` for the detector default
+    pub vad_threshold: Option<f32>,
+    /// Silero VAD model, only used by [`VadEngine::Silero`]
+    pub silero_model: Option<PathBuf>,
+    /// Audio from before the detected speech start that is sent along
+    pub pre_roll: Duration,
+    /// Noise suppression strength, `None` to leave the audio as is
+    pub noise_suppression: Option<f32>,
+    /// Normalize speech loudness with automatic gain control
+    pub auto_gain: bool,
+    /// Language, model and query options passed to the provider
+    pub options: SessionOptions,
+    /// How to reconnect when the connection drops, `None` to stop instead
+    pub reconnect: Option<RetryPolicy>,
+    /// Where the session's performance is recorded
+    pub metrics: Metrics,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            device_name: None,
+            device_priority: Vec::new(),
+            channel_mode: ChannelMode::default(),
+            buffer_size: None,
+            vad_engine: VadEngine::default(),
+            vad_threshold: None,
+            silero_model: None,
+            pre_roll: DEFAULT_PRE_ROLL,
+            noise_suppression: None,
+            auto_gain: false,
+            options: SessionOptions::default(),
+            reconnect: Some(
+                RetryPolicy::new()
+                    .with_max_attempts(5)
+                    .with_initial_delay(Duration::from_millis(250))
+                    .with_max_delay(Duration::from_secs(4)),
+            ),
+            metrics: Metrics::new(),
+        }
+    }
+}
+
+impl SessionConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_device(mut self, device_name: Option<String>) -> Self {
+        self.device_name = device_name;
+        self
+    }
+
+    pub fn with_device_priority(mut self, devices: Vec<String>) -> Self {
+        self.device_priority = devices;
+        self
+    }
+
+    pub fn with_channel_mode(mut self, mode: ChannelMode) -> Self {
+        self.channel_mode = mode;
+        self
+    }
+
+    pub fn with_buffer_size(mut self, frames: Option<u32>) -> Self {
+        self.buffer_size = frames;
+        self
+    }
+
+    pub fn with_vad_engine(mut self, engine: VadEngine) -> Self {
+        self.vad_engine = engine;
+        self
+    }
+
+    pub fn with_vad_threshold(mut self, threshold: f32) -> Self {
+        self.vad_threshold = Some(threshold);
+        self
+    }
+
+    /// Model for [`VadEngine::Silero`]
+    pub fn with_silero_vad(mut self, model: Option<PathBuf>) -> Self {
+        self.silero_model = model;
+        self
+    }
+
+    pub fn with_pre_roll(mut self, pre_roll: Duration) -> Self {
+        self.pre_roll = pre_roll;
+        self
+    }
+
+    pub fn with_noise_suppression(mut self, strength: Option<f32>) -> Self {
+        self.noise_suppression = strength;
+        self
+    }
+
+    pub fn with_auto_gain(mut self, enabled: bool) -> Self {
+        self.auto_gain = enabled;
+        self
+    }
+
+    pub fn with_options(mut self, options: SessionOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn with_reconnect(mut self, reconnect: Option<RetryPolicy>) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+}
+
+/// Flags shared between the session handle and its background tasks
+struct SessionShared {
+    running: AtomicBool,
+    paused: AtomicBool,
+    speaking: AtomicBool,
+    shutdown_tx: watch::Sender<bool>,
+    /// Committed transcripts received, used to release replayed audio
+    commits_received: AtomicUsize,
+    /// Tail of the committed text, sent as context after a reconnect
+    context: std::sync::Mutex<String>,
+    /// When each commit still waiting for its transcript was sent, oldest first
+    pending_commits: std::sync::Mutex<VecDeque<Instant>>,
+    metrics: Metrics,
+}
+
+impl SessionShared {
+    fn new(metrics: Metrics) -> Self {
+        let (shutdown_tx, _) = watch::channel(false);
+        Self {
+            running: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+            speaking: AtomicBool::new(false),
+            shutdown_tx,
+            commits_received: AtomicUsize::new(0),
+            context: std::sync::Mutex::new(String::new()),
+            pending_commits: std::sync::Mutex::new(VecDeque::new()),
+            metrics,
+        }
+    }
+
+    /// Mark the session as stopped and wake up all tasks
+    ///
+    /// Returns `true` if the session was still running
+    fn shutdown(&self) -> bool {
+        let was_running = self.running.swap(false, Ordering::SeqCst);
+        let _ = self.shutdown_tx.send(true);
+        was_running
+    }
+
+    /// Record that speech ended and its segment was committed
+    fn commit_sent(&self) {
+        self.pending_commits
+            .lock()
+            .unwrap()
+            .push_back(Instant::now());
+    }
+
+    /// Forget the oldest commits beyond `pending`, e.g. after their audio
+    /// was lost
+    fn retain_commits(&self, pending: usize) {
+        let mut commits = self.pending_commits.lock().unwrap();
+        let excess = commits.len().saturating_sub(pending);
+        commits.drain(..excess);
+    }
+
+    /// Record a committed transcript
+    ///
+    /// Transcripts arrive in the order their segments were committed, so
+    /// the oldest pending commit gives the speech-end-to-commit latency.
+    fn commit_received(&self, text: &str) {
+        self.commits_received.fetch_add(1, Ordering::SeqCst);
+        let sent = self.pending_commits.lock().unwrap().pop_front();
+        self.metrics
+            .record_committed_transcript(sent.map(|sent| sent.elapsed()));
+
+        let mut context = self.context.lock().unwrap();
+        context.push_str(text);
+        let excess = context.chars().count().saturating_sub(MAX_CONTEXT_CHARS);
+        if excess > 0 {
+            let start = context.char_indices().nth(excess).map_or(0, |(i, _)| i);
+            context.drain(..start);
+        }
+    }
+
+    fn context(&self) -> String {
+        self.context.lock().unwrap().trim().to_string()
+    }
+}
+
+/// Dictation engine: capture -> resample -> VAD -> provider -> transcript
+///
+/// The session is independent of Tauri. Everything it observes is reported
+/// as [`SessionEvent`]s through the channel returned by [`DictationSession::new`].
+pub struct DictationSession {
+    config: SessionConfig,
+    provider: Arc<dyn SpeechToTextProvider>,
+    shared: Arc<SessionShared>,
+    capture: Arc<Mutex<Option<AudioCapture>>>,
+    event_tx: mpsc::UnboundedSender<SessionEvent>,
+    audio_task: Option<JoinHandle<()>>,
+    transcript_task: Option<JoinHandle<()>>,
+}
+
+impl DictationSession {
+    /// Create a new session and the receiver for its events
+    ///
+    /// # Arguments
+    /// * `config` - Audio and VAD configuration
+    /// * `provider` - Speech-to-text backend to stream to
+    pub fn new(
+        config: SessionConfig,
+        provider: Arc<dyn SpeechToTextProvider>,
+    ) -> (Self, mpsc::UnboundedReceiver<SessionEvent>) {
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let session = Self {
+            shared: Arc::new(SessionShared::new(config.metrics.clone())),
+            config,
+            provider,
+            capture: Arc::new(Mutex::new(None)),
+            event_tx,
+            audio_task: None,
+            transcript_task: None,
+        };
+        (session, event_rx)
+    }
+
+    /// Open the configured input device and start transcribing
+    pub async fn start(&mut self) -> Result<()> {
+        if self.is_running() {
+            return Err(anyhow!("Already recording"));
+        }
+
+        // Increased capacity for better performance
+        let (audio_tx, audio_rx) = mpsc::channel(500);
+        let (error_tx, error_rx) = mpsc::unbounded_channel();
+        let capture = open_capture(
+            &self.config,
+            self.config.device_name.as_deref(),
+            audio_tx.clone(),
+            error_tx.clone(),
+        )?;
+        let sample_rate = capture
+            .sample_rate()
+            .ok_or_else(|| anyhow!("No sample rate"))?;
+        let failover = Failover {
+            config: self.config.clone(),
+            current: capture.device_name().map(str::to_string),
+            capture: self.capture.clone(),
+            audio_tx,
+            error_tx,
+            error_rx,
+            lost: false,
+        };
+        *self.capture.lock().await = Some(capture);
+
+        if let Err(e) = self.run(audio_rx, sample_rate, Some(failover)).await {
+            if let Some(mut capture) = self.capture.lock().await.take() {
+                let _ = capture.stop_stream();
+            }
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Start transcribing audio from an arbitrary source
+    ///
+    /// Used by [`start`](Self::start) for the microphone, and directly by
+    /// tests or headless tools that feed synthetic audio.
+    ///
+    /// # Arguments
+    /// * `audio_rx` - Mono audio packets
+    /// * `sample_rate` - Sample rate of the packets in Hz
+    pub async fn start_with_source(
+        &mut self,
+        audio_rx: mpsc::Receiver<AudioPacket>,
+        sample_rate: u32,
+    ) -> Result<()> {
+        self.run(audio_rx, sample_rate, None).await
+    }
+
+    async fn run(
+        &mut self,
+        audio_rx: mpsc::Receiver<AudioPacket>,
+        sample_rate: u32,
+        failover: Option<Failover>,
+    ) -> Result<()> {
+        if self.is_running() {
+            return Err(anyhow!("Already recording"));
+        }
+
+        let vad: Box<dyn Vad> = match self.config.vad_engine {
+            VadEngine::Silero => {
+                let model = self
+                    .config
+                    .silero_model
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("Silero VAD needs a model"))?;
+                Box::new(SileroVad::load(model)?)
+            }
+            VadEngine::Adaptive => Box::new(AdaptiveVad::new()),
+            VadEngine::Energy => match self.config.vad_threshold {
+                Some(threshold) => Box::new(VoiceActivityDetector::new(threshold)),
+                None => Box::new(VoiceActivityDetector::default()),
+            },
+        };
+        let mut pipeline =
+            AudioPipeline::new(sample_rate, vad)?.with_pre_roll(self.config.pre_roll);
+        if let Some(strength) = self.config.noise_suppression {
+            pipeline = pipeline.with_noise_suppression(strength);
+        }
+        if self.config.auto_gain {
+            pipeline = pipeline.with_auto_gain(DEFAULT_TARGET_RMS);
+        }
+
+        info!("🌐 Connecting to {}...", self.provider.name());
+        let connection = self.provider.connect(&self.config.options).await.map_err(|e| {
+            error!("❌ {} connection FAILED: {}", self.provider.name(), e);
+            anyhow!(
+                "WebSocket connection failed: {}. Please check your API key and model availability.",
+                e
+            )
+        })?;
+        info!("✅ {} connected successfully!", self.provider.name());
+
+        // Fresh flags for every run so tasks of a previous run can't interfere
+        self.shared = Arc::new(SessionShared::new(self.config.metrics.clone()));
+        self.shared.running.store(true, Ordering::SeqCst);
+
+        // The audio task hands new connections to the transcript task, which
+        // reports connections that closed
+        let (connections_tx, connections_rx) = mpsc::unbounded_channel();
+        let (lost_tx, lost_rx) = mpsc::unbounded_channel();
+
+        self.audio_task = Some(tokio::spawn(run_audio_task(
+            audio_rx,
+            pipeline,
+            AudioSender::spawn(connection.sink, self.config.metrics.clone()),
+            Reconnector {
+                provider: self.provider.clone(),
+                options: self.config.options.clone(),
+                policy: self.config.reconnect.clone(),
+                connections_tx,
+                lost_rx,
+            },
+            failover,
+            self.shared.clone(),
+            self.event_tx.clone(),
+        )));
+
+        self.transcript_task = Some(tokio::spawn(run_transcript_task(
+            connection.events,
+            connections_rx,
+            lost_tx,
+            self.shared.clone(),
+            self.event_tx.clone(),
+        )));
+
+        info!("Recording started successfully");
+        Ok(())
+    }
+
+    /// Pause the session
+    ///
+    /// Incoming audio is muted, so an ongoing segment is committed by the
+    /// VAD and the connection is kept alive until [`resume`](Self::resume).
+    pub fn pause(&self) {
+        if !self.shared.paused.swap(true, Ordering::SeqCst) {
+            info!("⏸️  Dictation paused");
+        }
+    }
+
+    /// Resume a paused session
+    pub fn resume(&self) {
+        if self.shared.paused.swap(false, Ordering::SeqCst) {
+            info!("▶️  Dictation resumed");
+        }
+    }
+
+    /// Stop the session and release the audio device and connection
+    pub async fn stop(&mut self) -> Result<()> {
+        info!("Stopping dictation session");
+        self.shared.shutdown();
+
+        // Let the audio task close the connection gracefully
+        if let Some(task) = self.audio_task.take() {
+            if let Err(e) = task.await {
+                warn!("Audio task ended abnormally: {}", e);
+            }
+        }
+
+        // Give the provider a moment to deliver the last transcripts
+        if let Some(mut task) = self.transcript_task.take() {
+            if tokio::time::timeout(TRANSCRIPT_DRAIN_TIMEOUT, &mut task)
+                .await
+                .is_err()
+            {
+                warn!("Provider did not close in time, dropping pending transcripts");
+                task.abort();
+            }
+        }
+
+        if let Some(mut capture) = self.capture.lock().await.take() {
+            capture.stop_stream()?;
+        }
+
+        info!("Dictation session stopped");
+        Ok(())
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.shared.running.load(Ordering::SeqCst)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.shared.paused.load(Ordering::SeqCst)
+    }
+
+    pub fn is_speaking(&self) -> bool {
+        self.shared.speaking.load(Ordering::SeqCst)
+    }
+
+    pub fn config(&self) -> &SessionConfig {
+        &self.config
+    }
+
+    pub fn provider_name(&self) -> &str {
+        self.provider.name()
+    }
+
+    /// Name of the input device being captured from
+    pub async fn device_name(&self) -> Option<String> {
+        self.capture
+            .lock()
+            .await
+            .as_ref()
+            .and_then(|capture| capture.device_name().map(str::to_string))
+    }
+}
+
+impl Drop for DictationSession {
+    fn drop(&mut self) {
+        self.shared.shutdown();
+    }
+}
+
+/// Open an input device and start streaming from it
+///
+/// # Arguments
+/// * `device` - Device name, `None` for the system default
+fn open_capture(
+    config: &SessionConfig,
+    device: Option<&str>,
+    audio_tx: mpsc::Sender<AudioPacket>,
+    error_tx: mpsc::UnboundedSender<String>,
+) -> Result<AudioCapture> {
+    let mut capture = AudioCapture::new()?;
+    capture.set_channel_mode(config.channel_mode);
+    capture.set_buffer_size(config.buffer_size);
+    capture.set_error_sender(error_tx);
+    match device {
+        Some(device) => {
+            info!("Setting device: {}", device);
+            capture.set_device(device)?;
+        }
+        None => {
+            info!("Using default device...");
+            capture.use_default_device()?;
+        }
+    }
+    info!("Sample rate: {:?}", capture.sample_rate());
+
+    capture.start_stream(audio_tx)?;
+    Ok(capture)
+}
+
+/// Devices to try after `failed` stopped working, in order
+///
+/// The priority list comes first, then the system default. The failed
+/// device is tried last, in case it only glitched.
+pub(crate) fn failover_candidates(
+    priority: &[String],
+    failed: Option<&str>,
+) -> Vec<Option<String>> {
+    let mut candidates: Vec<Option<String>> = priority
+        .iter()
+        .filter(|name| Some(name.as_str()) != failed)
+        .map(|name| Some(name.clone()))
+        .collect();
+    candidates.push(None);
+    if let Some(failed) = failed {
+        candidates.push(Some(failed.to_string()));
+    }
+    candidates
+}
+
+/// Moves the session to another input device when the current one fails
+struct Failover {
+    config: SessionConfig,
+    /// Device captured from
+    current: Option<String>,
+    capture: Arc<Mutex<Option<AudioCapture>>>,
+    /// Packets of every device go into the same channel
+    audio_tx: mpsc::Sender<AudioPacket>,
+    error_tx: mpsc::UnboundedSender<String>,
+    error_rx: mpsc::UnboundedReceiver<String>,
+    /// No device could be opened after the last failure
+    lost: bool,
+}
+
+impl Failover {
+    /// Open the first candidate device that works
+    fn switch(&mut self) -> Option<AudioCapture> {
+        // Errors of the old stream are stale now
+        while self.error_rx.try_recv().is_ok() {}
+
+        for device in failover_candidates(&self.config.device_priority, self.current.as_deref()) {
+            match open_capture(
+                &self.config,
+                device.as_deref(),
+                self.audio_tx.clone(),
+                self.error_tx.clone(),
+            ) {
+                Ok(capture) => {
+                    self.current = capture.device_name().map(str::to_string);
+                    self.lost = false;
+                    return Some(capture);
+                }
+                Err(e) => debug!("Input device {:?} unavailable: {}", device, e),
+            }
+        }
+        None
+    }
+}
+
+/// Wait until the input device fails, or forever without failover
+///
+/// # Returns
+/// Why the device is considered failed
+async fn device_failed(failover: &mut Option<Failover>, last_packet: Instant) -> String {
+    let Some(failover) = failover.as_mut() else {
+        return std::future::pending().await;
+    };
+    tokio::select! {
+        Some(reason) = failover.error_rx.recv() => reason,
+        _ = tokio::time::sleep_until(last_packet + DEVICE_STALL_TIMEOUT) => {
+            "No audio from the input device".to_string()
+        }
+    }
+}
+
+/// Reconnects the audio task when the provider connection drops
+struct Reconnector {
+    provider: Arc<dyn SpeechToTextProvider>,
+    options: SessionOptions,
+    policy: Option<RetryPolicy>,
+    /// Event receivers of new connections, for the transcript task
+    connections_tx: mpsc::UnboundedSender<mpsc::Receiver<TranscriptEvent>>,
+    /// Generation of connections the transcript task saw closing
+    lost_rx: mpsc::UnboundedReceiver<u64>,
+}
+
+impl Reconnector {
+    /// Start connecting in the background
+    ///
+    /// # Returns
+    /// `None` if reconnecting is disabled
+    fn spawn(&self) -> Option<JoinHandle<Result<ProviderConnection, RAFlowError>>> {
+        let policy = self.policy.clone()?;
+        let provider = self.provider.clone();
+        let options = self.options.clone();
+
+        Some(tokio::spawn(async move {
+            policy
+                .execute(|| async {
+                    provider
+                        .connect(&options)
+                        .await
+                        .map_err(|e| RAFlowError::Network(e.to_string()))
+                })
+                .await
+        }))
+    }
+}
+
+/// Wait for a running reconnect, or forever if there is none
+async fn reconnected(
+    task: &mut Option<JoinHandle<Result<ProviderConnection, RAFlowError>>>,
+) -> Result<ProviderConnection, RAFlowError> {
+    let result = match task.as_mut() {
+        Some(task) => task.await,
+        None => std::future::pending().await,
+    };
+    *task = None;
+    result.unwrap_or_else(|e| Err(RAFlowError::Network(e.to_string())))
+}
+
+/// Wait until sending audio fails, or forever while reconnecting
+///
+/// # Returns
+/// Why sending failed
+async fn send_failed(sender: &mut Option<AudioSender>) -> String {
+    match sender.as_mut() {
+        Some(sender) => sender.failed().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Send buffered audio on a new connection
+async fn replay(sink: &mut dyn AudioSink, buffer: &mut ReplayBuffer) -> anyhow::Result<()> {
+    let dropped = buffer.take_dropped();
+    if dropped > 0 {
+        warn!(
+            "Replay buffer overflowed, {:.1}s of audio lost",
+            dropped as f32 / 16000.0
+        );
+    }
+    if buffer.is_empty() {
+        return Ok(());
+    }
+    info!(
+        "🔁 Replaying {:.1}s of buffered audio",
+        buffer.len() as f32 / 16000.0
+    );
+    for chunk in buffer.chunks() {
+        sink.send_audio(&chunk.samples, chunk.commit).await?;
+    }
+    Ok(())
+}
+
+/// Audio task: runs the pipeline and forwards speech to the provider
+///
+/// Speech goes out through an [`AudioSender`], so a slow connection drops
+/// audio instead of stalling the pipeline. While a dropped connection is
+/// restored, speech collects in a replay buffer that is sent on the new
+/// connection after the recent transcript.
+async fn run_audio_task(
+    mut audio_rx: mpsc::Receiver<AudioPacket>,
+    mut pipeline: AudioPipeline,
+    sender: AudioSender,
+    mut reconnector: Reconnector,
+    mut failover: Option<Failover>,
+    shared: Arc<SessionShared>,
+    event_tx: mpsc::UnboundedSender<SessionEvent>,
+) {
+    let mut shutdown_rx = shared.shutdown_tx.subscribe();
+    let mut packet_count: u64 = 0;
+    let mut last_packet = Instant::now();
+    // Speech was sent since the last commit
+    let mut uncommitted = false;
+
+    // `None` while reconnecting
+    let mut sender = Some(sender);
+    let mut reconnect = None;
+    let mut generation: u64 = 0;
+    let mut replay_buffer = ReplayBuffer::new(REPLAY_BUFFER_SAMPLES);
+
+    // Drop the current connection and start a new one, or stop the session
+    let connection_lost = |reconnector: &Reconnector,
+                           sender: &mut Option<AudioSender>,
+                           reconnect: &mut Option<JoinHandle<_>>,
+                           reason: String| {
+        *sender = None;
+        *reconnect = reconnector.spawn();
+        shared.metrics.record_error();
+        if reconnect.is_some() {
+            shared.metrics.record_error_retry();
+            warn!("🔌 {}, reconnecting...", reason);
+            let _ = event_tx.send(SessionEvent::Reconnecting { reason });
+            true
+        } else {
+            if shared.shutdown() {
+                let _ = event_tx.send(SessionEvent::Stopped { reason });
+            }
+            false
+        }
+    };
+
+    info!("🎤 Audio processing task started");
+
+    loop {
+        let packet = tokio::select! {
+            packet = audio_rx.recv() => match packet {
+                Some(packet) => packet,
+                None => break,
+            },
+            Some(lost) = reconnector.lost_rx.recv() => {
+                // Closes of replaced connections are expected
+                if lost == generation
+                    && sender.is_some()
+                    && !connection_lost(
+                        &reconnector,
+                        &mut sender,
+                        &mut reconnect,
+                        "WebSocket connection closed".to_string(),
+                    )
+                {
+                    break;
+                }
+                continue;
+            }
+            result = reconnected(&mut reconnect) => {
+                let connection = match result {
+                    Ok(connection) => connection,
+                    Err(e) => {
+                        error!("❌ Reconnect failed: {}", e);
+                        if shared.shutdown() {
+                            let _ = event_tx.send(SessionEvent::Stopped {
+                                reason: format!("Connection lost: {}", e),
+                            });
+                        }
+                        break;
+                    }
+                };
+
+                generation += 1;
+                let _ = reconnector.connections_tx.send(connection.events);
+                let mut new_sink = connection.sink;
+                new_sink.set_previous_text(shared.context());
+                replay_buffer.acknowledge(shared.commits_received.load(Ordering::SeqCst));
+                // Segments lost from the replay buffer will never be transcribed
+                shared.retain_commits(replay_buffer.chunks().filter(|c| c.commit).count());
+
+                if let Err(e) = replay(new_sink.as_mut(), &mut replay_buffer).await {
+                    error!("Failed to replay audio: {}", e);
+                    if !connection_lost(
+                        &reconnector,
+                        &mut sender,
+                        &mut reconnect,
+                        format!("Failed to send audio: {}", e),
+                    ) {
+                        break;
+                    }
+                    continue;
+                }
+
+                info!("✅ Reconnected");
+                shared.metrics.record_ws_reconnect();
+                shared.metrics.record_error_recovered();
+                sender = Some(AudioSender::spawn(new_sink, shared.metrics.clone()));
+                let _ = event_tx.send(SessionEvent::Reconnected);
+                continue;
+            }
+            reason = send_failed(&mut sender) => {
+                error!("{}", reason);
+                if !connection_lost(&reconnector, &mut sender, &mut reconnect, reason) {
+                    break;
+                }
+                continue;
+            }
+            reason = device_failed(&mut failover, last_packet) => {
+                // Retry after another timeout if no device works
+                last_packet = Instant::now();
+                let Some(failover) = failover.as_mut() else {
+                    continue;
+                };
+                if let Some(mut old) = failover.capture.lock().await.take() {
+                    let _ = old.stop_stream();
+                }
+                if !failover.lost {
+                    warn!("🎙️ Input device {:?} failed: {}", failover.current, reason);
+                }
+
+                let Some(new_capture) = failover.switch() else {
+                    if !failover.lost {
+                        failover.lost = true;
+                        error!("❌ No input device available, waiting for one");
+                        let _ = event_tx.send(SessionEvent::DeviceLost { reason });
+                    }
+                    continue;
+                };
+                if let Some(rate) = new_capture.sample_rate() {
+                    if let Err(e) = pipeline.set_input_rate(rate) {
+                        error!("Failed to resample from {} Hz: {}", rate, e);
+                    }
+                }
+                let device = new_capture.device_name().unwrap_or_default().to_string();
+                info!("✅ Switched to input device {}", device);
+                *failover.capture.lock().await = Some(new_capture);
+                let _ = event_tx.send(SessionEvent::DeviceChanged { device });
+                continue;
+            }
+            _ = shutdown_rx.changed() => break,
+        };
+        packet_count += 1;
+        last_packet = Instant::now();
+
+        // Check audio signal every 100 packets
+        if packet_count.is_multiple_of(100) && !packet.is_empty() {
+            let rms = (packet.iter().map(|x| x * x).sum::<f32>() / packet.len() as f32).sqrt();
+            info!("📊 Audio input RMS: {:.6} (packet #{})", rms, packet_count);
+        }
+
+        // A paused session is fed silence so the VAD commits and keep-alives continue
+        let packet = if shared.paused.load(Ordering::Relaxed) {
+            vec![0.0; packet.len()]
+        } else {
+            packet
+        };
+
+        let started = Instant::now();
+        let chunks = match pipeline.process(&packet) {
+            Ok(chunks) => {
+                shared
+                    .metrics
+                    .record_audio_packet_processed(started.elapsed());
+                chunks
+            }
+            Err(e) => {
+                error!("Resampling error: {}", e);
+                continue;
+            }
+        };
+
+        for chunk in chunks {
+            let _ = event_tx.send(SessionEvent::AudioLevel {
+                level: chunk.audio_level,
+                input_level: chunk.input_level,
+            });
+            if chunk.speech_started {
+                let _ = event_tx.send(SessionEvent::SpeechStarted);
+            }
+            if chunk.speech_ended {
+                let _ = event_tx.send(SessionEvent::SpeechEnded);
+            }
+            shared.speaking.store(chunk.is_speech, Ordering::Relaxed);
+
+            let commit = match chunk.action {
+                ChunkAction::Send { commit } => commit,
+                // A new or busy connection doesn't need to be kept alive
+                ChunkAction::KeepAlive if !sender.as_ref().is_some_and(AudioSender::is_idle) => {
+                    continue
+                }
+                ChunkAction::KeepAlive => {
+                    info!("🔄 Sending keep-alive silence chunk to maintain WebSocket connection");
+                    false
+                }
+                ChunkAction::Skip => continue,
+            };
+
+            if chunk.action != ChunkAction::KeepAlive {
+                replay_buffer.acknowledge(shared.commits_received.load(Ordering::SeqCst));
+                replay_buffer.push(&chunk.samples, commit);
+                uncommitted = !commit;
+                if commit {
+                    shared.commit_sent();
+                }
+            }
+
+            if let Some(sender) = sender.as_ref() {
+                sender.send(&chunk.samples, commit);
+            }
+        }
+    }
+
+    info!("🔇 Audio processing task ended");
+    if let Some(stats) = pipeline.suppression_stats() {
+        info!(
+            "🔇 Noise suppression over the session: RMS {:.6} -> {:.6} ({:.1} dB)",
+            stats.input_rms,
+            stats.output_rms,
+            stats.reduction_db()
+        );
+    }
+
+    if let Some(task) = reconnect.take() {
+        task.abort();
+    }
+
+    if let Some(sender) = sender {
+        if uncommitted {
+            shared.commit_sent();
+        }
+        sender.finish(uncommitted).await;
+    }
+
+    // Stop the audio capture stream
+    let capture = match &failover {
+        Some(failover) => failover.capture.lock().await.take(),
+        None => None,
+    };
+    if let Some(mut capture) = capture {
+        if let Err(e) = capture.stop_stream() {
+            error!("Failed to stop audio stream: {}", e);
+        } else {
+            info!("✅ Audio capture stream stopped cleanly");
+        }
+    }
+}
+
+/// Transcript task: turns provider events into session events
+///
+/// Switches to the events of a new connection after a reconnect, and
+/// reports connections that close while the session is running.
+async fn run_transcript_task(
+    mut transcript_rx: mpsc::Receiver<TranscriptEvent>,
+    mut connections_rx: mpsc::UnboundedReceiver<mpsc::Receiver<TranscriptEvent>>,
+    lost_tx: mpsc::UnboundedSender<u64>,
+    shared: Arc<SessionShared>,
+    event_tx: mpsc::UnboundedSender<SessionEvent>,
+) {
+    info!("=== Transcript processing task started ===");
+
+    let mut generation: u64 = 0;
+    let mut spacer = SmartSpacer::new();
+
+    loop {
+        let event = tokio::select! {
+            event = transcript_rx.recv() => event,
+            Some(events) = connections_rx.recv() => {
+                transcript_rx = events;
+                generation += 1;
+                continue;
+            }
+        };
+
+        let Some(event) = event else {
+            if !shared.running.load(Ordering::SeqCst) {
+                break;
+            }
+            // Wait for the audio task to reconnect (or give up)
+            let _ = lost_tx.send(generation);
+            match connections_rx.recv().await {
+                Some(events) => {
+                    transcript_rx = events;
+                    generation += 1;
+                    continue;
+                }
+                None => break,
+            }
+        };
+
+        match event {
+            TranscriptEvent::SessionStarted {
+                session_id,
+                model_id,
+            } => {
+                info!("🎬 Session started: {} (model: {})", session_id, model_id);
+                let _ = event_tx.send(SessionEvent::Connected {
+                    session_id,
+                    model_id,
+                });
+            }
+            TranscriptEvent::Partial { text } => {
+                info!("📝 PARTIAL TRANSCRIPT: \"{}\"", text);
+                shared.metrics.record_partial_transcript();
+                let _ = event_tx.send(SessionEvent::PartialTranscript { text });
+            }
+            TranscriptEvent::Committed {
+                text,
+                language_code,
+                confidence,
+                words,
+            } => {
+                info!(
+                    "✅ COMMITTED TRANSCRIPT ({}): \"{}\"",
+                    language_code.as_deref().unwrap_or("?"),
+                    text
+                );
+                shared.commit_received(&spacer.next(&text).unwrap_or_default());
+                let _ = event_tx.send(SessionEvent::CommittedTranscript {
+                    text,
+                    language_code,
+                    confidence,
+                    words,
+                });
+            }
+            TranscriptEvent::Error { message, fatal } => {
+                error!("❌ API Error: {}", message);
+                shared.metrics.record_error();
+                let _ = event_tx.send(SessionEvent::Error {
+                    message: message.clone(),
+                });
+                // Stop recording on fatal errors (e.g. invalid request)
+                if fatal && shared.shutdown() {
+                    let _ = event_tx.send(SessionEvent::Stopped { reason: message });
+                }
+            }
+        }
+    }
+
+    // The connection closed without the session being stopped
+    if shared.shutdown() {
+        info!("WebSocket closed, stopping recording");
+        let _ = event_tx.send(SessionEvent::Stopped {
+            reason: "WebSocket connection closed".to_string(),
+        });
+    }
+
+    info!("=== Transcript processing task ended ===");
+}
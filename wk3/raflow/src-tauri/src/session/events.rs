@@ -0,0 +1,60 @@
+use serde::Serialize;
+
+use crate::network::WordTiming;
+
+/// Events emitted by a running [`DictationSession`](super::DictationSession)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionEvent {
+    /// The transcription service accepted the session
+    Connected {
+        session_id: String,
+        model_id: String,
+    },
+
+    /// Audio level of the latest chunk (0.0 - 1.0)
+    ///
+    /// `level` is what the provider receives, `input_level` the level as
+    /// captured before automatic gain control.
+    AudioLevel { level: f32, input_level: f32 },
+
+    /// VAD detected the start of a speech segment
+    SpeechStarted,
+
+    /// VAD detected the end of a speech segment (commit sent)
+    SpeechEnded,
+
+    /// Partial (interim) transcription result
+    PartialTranscript { text: String },
+
+    /// Final (committed) transcription result
+    ///
+    /// `language_code` is the language detected by the service and
+    /// `confidence` its score for the segment, if reported. `words` holds
+    /// word timings when the service sent them.
+    CommittedTranscript {
+        text: String,
+        language_code: Option<String>,
+        confidence: Option<f32>,
+        words: Vec<WordTiming>,
+    },
+
+    /// The connection dropped, audio is buffered while reconnecting
+    Reconnecting { reason: String },
+
+    /// The connection was restored and buffered audio replayed
+    Reconnected,
+
+    /// The input device failed and capture moved to `device`
+    DeviceChanged { device: String },
+
+    /// The input device failed and no other one could be opened, capture
+    /// resumes once a device shows up
+    DeviceLost { reason: String },
+
+    /// Recoverable error reported by the transcription service
+    Error { message: String },
+
+    /// The session ended on its own (connection lost, fatal error)
+    Stopped { reason: String },
+}
@@ -0,0 +1,11 @@
+pub mod dictation;
+pub mod events;
+pub mod pipeline;
+pub mod replay;
+pub mod sender;
+
+#[cfg(test)]
+mod tests;
+
+pub use dictation::{DictationSession, SessionConfig};
+pub use events::SessionEvent;
@@ -0,0 +1,502 @@
+use anyhow::Result;
+use std::time::Duration;
+use tracing::info;
+
+use crate::audio::gain::soft_limit;
+use crate::audio::vad::{audio_level, rms, NoiseFloor};
+use crate::audio::{AudioBuffer, AudioResampler, AutoGain, NoiseSuppressor, SuppressionStats, Vad};
+
+/// Sample rate expected by the transcription API
+pub const TARGET_SAMPLE_RATE: usize = 16000;
+
+/// Samples per processed chunk (100ms at 16kHz)
+pub const CHUNK_SIZE: usize = 1600;
+
+/// Send a keep-alive chunk after this many skipped chunks (5 seconds of silence)
+const MAX_SILENCE_CHUNKS_BEFORE_KEEPALIVE: usize = 50;
+
+/// Audio kept from before speech is detected, so word onsets aren't clipped
+pub const DEFAULT_PRE_ROLL: Duration = Duration::from_millis(500);
+
+/// Chunks after which the recording environment is reported (3 seconds)
+const NOISE_REPORT_CHUNKS: usize = 30;
+
+/// What the session should do with a processed chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkAction {
+    /// Send the chunk, committing the segment when speech just ended
+    Send { commit: bool },
+    /// Send the chunk only to keep an idle connection alive
+    KeepAlive,
+    /// Drop the chunk - it's just background noise
+    Skip,
+}
+
+/// A 100ms chunk of 16kHz audio together with its VAD analysis
+#[derive(Debug, Clone)]
+pub struct ProcessedChunk {
+    /// The chunk, preceded by the pre-roll when speech just started
+    pub samples: Vec<f32>,
+    /// Level after the gain, what the provider receives
+    pub audio_level: f32,
+    /// Level as captured, before the gain
+    pub input_level: f32,
+    pub is_speech: bool,
+    pub speech_started: bool,
+    pub speech_ended: bool,
+    pub action: ChunkAction,
+}
+
+/// Synchronous audio pipeline: resample -> denoise -> chunk -> gain -> VAD -> send decision
+///
+/// Kept free of any I/O so it can be driven directly from tests.
+pub struct AudioPipeline {
+    /// `None` when the input already is at the target rate
+    resampler: Option<AudioResampler>,
+    denoiser: Option<NoiseSuppressor>,
+    gain: Option<AutoGain>,
+    vad: Box<dyn Vad>,
+    buffer: Vec<f32>,
+    was_speaking: bool,
+    chunk_count: usize,
+    noise: NoiseFloor,
+    pre_roll: AudioBuffer,
+    silence_chunks_since_last_send: usize,
+}
+
+impl AudioPipeline {
+    /// Create a new pipeline
+    ///
+    /// # Arguments
+    /// * `input_sample_rate` - Sample rate of the incoming audio packets
+    /// * `vad` - Voice activity detector used to gate the audio
+    pub fn new(input_sample_rate: u32, vad: Box<dyn Vad>) -> Result<Self> {
+        Ok(Self {
+            resampler: resampler_for(input_sample_rate)?,
+            denoiser: None,
+            gain: None,
+            vad,
+            buffer: Vec::new(),
+            was_speaking: false,
+            chunk_count: 0,
+            noise: NoiseFloor::new(),
+            pre_roll: AudioBuffer::new(pre_roll_samples(DEFAULT_PRE_ROLL), CHUNK_SIZE),
+            silence_chunks_since_last_send: 0,
+        })
+    }
+
+    /// Set how much audio from before the speech start is sent along
+    pub fn with_pre_roll(mut self, pre_roll: Duration) -> Self {
+        self.pre_roll = AudioBuffer::new(pre_roll_samples(pre_roll), CHUNK_SIZE);
+        self
+    }
+
+    /// Suppress steady background noise before the VAD sees the audio
+    ///
+    /// # Arguments
+    /// * `strength` - 0.0 - 1.0, see [`NoiseSuppressor::new`]
+    pub fn with_noise_suppression(mut self, strength: f32) -> Self {
+        self.denoiser = Some(NoiseSuppressor::new(strength));
+        self
+    }
+
+    /// Normalize speech toward `target_rms`, see [`AutoGain`]
+    pub fn with_auto_gain(mut self, target_rms: f32) -> Self {
+        self.gain = Some(AutoGain::new(target_rms));
+        self
+    }
+
+    /// Switch to packets of another sample rate, e.g. after a device change
+    pub fn set_input_rate(&mut self, input_sample_rate: u32) -> Result<()> {
+        self.resampler = resampler_for(input_sample_rate)?;
+        Ok(())
+    }
+
+    /// Levels before and after noise suppression, `None` when it's off
+    pub fn suppression_stats(&self) -> Option<SuppressionStats> {
+        self.denoiser.as_ref().map(NoiseSuppressor::stats)
+    }
+
+    /// Process a packet from the audio source
+    ///
+    /// # Returns
+    /// All complete 100ms chunks that became available
+    pub fn process(&mut self, packet: &[f32]) -> Result<Vec<ProcessedChunk>> {
+        let mut resampled = match self.resampler.as_mut() {
+            Some(resampler) => resampler.process(packet)?,
+            None => packet.to_vec(),
+        };
+        if let Some(denoiser) = self.denoiser.as_mut() {
+            resampled = denoiser.process(&resampled);
+        }
+        self.buffer.extend_from_slice(&resampled);
+
+        let mut chunks = Vec::new();
+        while self.buffer.len() >= CHUNK_SIZE {
+            let chunk: Vec<f32> = self.buffer.drain(..CHUNK_SIZE).collect();
+            chunks.push(self.process_chunk(chunk));
+        }
+
+        Ok(chunks)
+    }
+
+    fn process_chunk(&mut self, mut samples: Vec<f32>) -> ProcessedChunk {
+        self.chunk_count += 1;
+
+        let input_level = audio_level(&samples);
+        if let Some(gain) = self.gain.as_mut() {
+            gain.process(&mut samples);
+        }
+        soft_limit(&mut samples);
+
+        // Tracked here for every detector so the SNR can be logged
+        let rms = rms(&samples);
+        let snr = self.noise.snr_db(rms);
+        self.noise.update(rms);
+        if self.chunk_count == NOISE_REPORT_CHUNKS {
+            self.report_noise_floor();
+        }
+
+        let is_speech = self.vad.is_speech(&samples);
+        let audio_level = audio_level(&samples);
+
+        let speech_started = !self.was_speaking && is_speech;
+        let speech_ended = self.was_speaking && !is_speech;
+
+        if speech_started {
+            info!(
+                "🎙️  VAD: Speech STARTED | RMS: {:.6} | Audio Level: {:.4} | SNR: {:.1} dB",
+                rms, audio_level, snr
+            );
+            if snr < 10.0 {
+                info!("⚠️  Low SNR! Background noise may interfere with recognition.");
+            }
+            if let Some(gain) = &self.gain {
+                info!("🎚️  AGC gain: {:+.1} dB", gain.gain_db());
+            }
+        } else if speech_ended {
+            info!("🔚 VAD: Speech ENDED (sending commit) | RMS: {:.6}", rms);
+        }
+
+        self.was_speaking = is_speech;
+
+        // Only send audio when speech is detected or just ended, so the API
+        // never receives (and misinterprets) background noise
+        let action = if is_speech || speech_ended {
+            self.silence_chunks_since_last_send = 0;
+            ChunkAction::Send {
+                commit: speech_ended,
+            }
+        } else {
+            self.silence_chunks_since_last_send += 1;
+            if self.silence_chunks_since_last_send >= MAX_SILENCE_CHUNKS_BEFORE_KEEPALIVE {
+                self.silence_chunks_since_last_send = 0;
+                ChunkAction::KeepAlive
+            } else {
+                ChunkAction::Skip
+            }
+        };
+
+        // Skipped chunks are held back in case speech starts right after
+        // them, e.g. the quiet onset the VAD needed a few chunks to confirm
+        match action {
+            ChunkAction::Skip => {
+                self.pre_roll.push_overwrite(&samples);
+            }
+            // Already sent
+            ChunkAction::KeepAlive => self.pre_roll.clear(),
+            ChunkAction::Send { .. } if speech_started => {
+                let mut with_pre_roll = self.pre_roll.take_all();
+                with_pre_roll.extend_from_slice(&samples);
+                samples = with_pre_roll;
+            }
+            ChunkAction::Send { .. } => {}
+        }
+
+        ProcessedChunk {
+            samples,
+            audio_level,
+            input_level,
+            is_speech,
+            speech_started,
+            speech_ended,
+            action,
+        }
+    }
+
+    fn report_noise_floor(&self) {
+        let floor = self.noise.floor();
+
+        info!("📊 Background noise floor: RMS = {:.6}", floor);
+        if floor > 0.01 {
+            info!("⚠️  High background noise! May cause API false positives.");
+        } else if floor > 0.005 {
+            info!("⚠️  Moderate background noise detected");
+        } else {
+            info!("✅ Low background noise - good recording environment");
+        }
+        if let Some(stats) = self.suppression_stats() {
+            info!(
+                "🔇 Noise suppression: RMS {:.6} -> {:.6} ({:.1} dB)",
+                stats.input_rms,
+                stats.output_rms,
+                stats.reduction_db()
+            );
+        }
+    }
+}
+
+/// Resampler to the target rate, `None` if the input already is at it
+fn resampler_for(input_sample_rate: u32) -> Result<Option<AudioResampler>> {
+    if input_sample_rate as usize == TARGET_SAMPLE_RATE {
+        return Ok(None);
+    }
+    Ok(Some(AudioResampler::new(
+        input_sample_rate as usize,
+        TARGET_SAMPLE_RATE,
+        CHUNK_SIZE,
+    )?))
+}
+
+fn pre_roll_samples(pre_roll: Duration) -> usize {
+    (pre_roll.as_secs_f64() * TARGET_SAMPLE_RATE as f64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{AdaptiveVad, VoiceActivityDetector};
+
+    fn tone(len: usize, amplitude: f32) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 200.0 * i as f32 / 16000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_pipeline_skips_silence() {
+        let mut pipeline =
+            AudioPipeline::new(16000, Box::new(VoiceActivityDetector::default())).unwrap();
+
+        let mut chunks = Vec::new();
+        for _ in 0..10 {
+            chunks.extend(pipeline.process(&vec![0.0; CHUNK_SIZE]).unwrap());
+        }
+
+        assert!(!chunks.is_empty());
+        assert!(chunks
+            .iter()
+            .all(|c| c.action == ChunkAction::Skip && !c.is_speech));
+    }
+
+    #[test]
+    fn test_pipeline_sends_speech_and_commits_on_end() {
+        let mut pipeline =
+            AudioPipeline::new(16000, Box::new(VoiceActivityDetector::default())).unwrap();
+
+        let mut chunks = Vec::new();
+        for _ in 0..10 {
+            chunks.extend(pipeline.process(&tone(CHUNK_SIZE, 0.5)).unwrap());
+        }
+        for _ in 0..30 {
+            chunks.extend(pipeline.process(&vec![0.0; CHUNK_SIZE]).unwrap());
+        }
+
+        assert_eq!(chunks.iter().filter(|c| c.speech_started).count(), 1);
+        assert_eq!(chunks.iter().filter(|c| c.speech_ended).count(), 1);
+
+        let commits: Vec<_> = chunks
+            .iter()
+            .filter(|c| c.action == ChunkAction::Send { commit: true })
+            .collect();
+        assert_eq!(commits.len(), 1);
+        assert!(commits[0].speech_ended);
+    }
+
+    #[test]
+    fn test_pipeline_adapts_to_background_noise() {
+        let mut pipeline = AudioPipeline::new(16000, Box::new(AdaptiveVad::new())).unwrap();
+
+        // A steady hum above the default energy threshold is not speech
+        let mut chunks = Vec::new();
+        for _ in 0..60 {
+            chunks.extend(pipeline.process(&tone(CHUNK_SIZE, 0.05)).unwrap());
+        }
+        assert!(chunks.iter().all(|c| !c.is_speech));
+
+        let mut chunks = Vec::new();
+        for _ in 0..10 {
+            chunks.extend(pipeline.process(&tone(CHUNK_SIZE, 0.5)).unwrap());
+        }
+        assert_eq!(chunks.iter().filter(|c| c.speech_started).count(), 1);
+    }
+
+    #[test]
+    fn test_noise_suppression_quiets_background() {
+        let hum: Vec<f32> = (0..CHUNK_SIZE * 30)
+            .map(|i| 0.02 * ((i as f32 * 0.37).sin() + (i as f32 * 1.91).sin()))
+            .collect();
+
+        let plain = AudioPipeline::new(16000, Box::new(VoiceActivityDetector::default())).unwrap();
+        assert!(plain.suppression_stats().is_none());
+
+        let mut pipeline = AudioPipeline::new(16000, Box::new(VoiceActivityDetector::default()))
+            .unwrap()
+            .with_noise_suppression(1.0);
+        let mut chunks = Vec::new();
+        for packet in hum.chunks(CHUNK_SIZE) {
+            chunks.extend(pipeline.process(packet).unwrap());
+        }
+
+        let stats = pipeline.suppression_stats().unwrap();
+        assert!(stats.reduction_db() > 10.0, "{:?}", stats);
+        let last = chunks.last().unwrap();
+        assert!(last.audio_level < 0.1 * audio_level(&hum[..CHUNK_SIZE]));
+    }
+
+    #[test]
+    fn test_auto_gain_raises_quiet_speech() {
+        let mut pipeline = AudioPipeline::new(16000, Box::new(VoiceActivityDetector::default()))
+            .unwrap()
+            .with_auto_gain(0.1);
+
+        let mut chunks = Vec::new();
+        for _ in 0..5 {
+            chunks.extend(pipeline.process(&vec![0.0; CHUNK_SIZE]).unwrap());
+        }
+        // Too quiet for the energy detector on its own
+        for _ in 0..50 {
+            chunks.extend(pipeline.process(&tone(CHUNK_SIZE, 0.02)).unwrap());
+        }
+
+        let last = chunks.last().unwrap();
+        assert!(last.input_level < 0.2);
+        assert!(last.audio_level > 0.9, "{}", last.audio_level);
+        assert!(chunks.iter().any(|c| c.speech_started));
+    }
+
+    #[test]
+    fn test_limiter_prevents_clipping() {
+        let mut pipeline =
+            AudioPipeline::new(16000, Box::new(VoiceActivityDetector::default())).unwrap();
+
+        let mut chunks = Vec::new();
+        for _ in 0..5 {
+            chunks.extend(pipeline.process(&tone(CHUNK_SIZE, 1.5)).unwrap());
+        }
+        assert!(chunks
+            .iter()
+            .flat_map(|c| c.samples.iter())
+            .all(|s| s.abs() < 1.0));
+    }
+
+    /// Silence, a soft onset the VAD doesn't catch yet, then loud speech
+    fn speech_burst(pipeline: &mut AudioPipeline) -> Vec<ProcessedChunk> {
+        let mut chunks = Vec::new();
+        for _ in 0..10 {
+            chunks.extend(pipeline.process(&vec![0.0; CHUNK_SIZE]).unwrap());
+        }
+        for amplitude in [0.01, 0.02] {
+            chunks.extend(pipeline.process(&tone(CHUNK_SIZE, amplitude)).unwrap());
+        }
+        for _ in 0..5 {
+            chunks.extend(pipeline.process(&tone(CHUNK_SIZE, 0.5)).unwrap());
+        }
+        chunks
+    }
+
+    #[test]
+    fn test_pre_roll_is_sent_with_speech_start() {
+        let mut pipeline = AudioPipeline::new(16000, Box::new(VoiceActivityDetector::default()))
+            .unwrap()
+            .with_pre_roll(Duration::from_millis(500));
+
+        let chunks = speech_burst(&mut pipeline);
+        let start = chunks.iter().position(|c| c.speech_started).unwrap();
+        assert!(chunks[..start]
+            .iter()
+            .all(|c| c.action == ChunkAction::Skip));
+
+        // 500ms of pre-roll: the soft onset, the two loud chunks the VAD
+        // needed to confirm speech and one chunk of silence before them
+        let first = &chunks[start];
+        assert_eq!(first.samples.len(), 5 * CHUNK_SIZE + CHUNK_SIZE);
+        let levels: Vec<f32> = first.samples.chunks(CHUNK_SIZE).map(rms).collect();
+        assert!(levels[0] < 0.001, "{:?}", levels);
+        assert!(levels[1] > 0.001 && levels[1] < 0.03, "{:?}", levels);
+
+        // Only the first speech chunk carries it
+        assert!(chunks[start + 1..]
+            .iter()
+            .all(|c| c.samples.len() == CHUNK_SIZE));
+    }
+
+    #[test]
+    fn test_pre_roll_can_be_disabled() {
+        let mut pipeline = AudioPipeline::new(16000, Box::new(VoiceActivityDetector::default()))
+            .unwrap()
+            .with_pre_roll(Duration::ZERO);
+
+        let chunks = speech_burst(&mut pipeline);
+        assert!(chunks.iter().any(|c| c.speech_started));
+        assert!(chunks.iter().all(|c| c.samples.len() == CHUNK_SIZE));
+    }
+
+    #[test]
+    fn test_pre_roll_starts_over_after_each_segment() {
+        let mut pipeline = AudioPipeline::new(16000, Box::new(VoiceActivityDetector::default()))
+            .unwrap()
+            .with_pre_roll(Duration::from_millis(200));
+
+        let mut chunks = speech_burst(&mut pipeline);
+        for _ in 0..20 {
+            chunks.extend(pipeline.process(&vec![0.0; CHUNK_SIZE]).unwrap());
+        }
+        chunks.extend(speech_burst(&mut pipeline));
+
+        let starts: Vec<_> = chunks.iter().filter(|c| c.speech_started).collect();
+        assert_eq!(starts.len(), 2);
+        assert!(starts
+            .iter()
+            .all(|c| c.samples.len() == 2 * CHUNK_SIZE + CHUNK_SIZE));
+    }
+
+    #[test]
+    fn test_pipeline_follows_input_rate_change() {
+        let mut pipeline =
+            AudioPipeline::new(48000, Box::new(VoiceActivityDetector::default())).unwrap();
+        let mut chunks = 0;
+        for _ in 0..30 {
+            chunks += pipeline.process(&vec![0.0; 1600]).unwrap().len();
+        }
+        assert!((9..=10).contains(&chunks), "{}", chunks);
+
+        // A 16kHz device needs no resampling
+        pipeline.set_input_rate(16000).unwrap();
+        let mut chunks = 0;
+        for _ in 0..10 {
+            chunks += pipeline.process(&vec![0.0; 1600]).unwrap().len();
+        }
+        assert!((10..=11).contains(&chunks), "{}", chunks);
+    }
+
+    #[test]
+    fn test_pipeline_keepalive_after_long_silence() {
+        let mut pipeline =
+            AudioPipeline::new(16000, Box::new(VoiceActivityDetector::default())).unwrap();
+
+        let mut chunks = Vec::new();
+        for _ in 0..120 {
+            chunks.extend(pipeline.process(&vec![0.0; CHUNK_SIZE]).unwrap());
+        }
+
+        let keepalives = chunks
+            .iter()
+            .filter(|c| c.action == ChunkAction::KeepAlive)
+            .count();
+        assert_eq!(
+            keepalives,
+            chunks.len() / MAX_SILENCE_CHUNKS_BEFORE_KEEPALIVE
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::VecDeque;
+
+/// A chunk of audio sent to the provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayChunk {
+    pub samples: Vec<f32>,
+    pub commit: bool,
+    /// Number of commits sent before this chunk
+    segment: usize,
+}
+
+/// Audio sent to the provider whose transcript hasn't arrived yet
+///
+/// When the connection drops, the buffered audio is sent again on the new
+/// connection so no speech is lost. A segment is released once its
+/// committed transcript arrives. The buffer is bounded: when it's full the
+/// oldest audio is dropped.
+#[derive(Debug)]
+pub struct ReplayBuffer {
+    chunks: VecDeque<ReplayChunk>,
+    len: usize,
+    capacity: usize,
+    commits_sent: usize,
+    commits_acknowledged: usize,
+    dropped_samples: usize,
+}
+
+impl ReplayBuffer {
+    /// Create a buffer holding at most `capacity` samples
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            chunks: VecDeque::new(),
+            len: 0,
+            capacity,
+            commits_sent: 0,
+            commits_acknowledged: 0,
+            dropped_samples: 0,
+        }
+    }
+
+    /// Remember a chunk that was (or should have been) sent
+    pub fn push(&mut self, samples: &[f32], commit: bool) {
+        self.chunks.push_back(ReplayChunk {
+            samples: samples.to_vec(),
+            commit,
+            segment: self.commits_sent,
+        });
+        self.len += samples.len();
+        if commit {
+            self.commits_sent += 1;
+        }
+
+        while self.len > self.capacity {
+            let Some(oldest) = self.chunks.pop_front() else {
+                break;
+            };
+            self.len -= oldest.samples.len();
+            self.dropped_samples += oldest.samples.len();
+        }
+    }
+
+    /// Release all segments up to the given number of committed transcripts
+    ///
+    /// # Arguments
+    /// * `commits` - Total committed transcripts received in the session
+    pub fn acknowledge(&mut self, commits: usize) {
+        self.commits_acknowledged = self.commits_acknowledged.max(commits);
+        while let Some(front) = self.chunks.front() {
+            if front.segment >= self.commits_acknowledged {
+                break;
+            }
+            self.len -= front.samples.len();
+            self.chunks.pop_front();
+        }
+    }
+
+    /// Chunks to send again after a reconnect, oldest first
+    pub fn chunks(&self) -> impl Iterator<Item = &ReplayChunk> {
+        self.chunks.iter()
+    }
+
+    /// Number of buffered samples
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Samples dropped because the buffer was full
+    ///
+    /// # Returns
+    /// The count since the last call
+    pub fn take_dropped(&mut self) -> usize {
+        std::mem::take(&mut self.dropped_samples)
+    }
+}
@@ -0,0 +1,362 @@
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::network::{AudioSink, MessageBatcher};
+use crate::utils::Metrics;
+
+/// Audio queued for sending before the oldest is dropped (5 s at 16kHz)
+pub const SEND_QUEUE_SAMPLES: usize = 5 * 16000;
+
+/// Most chunks coalesced into one message
+const BATCH_MAX_CHUNKS: usize = 5;
+
+/// How long a chunk waits for more to send along
+///
+/// Chunks the pipeline produces together, like the pre-roll at the start
+/// of speech, go out as one message.
+const BATCH_MAX_AGE: Duration = Duration::from_millis(20);
+
+/// A chunk waiting to be sent
+#[derive(Debug, Clone, PartialEq)]
+struct QueuedChunk {
+    samples: Vec<f32>,
+    commit: bool,
+}
+
+/// Chunks handed from the audio task to the sender task
+#[derive(Debug, Default)]
+struct SendQueue {
+    chunks: VecDeque<QueuedChunk>,
+    /// Samples in `chunks`
+    len: usize,
+    /// Audio was dropped since the queue last ran empty
+    congested: bool,
+    /// Set by [`AudioSender::finish`], with whether to commit before closing
+    closing: Option<bool>,
+}
+
+impl SendQueue {
+    /// Queue a chunk, dropping the oldest audio beyond `capacity` samples
+    ///
+    /// A dropped chunk that ends a segment keeps its commit, so segments
+    /// still end where the VAD ended them.
+    ///
+    /// # Returns
+    /// The number of chunks whose audio was dropped
+    fn push(&mut self, samples: &[f32], commit: bool, capacity: usize) -> usize {
+        self.chunks.push_back(QueuedChunk {
+            samples: samples.to_vec(),
+            commit,
+        });
+        self.len += samples.len();
+
+        let mut dropped = 0;
+        while self.len > capacity {
+            let Some(index) = self
+                .chunks
+                .iter()
+                .position(|chunk| !chunk.samples.is_empty())
+            else {
+                break;
+            };
+            self.len -= self.chunks[index].samples.len();
+            if self.chunks[index].commit {
+                self.chunks[index].samples = Vec::new();
+            } else {
+                self.chunks.remove(index);
+            }
+            dropped += 1;
+        }
+        dropped
+    }
+
+    fn pop(&mut self) -> Option<QueuedChunk> {
+        let chunk = self.chunks.pop_front()?;
+        self.len -= chunk.samples.len();
+        if self.chunks.is_empty() {
+            self.congested = false;
+        }
+        Some(chunk)
+    }
+}
+
+/// State shared by the [`AudioSender`] and its task
+struct Shared {
+    queue: Mutex<SendQueue>,
+    /// Signaled when chunks are queued or the sender is closing
+    notify: Notify,
+}
+
+/// Sends audio to the provider without holding up the audio task
+///
+/// Chunks are queued and sent by a background task that owns the sink,
+/// coalescing queued chunks into fewer messages. When the connection
+/// can't keep up, the oldest queued audio is dropped and counted in the
+/// metrics instead of stalling the pipeline.
+pub struct AudioSender {
+    shared: Arc<Shared>,
+    task: JoinHandle<Result<()>>,
+    metrics: Metrics,
+    capacity: usize,
+}
+
+impl AudioSender {
+    /// Start sending to `sink`
+    pub fn spawn(sink: Box<dyn AudioSink>, metrics: Metrics) -> Self {
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(SendQueue::default()),
+            notify: Notify::new(),
+        });
+        let task = tokio::spawn(run_sender_task(sink, shared.clone()));
+        Self {
+            shared,
+            task,
+            metrics,
+            capacity: SEND_QUEUE_SAMPLES,
+        }
+    }
+
+    /// Queue a chunk of 16kHz mono audio, never waits for the network
+    pub fn send(&self, samples: &[f32], commit: bool) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        let dropped = queue.push(samples, commit, self.capacity);
+        if dropped > 0 {
+            if !queue.congested {
+                queue.congested = true;
+                warn!("🐢 Connection can't keep up, dropping the oldest audio");
+            }
+            for _ in 0..dropped {
+                self.metrics.record_audio_packet_dropped();
+            }
+        }
+        drop(queue);
+        self.shared.notify.notify_one();
+    }
+
+    /// Whether everything queued has been picked up for sending
+    pub fn is_idle(&self) -> bool {
+        self.shared.queue.lock().unwrap().chunks.is_empty()
+    }
+
+    /// Wait until sending fails
+    ///
+    /// Must not be called again once it returned.
+    ///
+    /// # Returns
+    /// Why sending failed
+    pub async fn failed(&mut self) -> String {
+        match (&mut self.task).await {
+            Ok(Ok(())) => "Audio sender stopped".to_string(),
+            Ok(Err(e)) => format!("Failed to send audio: {}", e),
+            Err(e) => format!("Audio sender failed: {}", e),
+        }
+    }
+
+    /// Send what's queued and close the connection
+    ///
+    /// # Arguments
+    /// * `commit` - Finalize the current segment before closing
+    pub async fn finish(self, commit: bool) {
+        self.shared.queue.lock().unwrap().closing = Some(commit);
+        self.shared.notify.notify_one();
+
+        match self.task.await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!("Failed to send audio before closing: {}", e),
+            Err(e) => warn!("Audio sender ended abnormally: {}", e),
+        }
+    }
+}
+
+/// Sender task: sends queued chunks in batches until closed
+///
+/// Consecutive chunks are sent as one, a commit always ends a batch.
+async fn run_sender_task(mut sink: Box<dyn AudioSink>, shared: Arc<Shared>) -> Result<()> {
+    let mut batcher = MessageBatcher::new(BATCH_MAX_CHUNKS, BATCH_MAX_AGE);
+
+    let commit_on_close = loop {
+        match batcher.deadline() {
+            Some(deadline) => {
+                tokio::select! {
+                    _ = shared.notify.notified() => {}
+                    _ = tokio::time::sleep_until(deadline.into()) => {}
+                }
+            }
+            None => shared.notify.notified().await,
+        }
+
+        // Send what's queued, until the queue runs empty
+        let closing = loop {
+            let (chunk, closing) = {
+                let mut queue = shared.queue.lock().unwrap();
+                (queue.pop(), queue.closing)
+            };
+            let Some(chunk) = chunk else {
+                break closing;
+            };
+
+            let commit = chunk.commit;
+            if batcher.add(chunk) || commit {
+                send_batch(sink.as_mut(), batcher.flush()).await?;
+            }
+        };
+
+        if !batcher.is_empty() && (batcher.should_flush() || closing.is_some()) {
+            send_batch(sink.as_mut(), batcher.flush()).await?;
+        }
+        if let Some(commit) = closing {
+            break commit;
+        }
+    };
+
+    // Stopped mid-sentence (e.g. push-to-talk released): finalize the segment
+    if commit_on_close {
+        info!("📤 Committing pending speech before closing");
+        if let Err(e) = sink.commit().await {
+            warn!("Failed to commit pending speech: {}", e);
+        }
+    }
+
+    if let Err(e) = sink.close().await {
+        warn!("Failed to close provider connection: {}", e);
+    }
+    Ok(())
+}
+
+/// Send a batch of chunks as one message
+async fn send_batch(sink: &mut dyn AudioSink, batch: Vec<QueuedChunk>) -> Result<()> {
+    let Some(last) = batch.last() else {
+        return Ok(());
+    };
+    let commit = last.commit;
+
+    if batch.len() == 1 {
+        sink.send_audio(&batch[0].samples, commit).await?;
+    } else {
+        let samples: Vec<f32> = batch
+            .iter()
+            .flat_map(|chunk| &chunk.samples)
+            .copied()
+            .collect();
+        sink.send_audio(&samples, commit).await?;
+    }
+
+    if commit {
+        info!("📤 Sent audio chunk with COMMIT flag");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::future::BoxFuture;
+    use futures_util::FutureExt;
+
+    /// Sink that records what it sent and can be held up by the test
+    struct SlowSink {
+        sent: Arc<Mutex<Vec<(usize, bool)>>>,
+        gate: Arc<tokio::sync::Mutex<()>>,
+        closed: Arc<Mutex<bool>>,
+    }
+
+    impl AudioSink for SlowSink {
+        fn send_audio<'a>(
+            &'a mut self,
+            samples: &'a [f32],
+            commit: bool,
+        ) -> BoxFuture<'a, Result<()>> {
+            async move {
+                let _open = self.gate.lock().await;
+                self.sent.lock().unwrap().push((samples.len(), commit));
+                Ok(())
+            }
+            .boxed()
+        }
+
+        fn close(&mut self) -> BoxFuture<'_, Result<()>> {
+            *self.closed.lock().unwrap() = true;
+            async { Ok(()) }.boxed()
+        }
+    }
+
+    #[test]
+    fn test_queue_drops_oldest_audio() {
+        let mut queue = SendQueue::default();
+        assert_eq!(queue.push(&[0.1; 10], false, 25), 0);
+        assert_eq!(queue.push(&[0.1; 10], true, 25), 0);
+        assert_eq!(queue.push(&[0.1; 10], false, 25), 1);
+        assert_eq!(queue.len, 20);
+
+        // The end of the dropped segment is kept
+        assert_eq!(queue.push(&[0.1; 10], false, 25), 1);
+        let lengths: Vec<_> = queue
+            .chunks
+            .iter()
+            .map(|chunk| (chunk.samples.len(), chunk.commit))
+            .collect();
+        assert_eq!(lengths, vec![(0, true), (10, false), (10, false)]);
+
+        assert_eq!(queue.pop().map(|chunk| chunk.commit), Some(true));
+        assert_eq!(queue.len, 20);
+    }
+
+    #[tokio::test]
+    async fn test_congested_connection_drops_oldest_audio() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let gate = Arc::new(tokio::sync::Mutex::new(()));
+        let closed = Arc::new(Mutex::new(false));
+        let sink = SlowSink {
+            sent: sent.clone(),
+            gate: gate.clone(),
+            closed: closed.clone(),
+        };
+        let metrics = Metrics::new();
+        let mut sender = AudioSender::spawn(Box::new(sink), metrics.clone());
+        sender.capacity = 1000;
+
+        // The first chunk gets stuck in the socket, the rest pile up
+        let stalled = gate.lock().await;
+        sender.send(&[0.1; 100], false);
+        tokio::time::sleep(BATCH_MAX_AGE * 3).await;
+        for i in 0..19 {
+            sender.send(&[0.1; 100], i == 2);
+        }
+        assert!(!sender.is_idle());
+        assert_eq!(metrics.snapshot().audio_packets_dropped, 9);
+
+        // Once the connection recovers the backlog goes out in batches
+        drop(stalled);
+        sender.finish(true).await;
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent[0], (100, false));
+        assert_eq!(sent[1], (0, true));
+        assert_eq!(sent[2..4], [(500, false), (500, false)]);
+        assert_eq!(sent.last(), Some(&(0, true)));
+        assert_eq!(sent.len(), 5);
+        assert!(*closed.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_commit_ends_batch() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sink = SlowSink {
+            sent: sent.clone(),
+            gate: Arc::new(tokio::sync::Mutex::new(())),
+            closed: Arc::new(Mutex::new(false)),
+        };
+        let sender = AudioSender::spawn(Box::new(sink), Metrics::new());
+
+        sender.send(&[0.1; 100], false);
+        sender.send(&[0.1; 100], true);
+        sender.send(&[0.1; 100], false);
+        sender.finish(false).await;
+
+        assert_eq!(*sent.lock().unwrap(), vec![(200, true), (100, false)]);
+    }
+}
@@ -0,0 +1,700 @@
+use super::*;
+
+#[cfg(test)]
+mod dictation_tests {
+    use super::*;
+    use crate::audio::VadEngine;
+    use crate::network::mock_server::{MockConnection, MockReply, MockScribeServer, MockTrigger};
+    use crate::network::{
+        AudioSink, ElevenLabsProvider, ProviderConnection, RetryPolicy, SessionOptions,
+        SpeechToTextProvider, TranscriptEvent, WordKind, WordTiming,
+    };
+    use crate::session::dictation::failover_candidates;
+    use crate::session::pipeline::CHUNK_SIZE;
+    use crate::utils::Metrics;
+    use anyhow::Result;
+    use futures_util::future::BoxFuture;
+    use futures_util::FutureExt;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    /// What the fake provider received
+    #[derive(Default)]
+    struct SinkLog {
+        chunks: Vec<(usize, bool)>,
+        closed: bool,
+    }
+
+    impl SinkLog {
+        fn commits(&self) -> usize {
+            self.chunks.iter().filter(|(_, commit)| *commit).count()
+        }
+
+        fn samples(&self) -> usize {
+            self.chunks.iter().map(|(samples, _)| samples).sum()
+        }
+    }
+
+    /// Provider that records audio and replays scripted transcript events
+    struct FakeProvider {
+        log: Arc<Mutex<SinkLog>>,
+        events: Mutex<Option<mpsc::Receiver<TranscriptEvent>>>,
+        fail_connect: AtomicBool,
+        options: Mutex<Option<SessionOptions>>,
+    }
+
+    impl FakeProvider {
+        fn new() -> (Arc<Self>, mpsc::Sender<TranscriptEvent>) {
+            let (events_tx, events_rx) = mpsc::channel(16);
+            let provider = Arc::new(Self {
+                log: Arc::new(Mutex::new(SinkLog::default())),
+                events: Mutex::new(Some(events_rx)),
+                fail_connect: AtomicBool::new(false),
+                options: Mutex::new(None),
+            });
+            (provider, events_tx)
+        }
+    }
+
+    struct FakeSink {
+        log: Arc<Mutex<SinkLog>>,
+    }
+
+    impl AudioSink for FakeSink {
+        fn send_audio<'a>(
+            &'a mut self,
+            samples: &'a [f32],
+            commit: bool,
+        ) -> BoxFuture<'a, Result<()>> {
+            self.log
+                .lock()
+                .unwrap()
+                .chunks
+                .push((samples.len(), commit));
+            async { Ok(()) }.boxed()
+        }
+
+        fn close(&mut self) -> BoxFuture<'_, Result<()>> {
+            self.log.lock().unwrap().closed = true;
+            async { Ok(()) }.boxed()
+        }
+    }
+
+    impl SpeechToTextProvider for FakeProvider {
+        fn name(&self) -> &str {
+            "fake"
+        }
+
+        fn connect<'a>(
+            &'a self,
+            options: &'a SessionOptions,
+        ) -> BoxFuture<'a, Result<ProviderConnection>> {
+            async move {
+                if self.fail_connect.load(Ordering::SeqCst) {
+                    anyhow::bail!("connection refused");
+                }
+                *self.options.lock().unwrap() = Some(options.clone());
+                let events = self
+                    .events
+                    .lock()
+                    .unwrap()
+                    .take()
+                    .ok_or_else(|| anyhow::anyhow!("already connected"))?;
+                Ok(ProviderConnection {
+                    sink: Box::new(FakeSink {
+                        log: self.log.clone(),
+                    }),
+                    events,
+                })
+            }
+            .boxed()
+        }
+    }
+
+    fn word(text: &str, start: f64, end: f64) -> WordTiming {
+        WordTiming {
+            text: text.to_string(),
+            start,
+            end,
+            kind: WordKind::Word,
+            speaker: None,
+            logprob: Some(-0.2),
+        }
+    }
+
+    fn tone(amplitude: f32) -> Vec<f32> {
+        (0..CHUNK_SIZE)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 200.0 * i as f32 / 16000.0).sin())
+            .collect()
+    }
+
+    async fn feed_speech_burst(audio_tx: &mpsc::Sender<Vec<f32>>) {
+        for _ in 0..10 {
+            audio_tx.send(tone(0.5)).await.unwrap();
+        }
+        for _ in 0..30 {
+            audio_tx.send(vec![0.0; CHUNK_SIZE]).await.unwrap();
+        }
+    }
+
+    fn fast_reconnect() -> Option<RetryPolicy> {
+        Some(
+            RetryPolicy::new()
+                .with_max_attempts(3)
+                .with_initial_delay(Duration::from_millis(10)),
+        )
+    }
+
+    async fn wait_until(mut condition: impl FnMut() -> bool) {
+        for _ in 0..200 {
+            if condition() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("condition not met in time");
+    }
+
+    async fn next_event(
+        events: &mut mpsc::UnboundedReceiver<SessionEvent>,
+        matches: impl Fn(&SessionEvent) -> bool,
+    ) -> SessionEvent {
+        tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                let event = events.recv().await.expect("event channel closed");
+                if matches(&event) {
+                    return event;
+                }
+            }
+        })
+        .await
+        .expect("event not received in time")
+    }
+
+    fn is_committed_text(text: &'static str) -> impl Fn(&SessionEvent) -> bool {
+        move |e| matches!(e, SessionEvent::CommittedTranscript { text: t, .. } if t == text)
+    }
+
+    #[tokio::test]
+    async fn test_session_sends_speech_and_commits() {
+        let (provider, _transcripts) = FakeProvider::new();
+        let (mut session, mut events) =
+            DictationSession::new(SessionConfig::new(), provider.clone());
+        let (audio_tx, audio_rx) = mpsc::channel(100);
+
+        session.start_with_source(audio_rx, 16000).await.unwrap();
+        assert!(session.is_running());
+
+        feed_speech_burst(&audio_tx).await;
+        wait_until(|| provider.log.lock().unwrap().commits() == 1).await;
+
+        next_event(&mut events, |e| *e == SessionEvent::SpeechStarted).await;
+        next_event(&mut events, |e| *e == SessionEvent::SpeechEnded).await;
+
+        session.stop().await.unwrap();
+        assert!(!session.is_running());
+        assert!(provider.log.lock().unwrap().closed);
+    }
+
+    #[tokio::test]
+    async fn test_session_records_metrics() {
+        let (provider, transcripts) = FakeProvider::new();
+        let metrics = Metrics::new();
+        let config = SessionConfig::new().with_metrics(metrics.clone());
+        let (mut session, mut events) = DictationSession::new(config, provider.clone());
+        let (audio_tx, audio_rx) = mpsc::channel(100);
+
+        session.start_with_source(audio_rx, 16000).await.unwrap();
+        feed_speech_burst(&audio_tx).await;
+        wait_until(|| provider.log.lock().unwrap().commits() == 1).await;
+
+        // The transcript arrives a while after the commit was sent
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        transcripts
+            .send(TranscriptEvent::Partial {
+                text: "hel".to_string(),
+            })
+            .await
+            .unwrap();
+        transcripts
+            .send(TranscriptEvent::Committed {
+                text: "hello".to_string(),
+                language_code: None,
+                confidence: None,
+                words: Vec::new(),
+            })
+            .await
+            .unwrap();
+        next_event(&mut events, |e| {
+            matches!(e, SessionEvent::CommittedTranscript { .. })
+        })
+        .await;
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.audio_packets_processed, 40);
+        assert_eq!(snapshot.partial_transcripts, 1);
+        assert_eq!(snapshot.committed_transcripts, 1);
+        assert!(
+            (60..1000).contains(&snapshot.transcript_latency_p50_ms),
+            "{}",
+            snapshot.transcript_latency_p50_ms
+        );
+
+        drop(transcripts);
+        session.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stop_commits_pending_speech() {
+        let (provider, transcripts) = FakeProvider::new();
+        let (mut session, _events) = DictationSession::new(SessionConfig::new(), provider.clone());
+        let (audio_tx, audio_rx) = mpsc::channel(100);
+
+        session.start_with_source(audio_rx, 16000).await.unwrap();
+
+        // Speech without the trailing silence that would let the VAD commit
+        for _ in 0..10 {
+            audio_tx.send(tone(0.5)).await.unwrap();
+        }
+        // Queued chunks may be coalesced into fewer messages
+        wait_until(|| provider.log.lock().unwrap().samples() >= 5 * CHUNK_SIZE).await;
+        assert_eq!(provider.log.lock().unwrap().commits(), 0);
+
+        // Close the provider once the sink is closed so stop doesn't wait for the drain timeout
+        let provider_closed = async {
+            wait_until(|| provider.log.lock().unwrap().closed).await;
+            drop(transcripts);
+        };
+        let (stopped, _) = tokio::join!(session.stop(), provider_closed);
+        stopped.unwrap();
+
+        let log = provider.log.lock().unwrap();
+        assert_eq!(log.commits(), 1);
+        assert_eq!(log.chunks.last(), Some(&(0, true)));
+        assert!(log.closed);
+    }
+
+    #[tokio::test]
+    async fn test_session_forwards_transcripts() {
+        let (provider, transcripts) = FakeProvider::new();
+        let (mut session, mut events) = DictationSession::new(SessionConfig::new(), provider);
+        let (_audio_tx, audio_rx) = mpsc::channel(100);
+
+        session.start_with_source(audio_rx, 16000).await.unwrap();
+
+        transcripts
+            .send(TranscriptEvent::Partial {
+                text: "hel".to_string(),
+            })
+            .await
+            .unwrap();
+        transcripts
+            .send(TranscriptEvent::Committed {
+                text: "hello".to_string(),
+                language_code: Some("eng".to_string()),
+                confidence: Some(0.8),
+                words: vec![word("hello", 0.0, 0.5)],
+            })
+            .await
+            .unwrap();
+
+        let partial = next_event(&mut events, |e| {
+            matches!(e, SessionEvent::PartialTranscript { .. })
+        })
+        .await;
+        assert_eq!(
+            partial,
+            SessionEvent::PartialTranscript {
+                text: "hel".to_string()
+            }
+        );
+        let committed = next_event(&mut events, |e| {
+            matches!(e, SessionEvent::CommittedTranscript { .. })
+        })
+        .await;
+        assert_eq!(
+            committed,
+            SessionEvent::CommittedTranscript {
+                text: "hello".to_string(),
+                language_code: Some("eng".to_string()),
+                confidence: Some(0.8),
+                words: vec![word("hello", 0.0, 0.5)]
+            }
+        );
+
+        drop(transcripts);
+        session.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_session_passes_options_to_provider() {
+        let (provider, transcripts) = FakeProvider::new();
+        let options = SessionOptions::new()
+            .with_auto_detect()
+            .with_model("test_model");
+        let config = SessionConfig::new().with_options(options.clone());
+        let (mut session, _events) = DictationSession::new(config, provider.clone());
+        let (_audio_tx, audio_rx) = mpsc::channel(100);
+
+        session.start_with_source(audio_rx, 16000).await.unwrap();
+        assert_eq!(*provider.options.lock().unwrap(), Some(options));
+
+        drop(transcripts);
+        session.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_session_stops_when_provider_closes() {
+        let (provider, transcripts) = FakeProvider::new();
+        let config = SessionConfig::new().with_reconnect(None);
+        let (mut session, mut events) = DictationSession::new(config, provider);
+        let (_audio_tx, audio_rx) = mpsc::channel(100);
+
+        session.start_with_source(audio_rx, 16000).await.unwrap();
+        drop(transcripts);
+
+        let stopped = next_event(&mut events, |e| matches!(e, SessionEvent::Stopped { .. })).await;
+        assert_eq!(
+            stopped,
+            SessionEvent::Stopped {
+                reason: "WebSocket connection closed".to_string()
+            }
+        );
+        assert!(!session.is_running());
+    }
+
+    #[tokio::test]
+    async fn test_session_stops_when_reconnect_fails() {
+        let (provider, transcripts) = FakeProvider::new();
+        let config = SessionConfig::new().with_reconnect(fast_reconnect());
+        let (mut session, mut events) = DictationSession::new(config, provider);
+        let (_audio_tx, audio_rx) = mpsc::channel(100);
+
+        session.start_with_source(audio_rx, 16000).await.unwrap();
+        drop(transcripts);
+
+        next_event(&mut events, |e| {
+            matches!(e, SessionEvent::Reconnecting { .. })
+        })
+        .await;
+        let stopped = next_event(&mut events, |e| matches!(e, SessionEvent::Stopped { .. })).await;
+        let SessionEvent::Stopped { reason } = stopped else {
+            unreachable!()
+        };
+        assert!(reason.contains("already connected"), "{}", reason);
+        assert!(!session.is_running());
+    }
+
+    #[tokio::test]
+    async fn test_session_resumes_after_connection_drop() {
+        // The first connection transcribes one segment, then stalls and drops
+        // while the second segment is in flight
+        let server = MockScribeServer::builder()
+            .connection(
+                MockConnection::new()
+                    .on(MockTrigger::EveryCommit, [MockReply::committed("hello")])
+                    .on(
+                        MockTrigger::Commit(1),
+                        [
+                            MockReply::Delay(Duration::from_millis(300)),
+                            MockReply::Disconnect,
+                        ],
+                    ),
+            )
+            .connection(
+                MockConnection::new().on(MockTrigger::EveryCommit, [MockReply::committed("world")]),
+            )
+            .start()
+            .await
+            .unwrap();
+
+        let provider =
+            Arc::new(ElevenLabsProvider::new("test-key".to_string()).with_endpoint(server.url()));
+        let config = SessionConfig::new().with_reconnect(fast_reconnect());
+        let (mut session, mut events) = DictationSession::new(config, provider);
+        let (audio_tx, audio_rx) = mpsc::channel(100);
+        session.start_with_source(audio_rx, 16000).await.unwrap();
+
+        feed_speech_burst(&audio_tx).await;
+        next_event(&mut events, is_committed_text("hello")).await;
+        feed_speech_burst(&audio_tx).await;
+
+        next_event(&mut events, |e| {
+            matches!(e, SessionEvent::Reconnecting { .. })
+        })
+        .await;
+        next_event(&mut events, |e| *e == SessionEvent::Reconnected).await;
+        next_event(&mut events, is_committed_text("world")).await;
+        assert!(session.is_running());
+        assert_eq!(server.connections(), 2);
+
+        // The second segment was replayed with the first as context
+        let resumed: Vec<_> = server
+            .received()
+            .into_iter()
+            .filter(|chunk| chunk.connection == 1)
+            .collect();
+        assert!(resumed.len() >= 2, "{:?}", resumed);
+        assert_eq!(resumed[0].previous_text.as_deref(), Some("hello"));
+        assert_eq!(resumed.iter().filter(|chunk| chunk.commit).count(), 1);
+
+        session.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_timestamped_commits_are_delivered_once() {
+        let server = MockScribeServer::builder()
+            .connection(MockConnection::new().on(
+                MockTrigger::EveryCommit,
+                [MockReply::committed_with_timestamps("hello there", "eng")],
+            ))
+            .start()
+            .await
+            .unwrap();
+
+        let provider =
+            Arc::new(ElevenLabsProvider::new("test-key".to_string()).with_endpoint(server.url()));
+        let metrics = Metrics::new();
+        let config = SessionConfig::new().with_metrics(metrics.clone());
+        let (mut session, mut events) = DictationSession::new(config, provider);
+        let (audio_tx, audio_rx) = mpsc::channel(100);
+        session.start_with_source(audio_rx, 16000).await.unwrap();
+
+        for _ in 0..2 {
+            feed_speech_burst(&audio_tx).await;
+            let committed = next_event(&mut events, is_committed_text("hello there")).await;
+            let SessionEvent::CommittedTranscript { words, .. } = committed else {
+                unreachable!()
+            };
+            assert_eq!(words.len(), 2);
+        }
+
+        // The plain copies of the commits never show up
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        while let Ok(event) = events.try_recv() {
+            assert!(
+                !matches!(event, SessionEvent::CommittedTranscript { .. }),
+                "{:?}",
+                event
+            );
+        }
+        assert_eq!(metrics.snapshot().committed_transcripts, 2);
+
+        session.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_detected_language_is_reported() {
+        let server = MockScribeServer::builder()
+            .connection(MockConnection::new().on(
+                MockTrigger::EveryCommit,
+                [MockReply::committed_with_timestamps("bonjour", "fra")],
+            ))
+            .start()
+            .await
+            .unwrap();
+
+        let provider =
+            Arc::new(ElevenLabsProvider::new("test-key".to_string()).with_endpoint(server.url()));
+        let config = SessionConfig::new().with_options(SessionOptions::new().with_auto_detect());
+        let (mut session, mut events) = DictationSession::new(config, provider);
+        let (audio_tx, audio_rx) = mpsc::channel(100);
+        session.start_with_source(audio_rx, 16000).await.unwrap();
+
+        feed_speech_burst(&audio_tx).await;
+        let committed = next_event(&mut events, is_committed_text("bonjour")).await;
+        let SessionEvent::CommittedTranscript { language_code, .. } = committed else {
+            unreachable!()
+        };
+        assert_eq!(language_code.as_deref(), Some("fra"));
+
+        session.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_session_stops_on_fatal_error() {
+        let (provider, transcripts) = FakeProvider::new();
+        let (mut session, mut events) = DictationSession::new(SessionConfig::new(), provider);
+        let (_audio_tx, audio_rx) = mpsc::channel(100);
+
+        session.start_with_source(audio_rx, 16000).await.unwrap();
+        transcripts
+            .send(TranscriptEvent::Error {
+                message: "bad key".to_string(),
+                fatal: true,
+            })
+            .await
+            .unwrap();
+
+        next_event(&mut events, |e| matches!(e, SessionEvent::Stopped { .. })).await;
+        assert!(!session.is_running());
+
+        drop(transcripts);
+        session.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_paused_session_sends_no_speech() {
+        let (provider, transcripts) = FakeProvider::new();
+        let (mut session, mut events) =
+            DictationSession::new(SessionConfig::new(), provider.clone());
+        let (audio_tx, audio_rx) = mpsc::channel(100);
+
+        session.start_with_source(audio_rx, 16000).await.unwrap();
+        session.pause();
+        assert!(session.is_paused());
+
+        feed_speech_burst(&audio_tx).await;
+        for _ in 0..30 {
+            next_event(
+                &mut events,
+                |e| matches!(e, SessionEvent::AudioLevel { level, .. } if *level == 0.0),
+            )
+            .await;
+        }
+
+        drop(transcripts);
+        session.stop().await.unwrap();
+
+        assert!(provider.log.lock().unwrap().chunks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_connect_failure_is_reported() {
+        let (provider, _transcripts) = FakeProvider::new();
+        provider.fail_connect.store(true, Ordering::SeqCst);
+        let (mut session, _events) = DictationSession::new(SessionConfig::new(), provider);
+        let (_audio_tx, audio_rx) = mpsc::channel(100);
+
+        let err = session
+            .start_with_source(audio_rx, 16000)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("connection refused"));
+        assert!(!session.is_running());
+    }
+
+    #[tokio::test]
+    async fn test_missing_silero_model_is_reported() {
+        let (provider, _transcripts) = FakeProvider::new();
+        let config = SessionConfig::new()
+            .with_vad_engine(VadEngine::Silero)
+            .with_silero_vad(Some("missing/silero_vad.onnx".into()));
+        let (mut session, _events) = DictationSession::new(config, provider.clone());
+        let (_audio_tx, audio_rx) = mpsc::channel(100);
+
+        let err = session
+            .start_with_source(audio_rx, 16000)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("VAD model"));
+        assert!(!session.is_running());
+        assert!(provider.options.lock().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_silero_without_model_is_reported() {
+        let (provider, _transcripts) = FakeProvider::new();
+        let config = SessionConfig::new().with_vad_engine(VadEngine::Silero);
+        let (mut session, _events) = DictationSession::new(config, provider.clone());
+        let (_audio_tx, audio_rx) = mpsc::channel(100);
+
+        let err = session
+            .start_with_source(audio_rx, 16000)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("needs a model"));
+        assert!(!session.is_running());
+        assert!(provider.options.lock().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_other_engines_ignore_silero_model() {
+        for engine in [VadEngine::Energy, VadEngine::Adaptive] {
+            let (provider, transcripts) = FakeProvider::new();
+            let config = SessionConfig::new()
+                .with_vad_engine(engine)
+                .with_silero_vad(Some("missing/silero_vad.onnx".into()));
+            let (mut session, _events) = DictationSession::new(config, provider);
+            let (_audio_tx, audio_rx) = mpsc::channel(100);
+
+            session.start_with_source(audio_rx, 16000).await.unwrap();
+
+            drop(transcripts);
+            session.stop().await.unwrap();
+        }
+    }
+
+    #[test]
+    fn test_failover_candidates() {
+        let priority = vec!["USB Mic".to_string(), "Headset".to_string()];
+
+        assert_eq!(
+            failover_candidates(&priority, Some("USB Mic")),
+            vec![
+                Some("Headset".to_string()),
+                None,
+                Some("USB Mic".to_string())
+            ]
+        );
+        assert_eq!(failover_candidates(&[], None), vec![None]);
+    }
+}
+
+#[cfg(test)]
+mod replay_tests {
+    use crate::session::replay::ReplayBuffer;
+
+    fn lengths(buffer: &ReplayBuffer) -> Vec<(usize, bool)> {
+        buffer
+            .chunks()
+            .map(|chunk| (chunk.samples.len(), chunk.commit))
+            .collect()
+    }
+
+    #[test]
+    fn test_acknowledge_releases_committed_segments() {
+        let mut buffer = ReplayBuffer::new(1000);
+        buffer.push(&[0.1; 10], false);
+        buffer.push(&[0.1; 10], true);
+        buffer.push(&[0.1; 20], false);
+        assert_eq!(buffer.len(), 40);
+
+        buffer.acknowledge(0);
+        assert_eq!(buffer.len(), 40);
+
+        buffer.acknowledge(1);
+        assert_eq!(lengths(&buffer), vec![(20, false)]);
+
+        buffer.push(&[0.1; 5], true);
+        buffer.acknowledge(2);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.len(), 0);
+    }
+
+    #[test]
+    fn test_overflow_drops_oldest_audio() {
+        let mut buffer = ReplayBuffer::new(25);
+        buffer.push(&[0.1; 10], false);
+        buffer.push(&[0.1; 10], true);
+        buffer.push(&[0.1; 10], false);
+
+        assert_eq!(lengths(&buffer), vec![(10, true), (10, false)]);
+        assert_eq!(buffer.take_dropped(), 10);
+        assert_eq!(buffer.take_dropped(), 0);
+    }
+
+    #[test]
+    fn test_acknowledge_after_overflow() {
+        let mut buffer = ReplayBuffer::new(15);
+        buffer.push(&[0.1; 10], true);
+        buffer.push(&[0.1; 10], false);
+        assert_eq!(lengths(&buffer), vec![(10, false)]);
+
+        // The dropped segment's transcript must not release the next one
+        buffer.acknowledge(1);
+        assert_eq!(lengths(&buffer), vec![(10, false)]);
+    }
+}
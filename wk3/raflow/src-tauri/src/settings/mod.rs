@@ -0,0 +1,11 @@
+pub mod model;
+pub mod store;
+
+#[cfg(test)]
+mod tests;
+
+pub use model::{
+    HistorySettings, HotkeySettings, MetricsExporterSettings, NoiseSuppressionSettings,
+    OfflineSettings, Settings,
+};
+pub use store::SettingsStore;
@@ -0,0 +1,349 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, warn};
+use url::Url;
+
+use crate::audio::denoise::DEFAULT_STRENGTH;
+use crate::audio::downmix::ChannelMode;
+use crate::audio::vad::{VadEngine, DEFAULT_ENERGY_THRESHOLD};
+use crate::input::InjectionStrategy;
+use crate::network::SessionOptions;
+use crate::session::pipeline::DEFAULT_PRE_ROLL;
+use crate::utils::{RAFlowError, DEFAULT_SAMPLE_INTERVAL};
+use crate::whisper::WhisperModel;
+
+/// Current settings schema version
+///
+/// v2 moved the API key out of the settings into the credential store.
+pub const SETTINGS_VERSION: u32 = 2;
+
+/// Longest pre-roll that can be configured
+pub const MAX_PRE_ROLL_MS: u32 = 2000;
+
+/// Capture buffer sizes that can be configured, in frames
+pub const BUFFER_SIZE_RANGE: std::ops::RangeInclusive<u32> = 64..=8192;
+
+/// Resource sampling intervals that can be configured, in ms
+pub const SAMPLE_INTERVAL_RANGE_MS: std::ops::RangeInclusive<u32> = 500..=60_000;
+
+/// Global shortcut bindings
+///
+/// Accelerators use the global-shortcut plugin syntax, e.g. `Alt+Space`.
+/// `None` disables the binding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeySettings {
+    /// Record while the keys are held down
+    pub push_to_talk: Option<String>,
+    /// Start recording on first press, stop on the second
+    pub toggle: Option<String>,
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        Self {
+            push_to_talk: Some("Alt+Space".to_string()),
+            toggle: Some("CommandOrControl+Shift+Space".to_string()),
+        }
+    }
+}
+
+/// Transcript history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistorySettings {
+    /// Record committed transcripts
+    pub enabled: bool,
+    /// Delete entries older than this many days, `None` keeps them forever
+    pub retention_days: Option<u32>,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            retention_days: Some(30),
+        }
+    }
+}
+
+/// Background noise suppression before voice detection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoiseSuppressionSettings {
+    pub enabled: bool,
+    /// 0.0 - 1.0, higher removes more noise but can make speech sound thin
+    pub strength: f32,
+}
+
+impl Default for NoiseSuppressionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            strength: DEFAULT_STRENGTH,
+        }
+    }
+}
+
+/// Local transcription without a provider connection
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OfflineSettings {
+    /// Transcribe with a local Whisper model instead of the provider
+    pub enabled: bool,
+    /// Model to use, downloaded into the app data directory
+    pub model: WhisperModel,
+}
+
+/// Local HTTP endpoint for scraping metrics in OpenMetrics format
+///
+/// Only available in builds with the `prometheus` feature. Changes take
+/// effect on the next start.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsExporterSettings {
+    pub enabled: bool,
+    /// Port on 127.0.0.1 serving `/metrics`
+    pub port: u16,
+}
+
+impl Default for MetricsExporterSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 9464,
+        }
+    }
+}
+
+/// User settings persisted in the settings store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Schema version, used for migrations
+    pub version: u32,
+    /// Input device name, `None` for the system default
+    pub device_name: Option<String>,
+    /// Devices to fall back to, in order, when the input device fails
+    pub device_priority: Vec<String>,
+    /// How multichannel devices are mixed down to mono
+    pub channel_mode: ChannelMode,
+    /// Capture buffer size in frames, `None` for the device default
+    pub buffer_size: Option<u32>,
+    /// Voice activity detector
+    pub vad_engine: VadEngine,
+    /// VAD energy threshold, used by the energy detector
+    pub vad_threshold: f32,
+    /// Audio from before the detected speech start sent along, in ms
+    pub pre_roll_ms: u32,
+    /// Noise suppression
+    pub noise_suppression: NoiseSuppressionSettings,
+    /// Normalize quiet or loud microphones with automatic gain control
+    pub auto_gain: bool,
+    /// Text injection strategy, `None` to pick one per window
+    pub injection_strategy: Option<InjectionStrategy>,
+    /// Type committed transcripts into the focused app as they arrive
+    pub auto_inject: bool,
+    /// Language, model and query options for new sessions
+    pub transcription: SessionOptions,
+    /// Provider endpoint, e.g. a proxy, `None` for the public API
+    pub endpoint: Option<String>,
+    /// Global shortcuts
+    pub hotkeys: HotkeySettings,
+    /// Transcript history
+    pub history: HistorySettings,
+    /// Offline transcription
+    pub offline: OfflineSettings,
+    /// Metrics endpoint for monitoring
+    pub metrics_exporter: MetricsExporterSettings,
+    /// Time between two memory and CPU samples in ms, applied on restart
+    pub sample_interval_ms: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            device_name: None,
+            device_priority: Vec::new(),
+            channel_mode: ChannelMode::default(),
+            buffer_size: None,
+            vad_engine: VadEngine::default(),
+            vad_threshold: DEFAULT_ENERGY_THRESHOLD,
+            pre_roll_ms: DEFAULT_PRE_ROLL.as_millis() as u32,
+            noise_suppression: NoiseSuppressionSettings::default(),
+            auto_gain: false,
+            injection_strategy: None,
+            auto_inject: false,
+            transcription: SessionOptions::default(),
+            endpoint: None,
+            hotkeys: HotkeySettings::default(),
+            history: HistorySettings::default(),
+            offline: OfflineSettings::default(),
+            metrics_exporter: MetricsExporterSettings::default(),
+            sample_interval_ms: DEFAULT_SAMPLE_INTERVAL.as_millis() as u32,
+        }
+    }
+}
+
+impl Settings {
+    /// Restore settings from a stored JSON document
+    ///
+    /// Older documents are migrated to the current schema. Invalid fields
+    /// fall back to their defaults, the valid ones are kept.
+    ///
+    /// # Returns
+    /// The settings and whether they changed and should be saved back
+    pub fn from_stored(value: Value) -> (Self, bool) {
+        let version = value.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+
+        if version > SETTINGS_VERSION {
+            warn!(
+                "Settings were written by a newer version (v{}), reading what we understand",
+                version
+            );
+        }
+
+        // Unversioned documents predate the schema version and only need
+        // the missing fields filled in. A v1 API key is moved out by
+        // `take_legacy_api_key` before this runs. Add per-version upgrade
+        // steps here whenever the layout changes.
+        let mut settings = match serde_json::from_value::<Settings>(value.clone()) {
+            Ok(settings) => settings,
+            Err(e) => {
+                warn!("Stored settings are invalid, keeping the valid ones: {}", e);
+                Self::from_valid_fields(value)
+            }
+        };
+
+        let migrated = version < SETTINGS_VERSION;
+        if migrated {
+            info!(
+                "Migrated settings from v{} to v{}",
+                version, SETTINGS_VERSION
+            );
+            settings.version = SETTINGS_VERSION;
+        }
+        (settings, migrated)
+    }
+
+    /// Restore the fields of a stored document one at a time
+    ///
+    /// A field that doesn't parse, e.g. after its type changed, is left at
+    /// its default instead of discarding the whole document.
+    fn from_valid_fields(value: Value) -> Self {
+        let Value::Object(fields) = value else {
+            return Self::default();
+        };
+
+        let mut valid = serde_json::Map::new();
+        for (name, field) in fields {
+            let mut candidate = valid.clone();
+            candidate.insert(name.clone(), field);
+            match serde_json::from_value::<Settings>(Value::Object(candidate.clone())) {
+                Ok(_) => valid = candidate,
+                Err(e) => warn!("Ignoring invalid setting `{}`: {}", name, e),
+            }
+        }
+
+        serde_json::from_value(Value::Object(valid)).unwrap_or_default()
+    }
+
+    /// Check that the settings can be applied
+    pub fn validate(&self) -> Result<(), RAFlowError> {
+        if !(self.vad_threshold > 0.0 && self.vad_threshold <= 1.0) {
+            return Err(RAFlowError::Config(format!(
+                "VAD threshold must be in (0, 1], got {}",
+                self.vad_threshold
+            )));
+        }
+
+        if let Some(frames) = self.buffer_size {
+            if !BUFFER_SIZE_RANGE.contains(&frames) {
+                return Err(RAFlowError::Config(format!(
+                    "Buffer size must be in {:?} frames, got {}",
+                    BUFFER_SIZE_RANGE, frames
+                )));
+            }
+        }
+
+        if self.pre_roll_ms > MAX_PRE_ROLL_MS {
+            return Err(RAFlowError::Config(format!(
+                "Pre-roll must be at most {}ms, got {}ms",
+                MAX_PRE_ROLL_MS, self.pre_roll_ms
+            )));
+        }
+
+        if !SAMPLE_INTERVAL_RANGE_MS.contains(&self.sample_interval_ms) {
+            return Err(RAFlowError::Config(format!(
+                "Sample interval must be in {:?}ms, got {}ms",
+                SAMPLE_INTERVAL_RANGE_MS, self.sample_interval_ms
+            )));
+        }
+
+        if !(0.0..=1.0).contains(&self.noise_suppression.strength) {
+            return Err(RAFlowError::Config(format!(
+                "Noise suppression strength must be in [0, 1], got {}",
+                self.noise_suppression.strength
+            )));
+        }
+
+        if self.transcription.model_id.trim().is_empty() {
+            return Err(RAFlowError::Config(
+                "Model id must not be empty".to_string(),
+            ));
+        }
+
+        if self.metrics_exporter.enabled && self.metrics_exporter.port == 0 {
+            return Err(RAFlowError::Config(
+                "Metrics exporter port must not be 0".to_string(),
+            ));
+        }
+
+        if let Some(endpoint) = &self.endpoint {
+            let url = Url::parse(endpoint).map_err(|e| {
+                RAFlowError::Config(format!("Invalid endpoint {}: {}", endpoint, e))
+            })?;
+            if !matches!(url.scheme(), "ws" | "wss") {
+                return Err(RAFlowError::Config(format!(
+                    "Endpoint must be a ws:// or wss:// URL, got {}",
+                    endpoint
+                )));
+            }
+        }
+
+        for hotkey in [&self.hotkeys.push_to_talk, &self.hotkeys.toggle]
+            .into_iter()
+            .flatten()
+        {
+            if hotkey.trim().is_empty() {
+                return Err(RAFlowError::Config(
+                    "Hotkey must not be empty, use null to disable it".to_string(),
+                ));
+            }
+        }
+
+        if self.history.retention_days == Some(0) {
+            return Err(RAFlowError::Config(
+                "History retention must be at least one day, use null to keep everything"
+                    .to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Remove a plain-text API key left by a v1 settings document
+    ///
+    /// # Returns
+    /// The key, so it can be moved into the credential store
+    pub fn take_legacy_api_key(value: &mut Value) -> Option<String> {
+        value
+            .as_object_mut()?
+            .remove("api_key")?
+            .as_str()
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(str::to_string)
+    }
+}
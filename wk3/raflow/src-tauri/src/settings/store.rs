@@ -0,0 +1,85 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{AppHandle, Wry};
+use tauri_plugin_store::{Store, StoreExt};
+use tracing::{error, info};
+
+use super::model::Settings;
+
+/// File name of the settings store inside the app data directory
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// Key the settings document is stored under
+const SETTINGS_KEY: &str = "settings";
+
+/// Settings persisted through `tauri-plugin-store`
+#[derive(Clone)]
+pub struct SettingsStore {
+    store: Arc<Store<Wry>>,
+}
+
+impl SettingsStore {
+    /// Open (or create) the settings store
+    ///
+    /// # Arguments
+    /// * `app` - Tauri app handle
+    /// * `data_dir` - Directory the settings file lives in
+    pub fn open(app: &AppHandle, data_dir: &Path) -> Result<Self> {
+        let path = data_dir.join(SETTINGS_FILE);
+        let store = app
+            .store(&path)
+            .map_err(|e| anyhow!("Failed to open settings store {}: {}", path.display(), e))?;
+
+        info!("Settings store opened: {}", path.display());
+        Ok(Self { store })
+    }
+
+    /// Load the settings, migrating and saving them back if needed
+    pub fn load(&self) -> Settings {
+        let Some(value) = self.store.get(SETTINGS_KEY) else {
+            info!("No saved settings, using defaults");
+            return Settings::default();
+        };
+
+        let (settings, migrated) = Settings::from_stored(value);
+        if migrated {
+            if let Err(e) = self.save(&settings) {
+                error!("Failed to save migrated settings: {}", e);
+            }
+        }
+
+        settings
+    }
+
+    /// Move a plain-text API key out of an old settings file
+    ///
+    /// The key is handed to `persist` and only removed from the file once
+    /// that succeeded.
+    pub fn migrate_legacy_api_key(&self, persist: impl FnOnce(&str) -> Result<()>) -> Result<()> {
+        let Some(mut value) = self.store.get(SETTINGS_KEY) else {
+            return Ok(());
+        };
+        let Some(api_key) = Settings::take_legacy_api_key(&mut value) else {
+            return Ok(());
+        };
+
+        persist(&api_key)?;
+        self.store.set(SETTINGS_KEY, value);
+        self.store
+            .save()
+            .map_err(|e| anyhow!("Failed to save settings: {}", e))?;
+
+        info!("Moved API key from settings file to the credential store");
+        Ok(())
+    }
+
+    /// Persist the settings to disk
+    pub fn save(&self, settings: &Settings) -> Result<()> {
+        let value = serde_json::to_value(settings)?;
+        self.store.set(SETTINGS_KEY, value);
+        self.store
+            .save()
+            .map_err(|e| anyhow!("Failed to save settings: {}", e))
+    }
+}
@@ -0,0 +1,182 @@
+use super::*;
+
+#[cfg(test)]
+mod model_tests {
+    use super::*;
+    use crate::audio::{ChannelMode, VadEngine};
+    use crate::input::InjectionStrategy;
+    use crate::settings::model::SETTINGS_VERSION;
+    use crate::whisper::WhisperModel;
+    use serde_json::json;
+
+    #[test]
+    fn test_defaults_are_valid() {
+        let settings = Settings::default();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let settings = Settings {
+            device_name: Some("USB Mic".to_string()),
+            vad_engine: VadEngine::Silero,
+            vad_threshold: 0.05,
+            injection_strategy: Some(InjectionStrategy::Clipboard),
+            auto_inject: true,
+            ..Settings::default()
+        };
+
+        let value = serde_json::to_value(&settings).unwrap();
+        assert_eq!(value["injection_strategy"], "clipboard");
+        assert_eq!(value["vad_engine"], "silero");
+
+        let (restored, migrated) = Settings::from_stored(value);
+        assert_eq!(restored, settings);
+        assert!(!migrated);
+    }
+
+    #[test]
+    fn test_channel_mode() {
+        assert_eq!(Settings::default().channel_mode, ChannelMode::Mix);
+
+        let (settings, _) = Settings::from_stored(json!({ "channel_mode": { "single": 1 } }));
+        assert_eq!(settings.channel_mode, ChannelMode::Single(1));
+
+        let value = serde_json::to_value(Settings {
+            channel_mode: ChannelMode::Auto,
+            ..Settings::default()
+        })
+        .unwrap();
+        assert_eq!(value["channel_mode"], "auto");
+    }
+
+    #[test]
+    fn test_offline_settings() {
+        let (settings, _) = Settings::from_stored(json!({ "version": 2 }));
+        assert_eq!(settings.offline, OfflineSettings::default());
+        assert!(!settings.offline.enabled);
+
+        let (settings, _) = Settings::from_stored(json!({
+            "version": 2,
+            "offline": { "enabled": true, "model": "small" }
+        }));
+        assert!(settings.offline.enabled);
+        assert_eq!(settings.offline.model, WhisperModel::Small);
+    }
+
+    #[test]
+    fn test_unversioned_document_is_migrated() {
+        let (settings, migrated) = Settings::from_stored(json!({
+            "device_name": "USB Mic",
+            "vad_threshold": 0.02
+        }));
+
+        assert!(migrated);
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.device_name.as_deref(), Some("USB Mic"));
+        assert_eq!(settings.vad_threshold, 0.02);
+        assert_eq!(settings.pre_roll_ms, 500);
+        assert_eq!(settings.hotkeys, HotkeySettings::default());
+    }
+
+    #[test]
+    fn test_legacy_api_key_is_taken_out() {
+        let mut value = json!({
+            "version": 1,
+            "api_key": " sk-old ",
+            "vad_threshold": 0.02
+        });
+
+        assert_eq!(
+            Settings::take_legacy_api_key(&mut value),
+            Some("sk-old".to_string())
+        );
+        assert!(value.get("api_key").is_none());
+        assert_eq!(Settings::take_legacy_api_key(&mut value), None);
+
+        let (settings, migrated) = Settings::from_stored(value);
+        assert!(migrated);
+        assert_eq!(settings.vad_threshold, 0.02);
+    }
+
+    #[test]
+    fn test_invalid_fields_fall_back_to_defaults() {
+        let (settings, migrated) = Settings::from_stored(json!({
+            "version": 2,
+            "device_name": "USB Mic",
+            "vad_threshold": "loud",
+            "hotkeys": { "toggle": 42 },
+            "history": { "enabled": false }
+        }));
+
+        assert!(!migrated);
+        assert_eq!(settings.device_name.as_deref(), Some("USB Mic"));
+        assert_eq!(settings.vad_threshold, Settings::default().vad_threshold);
+        assert_eq!(settings.hotkeys, HotkeySettings::default());
+        assert!(!settings.history.enabled);
+
+        let (settings, _) = Settings::from_stored(json!("garbage"));
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_values() {
+        let settings = Settings {
+            vad_threshold: 0.0,
+            ..Settings::default()
+        };
+        assert!(settings.validate().is_err());
+
+        let settings = Settings {
+            pre_roll_ms: 5000,
+            ..Settings::default()
+        };
+        assert!(settings.validate().is_err());
+
+        let settings = Settings {
+            buffer_size: Some(16),
+            ..Settings::default()
+        };
+        assert!(settings.validate().is_err());
+
+        let settings = Settings {
+            sample_interval_ms: 0,
+            ..Settings::default()
+        };
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.noise_suppression.strength = 1.5;
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.hotkeys.toggle = Some("  ".to_string());
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.hotkeys.toggle = None;
+        assert!(settings.validate().is_ok());
+
+        let mut settings = Settings::default();
+        settings.history.retention_days = Some(0);
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.metrics_exporter.enabled = true;
+        settings.metrics_exporter.port = 0;
+        assert!(settings.validate().is_err());
+
+        let settings = Settings {
+            endpoint: Some("https://api.elevenlabs.io".to_string()),
+            ..Settings::default()
+        };
+        assert!(settings.validate().is_err());
+
+        let settings = Settings {
+            endpoint: Some("ws://127.0.0.1:9000/v1/speech-to-text/realtime".to_string()),
+            ..Settings::default()
+        };
+        assert!(settings.validate().is_ok());
+    }
+}
@@ -1,47 +1,299 @@
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::info;
+use tracing::{error, info};
 
-use crate::audio::{AudioCapture, AudioResampler, VoiceActivityDetector};
+use crate::audio::SileroVad;
+use crate::history::{HistoryStore, NewSegment};
 use crate::input::TextInjectorService;
-use crate::network::WebSocketClient;
+use crate::network::{ProviderKind, SessionOptions};
+use crate::secrets::SecretManager;
+use crate::session::DictationSession;
+use crate::settings::{Settings, SettingsStore};
 use crate::utils::Metrics;
+use crate::whisper::{ModelManager, WhisperProvider};
 
 #[derive(Clone)]
 pub struct AppState {
-    pub audio_capture: Arc<Mutex<Option<AudioCapture>>>,
-    pub resampler: Arc<Mutex<Option<AudioResampler>>>,
-    pub vad: Arc<Mutex<VoiceActivityDetector>>,
-    pub ws_client: Arc<Mutex<Option<WebSocketClient>>>,
+    pub session: Arc<Mutex<Option<DictationSession>>>,
+    /// Set while a session connects, `true` once a stop cancelled it
+    pub session_starting: Arc<Mutex<Option<bool>>>,
     pub is_recording: Arc<Mutex<bool>>,
     pub current_transcript: Arc<Mutex<String>>,
     pub api_key: Arc<Mutex<Option<String>>>,
     pub text_injector_service: Arc<Mutex<Option<TextInjectorService>>>,
     pub metrics: Arc<Metrics>,
+    pub settings: Arc<Mutex<Settings>>,
+    pub settings_store: Arc<Mutex<Option<SettingsStore>>>,
+    pub secrets: Arc<Mutex<Option<SecretManager>>>,
+    pub history: Arc<Mutex<Option<HistoryStore>>>,
+    pub whisper: Arc<Mutex<Option<Arc<WhisperProvider>>>>,
+    pub silero_model_path: Arc<Mutex<Option<PathBuf>>>,
 }
 
 impl AppState {
     pub fn new() -> Self {
         info!("Initializing RAFlow state");
         Self {
-            audio_capture: Arc::new(Mutex::new(None)),
-            resampler: Arc::new(Mutex::new(None)),
-            vad: Arc::new(Mutex::new(VoiceActivityDetector::default())),
-            ws_client: Arc::new(Mutex::new(None)),
+            session: Arc::new(Mutex::new(None)),
+            session_starting: Arc::new(Mutex::new(None)),
             is_recording: Arc::new(Mutex::new(false)),
             current_transcript: Arc::new(Mutex::new(String::new())),
             api_key: Arc::new(Mutex::new(None)),
             text_injector_service: Arc::new(Mutex::new(None)),
             metrics: Arc::new(Metrics::new()),
+            settings: Arc::new(Mutex::new(Settings::default())),
+            settings_store: Arc::new(Mutex::new(None)),
+            secrets: Arc::new(Mutex::new(None)),
+            history: Arc::new(Mutex::new(None)),
+            whisper: Arc::new(Mutex::new(None)),
+            silero_model_path: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// Reserve the start of a session
+    ///
+    /// Fails while a session is running or starting. The session itself is
+    /// only locked for the check, so stopping, pausing and resuming aren't
+    /// held up while the new session connects.
+    pub async fn begin_session_start(&self) -> Result<()> {
+        let session = self.session.lock().await;
+        let mut starting = self.session_starting.lock().await;
+        if starting.is_some() || session.as_ref().is_some_and(|s| s.is_running()) {
+            return Err(anyhow!("Already recording"));
+        }
+        *starting = Some(false);
+        Ok(())
+    }
+
+    /// Whether a session is still connecting
+    pub async fn is_session_starting(&self) -> bool {
+        self.session_starting.lock().await.is_some()
+    }
+
+    /// Ask a session that is still connecting to stop once it started
+    pub async fn cancel_session_start(&self) {
+        if let Some(cancelled) = self.session_starting.lock().await.as_mut() {
+            *cancelled = true;
+        }
+    }
+
+    /// Give up a reserved start after the session failed to start
+    pub async fn abort_session_start(&self) {
+        *self.session_starting.lock().await = None;
+    }
+
+    /// Store a session started after [`begin_session_start`](Self::begin_session_start)
+    ///
+    /// # Returns
+    /// Whether it was cancelled while connecting and should be stopped
+    pub async fn finish_session_start(&self, session: DictationSession) -> bool {
+        let mut guard = self.session.lock().await;
+        let cancelled = self.session_starting.lock().await.take().unwrap_or(false);
+        *guard = Some(session);
+        *self.is_recording.lock().await = true;
+        cancelled
+    }
+
     /// Initialize the text injector service (must be called after app is setup)
     pub async fn init_text_injector_service(&self, app: tauri::AppHandle) {
         let service = TextInjectorService::new(app);
         *self.text_injector_service.lock().await = Some(service);
         info!("Text injector service initialized");
     }
+
+    /// Pick the credential store and load the saved API key
+    pub async fn init_secrets(&self, data_dir: &Path) {
+        let secrets = SecretManager::platform(data_dir);
+        *self.api_key.lock().await = match secrets.api_key(ProviderKind::default()) {
+            Ok(api_key) => api_key,
+            Err(e) => {
+                error!("Failed to read API key: {}", e);
+                None
+            }
+        };
+        *self.secrets.lock().await = Some(secrets);
+    }
+
+    /// Open the settings store and load the saved settings
+    ///
+    /// Must run after [`init_secrets`](Self::init_secrets) so a plain-text
+    /// API key from an old settings file can be moved into the credential store.
+    pub async fn init_settings(&self, app: &tauri::AppHandle, data_dir: &Path) -> Result<()> {
+        let store = SettingsStore::open(app, data_dir)?;
+
+        if let Some(secrets) = self.secrets.lock().await.as_ref() {
+            let migrated = store.migrate_legacy_api_key(|api_key| {
+                secrets.set_api_key(ProviderKind::default(), api_key)?;
+                Ok(())
+            });
+            match migrated {
+                Ok(()) => {
+                    *self.api_key.lock().await = secrets.api_key(ProviderKind::default())?;
+                }
+                Err(e) => error!(
+                    "Failed to move API key to the credential store, please enter it again: {}",
+                    e
+                ),
+            }
+        }
+
+        let settings = store.load();
+        *self.settings_store.lock().await = Some(store);
+        self.apply_settings(settings).await;
+        info!("Settings loaded");
+        Ok(())
+    }
+
+    /// Validate, persist and apply new settings
+    pub async fn update_settings(&self, settings: Settings) -> Result<()> {
+        settings.validate()?;
+
+        if let Some(store) = self.settings_store.lock().await.as_ref() {
+            store.save(&settings)?;
+        }
+
+        self.apply_settings(settings).await;
+        Ok(())
+    }
+
+    /// Push settings into the running state
+    ///
+    /// Sessions that are already running keep their configuration, the
+    /// next session picks up the new values.
+    async fn apply_settings(&self, settings: Settings) {
+        *self.settings.lock().await = settings;
+    }
+
+    /// Save the API key in the credential store
+    pub async fn set_api_key(&self, api_key: &str) -> Result<()> {
+        let secrets = self.secrets.lock().await;
+        let secrets = secrets
+            .as_ref()
+            .ok_or_else(|| anyhow!("Credential store not initialized"))?;
+
+        secrets.set_api_key(ProviderKind::default(), api_key)?;
+        *self.api_key.lock().await = secrets.api_key(ProviderKind::default())?;
+        Ok(())
+    }
+
+    /// Remove the API key from the credential store
+    pub async fn clear_api_key(&self) -> Result<()> {
+        if let Some(secrets) = self.secrets.lock().await.as_ref() {
+            secrets.clear_api_key(ProviderKind::default())?;
+        }
+        *self.api_key.lock().await = None;
+        Ok(())
+    }
+
+    /// Open the history database and apply the retention policy
+    ///
+    /// Must run after [`init_settings`](Self::init_settings).
+    pub async fn init_history(&self, data_dir: &Path) -> Result<()> {
+        let store = HistoryStore::open(data_dir)?;
+        *self.history.lock().await = Some(store);
+        self.apply_history_retention().await;
+        Ok(())
+    }
+
+    /// Set up offline transcription with models in the app data directory
+    ///
+    /// Models are only loaded when an offline session starts.
+    pub async fn init_whisper(&self, data_dir: &Path) {
+        let provider = WhisperProvider::new(ModelManager::new(data_dir));
+        *self.whisper.lock().await = Some(Arc::new(provider));
+        info!("Offline transcription initialized");
+    }
+
+    /// The offline provider
+    pub async fn whisper(&self) -> Result<Arc<WhisperProvider>> {
+        self.whisper
+            .lock()
+            .await
+            .clone()
+            .ok_or_else(|| anyhow!("Offline transcription not initialized"))
+    }
+
+    /// Keep the Silero VAD model in the app data directory
+    ///
+    /// The model is only downloaded when a session first needs it.
+    pub async fn init_silero_model_path(&self, data_dir: &Path) {
+        *self.silero_model_path.lock().await = Some(SileroVad::model_path(data_dir));
+    }
+
+    /// Path of the Silero VAD model, downloading it on first use
+    pub async fn silero_model(&self) -> Result<PathBuf> {
+        let path = self
+            .silero_model_path
+            .lock()
+            .await
+            .clone()
+            .ok_or_else(|| anyhow!("Silero VAD model path not initialized"))?;
+        SileroVad::ensure_model(&path).await?;
+        Ok(path)
+    }
+
+    /// Record the start of a session in the history
+    ///
+    /// # Returns
+    /// The history session id, `None` if history is disabled or unavailable
+    pub async fn start_history_session(&self, options: &SessionOptions) -> Option<i64> {
+        if !self.settings.lock().await.history.enabled {
+            return None;
+        }
+
+        let history = self.history.lock().await;
+        match history
+            .as_ref()?
+            .start_session(&options.model_id, options.language())
+        {
+            Ok(session_id) => Some(session_id),
+            Err(e) => {
+                error!("Failed to record history session: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Add a committed segment to a history session
+    pub async fn record_history_segment(&self, session_id: i64, segment: NewSegment) {
+        if let Some(history) = self.history.lock().await.as_ref() {
+            if let Err(e) = history.add_segment(session_id, &segment) {
+                error!("Failed to record transcript in history: {}", e);
+            }
+        }
+    }
+
+    /// Record the end of a history session
+    pub async fn end_history_session(&self, session_id: i64) {
+        if let Some(history) = self.history.lock().await.as_ref() {
+            if let Err(e) = history.end_session(session_id) {
+                error!("Failed to end history session: {}", e);
+            }
+        }
+        self.apply_history_retention().await;
+    }
+
+    /// Delete history entries older than the configured retention
+    pub async fn apply_history_retention(&self) {
+        let Some(days) = self.settings.lock().await.history.retention_days else {
+            return;
+        };
+
+        if let Some(history) = self.history.lock().await.as_ref() {
+            let cutoff = Utc::now() - Duration::days(i64::from(days));
+            match history.prune(cutoff) {
+                Ok(0) => {}
+                Ok(deleted) => info!(
+                    "Removed {} history entries older than {} days",
+                    deleted, days
+                ),
+                Err(e) => error!("Failed to apply history retention: {}", e),
+            }
+        }
+    }
 }
 
 impl Default for AppState {
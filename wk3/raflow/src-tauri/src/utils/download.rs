@@ -0,0 +1,122 @@
+use anyhow::{anyhow, Result};
+use std::ffi::OsString;
+use std::path::Path;
+use tokio::io::AsyncWriteExt;
+
+/// Download a file over HTTP
+///
+/// The file is written next to `dest` first and only renamed once it has
+/// the size the server announced, so an interrupted download is never
+/// mistaken for a usable file. The partial file is removed when the
+/// download fails.
+///
+/// # Arguments
+/// * `on_progress` - Called after every received chunk with the bytes
+///   downloaded so far and the total size, if the server reported it
+pub async fn download_file<F>(url: &str, dest: &Path, on_progress: F) -> Result<()>
+where
+    F: FnMut(u64, Option<u64>),
+{
+    let mut partial = dest.as_os_str().to_owned();
+    partial.push(".part");
+
+    match fetch(url, &partial, on_progress).await {
+        Ok(()) => {
+            tokio::fs::rename(&partial, dest).await?;
+            Ok(())
+        }
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&partial).await;
+            Err(e)
+        }
+    }
+}
+
+/// Download into `partial` and check that it is complete
+async fn fetch<F>(url: &str, partial: &OsString, mut on_progress: F) -> Result<()>
+where
+    F: FnMut(u64, Option<u64>),
+{
+    let mut response = reqwest::get(url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| anyhow!("Failed to download {}: {}", url, e))?;
+
+    let total = response.content_length();
+    let mut out = tokio::fs::File::create(partial).await?;
+    let mut downloaded = 0;
+
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| anyhow!("Download of {} interrupted: {}", url, e))?
+    {
+        out.write_all(&chunk).await?;
+        downloaded += chunk.len() as u64;
+        on_progress(downloaded, total);
+    }
+    out.flush().await?;
+    drop(out);
+
+    if let Some(total) = total.filter(|&total| total != downloaded) {
+        anyhow::bail!(
+            "Download of {} ended after {} of {} bytes",
+            url,
+            downloaded,
+            total
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    /// Serve one response with `body`, claiming `length` bytes
+    async fn serve_once(body: &'static [u8], length: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).await;
+            let header = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", length);
+            let _ = stream.write_all(header.as_bytes()).await;
+            let _ = stream.write_all(body).await;
+        });
+        format!("http://{}/model.bin", addr)
+    }
+
+    #[tokio::test]
+    async fn test_download_writes_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("model.bin");
+
+        let url = serve_once(b"hello world", 11).await;
+        let mut progress = Vec::new();
+        download_file(&url, &dest, |downloaded, total| {
+            progress.push((downloaded, total))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(std::fs::read(&dest).unwrap(), b"hello world");
+        assert_eq!(progress.last(), Some(&(11, Some(11))));
+    }
+
+    #[tokio::test]
+    async fn test_truncated_download_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("model.bin");
+
+        // The connection closes before the announced size
+        let url = serve_once(b"hello", 11).await;
+        assert!(download_file(&url, &dest, |_, _| {}).await.is_err());
+
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
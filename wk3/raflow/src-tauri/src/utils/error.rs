@@ -57,9 +57,6 @@ pub enum RAFlowError {
     Internal(String),
 }
 
-/// Result type alias for RAFlow operations
-pub type Result<T> = std::result::Result<T, RAFlowError>;
-
 /// Error context for better error reporting
 #[derive(Debug, Clone)]
 pub struct ErrorContext {
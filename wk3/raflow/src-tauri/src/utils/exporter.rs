@@ -0,0 +1,428 @@
+use anyhow::Result;
+use std::fmt::{Display, Write as _};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use super::metrics::{Metrics, LATENCY_BUCKETS_MS};
+
+/// Content type of the OpenMetrics text format
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Largest request accepted, scrapers send a few short headers
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// How long a client may take to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Serves the metrics on a localhost HTTP endpoint for Prometheus
+///
+/// `GET /metrics` returns [`render`]. Serving stops when the exporter is
+/// dropped.
+pub struct MetricsExporter {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl MetricsExporter {
+    /// Start serving on 127.0.0.1
+    ///
+    /// # Arguments
+    /// * `metrics` - Metrics to serve
+    /// * `port` - Port to listen on, 0 picks a free one
+    pub async fn start(metrics: Metrics, port: u16) -> Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port)).await?;
+        let addr = listener.local_addr()?;
+        info!("📈 Serving metrics on http://{}/metrics", addr);
+
+        let task = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(serve(stream, metrics.clone()));
+                    }
+                    Err(e) => warn!("Failed to accept metrics request: {}", e),
+                }
+            }
+        });
+
+        Ok(Self { addr, task })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for MetricsExporter {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn serve(mut stream: TcpStream, metrics: Metrics) {
+    if let Err(e) = respond(&mut stream, &metrics).await {
+        debug!("Metrics request failed: {}", e);
+    }
+}
+
+/// Answer one HTTP request and close the connection
+async fn respond(stream: &mut TcpStream, metrics: &Metrics) -> Result<()> {
+    // Only the request line matters, but the headers have to be read first
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+        if request.len() > MAX_REQUEST_BYTES {
+            anyhow::bail!("Request too large");
+        }
+        let n = tokio::time::timeout(REQUEST_TIMEOUT, stream.read(&mut buf)).await??;
+        if n == 0 {
+            anyhow::bail!("Connection closed before the request was complete");
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.split_whitespace();
+    let method = request_line.next();
+    let path = request_line.next().and_then(|p| p.split('?').next());
+
+    let (status, content_type, body) = match (method, path) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", CONTENT_TYPE, render(metrics)),
+        (Some("GET"), _) => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Not found, metrics are at /metrics\n".to_string(),
+        ),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "Method not allowed\n".to_string(),
+        ),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Render the metrics in the OpenMetrics text format
+///
+/// Series counting since the app started carry no labels, so switching
+/// providers or devices doesn't split them. The `raflow_session_*` series
+/// of the current or last session start from zero with every session and
+/// are labeled with its provider and input device.
+pub fn render(metrics: &Metrics) -> String {
+    let snapshot = metrics.snapshot();
+    let mut out = OpenMetricsWriter {
+        out: String::new(),
+        labels: String::new(),
+    };
+
+    out.gauge(
+        "raflow_uptime_seconds",
+        "Time since the app started",
+        snapshot.uptime_secs,
+    );
+    out.gauge(
+        "raflow_healthy",
+        "1 if the drop rate, latency and memory use are within limits",
+        u8::from(metrics.is_healthy()),
+    );
+
+    // Audio
+    out.counter(
+        "raflow_audio_packets_processed",
+        "Audio packets run through the pipeline",
+        snapshot.audio_packets_processed,
+    );
+    out.counter(
+        "raflow_audio_packets_dropped",
+        "Audio chunks dropped because the connection couldn't keep up",
+        snapshot.audio_packets_dropped,
+    );
+    out.gauge(
+        "raflow_audio_drop_ratio",
+        "Share of the audio that was dropped",
+        snapshot.audio_drop_rate / 100.0,
+    );
+    out.gauge(
+        "raflow_audio_processing_average_seconds",
+        "Average pipeline time per audio packet",
+        snapshot.avg_audio_processing_us as f64 / 1e6,
+    );
+    out.counter(
+        "raflow_audio_upload_pcm_bytes",
+        "Uploaded audio measured as 16-bit PCM",
+        snapshot.audio_pcm_bytes,
+    );
+    out.counter(
+        "raflow_audio_upload_wire_bytes",
+        "Bytes sent for the uploaded audio after encoding",
+        snapshot.audio_wire_bytes,
+    );
+
+    // Network
+    out.counter(
+        "raflow_ws_messages_sent",
+        "WebSocket messages sent to the provider",
+        snapshot.ws_messages_sent,
+    );
+    out.counter(
+        "raflow_ws_messages_received",
+        "WebSocket messages received from the provider",
+        snapshot.ws_messages_received,
+    );
+    out.counter(
+        "raflow_ws_sent_bytes",
+        "WebSocket bytes sent to the provider",
+        snapshot.ws_bytes_sent,
+    );
+    out.counter(
+        "raflow_ws_received_bytes",
+        "WebSocket bytes received from the provider",
+        snapshot.ws_bytes_received,
+    );
+    out.counter(
+        "raflow_ws_reconnects",
+        "Provider connections restored after a drop",
+        snapshot.ws_reconnects,
+    );
+
+    // Transcription
+    out.counter(
+        "raflow_partial_transcripts",
+        "Partial transcripts received",
+        snapshot.partial_transcripts,
+    );
+    out.counter(
+        "raflow_committed_transcripts",
+        "Committed transcripts received",
+        snapshot.committed_transcripts,
+    );
+    out.latency_histogram(
+        "raflow_transcript_latency_seconds",
+        "Time from the end of speech to its committed transcript",
+        metrics,
+    );
+
+    // System
+    out.gauge(
+        "raflow_process_resident_memory_bytes",
+        "Resident memory of the app",
+        snapshot.memory_usage_mb as u64 * 1024 * 1024,
+    );
+    out.gauge(
+        "raflow_process_cpu_usage_ratio",
+        "CPU used by the app, 1 per fully busy core",
+        snapshot.cpu_usage_percent as f64 / 100.0,
+    );
+    out.counter("raflow_errors", "Errors", snapshot.errors_total);
+    out.counter(
+        "raflow_errors_retried",
+        "Errors that were retried",
+        snapshot.errors_retried,
+    );
+    out.counter(
+        "raflow_errors_recovered",
+        "Errors recovered from",
+        snapshot.errors_recovered,
+    );
+
+    if let Some(session) = &snapshot.session {
+        out.labels = format!(
+            "provider=\"{}\",device=\"{}\"",
+            escape(&session.provider),
+            escape(&session.device)
+        );
+        out.info("raflow_session", "Provider and input device of the session");
+        out.gauge(
+            "raflow_session_duration_seconds",
+            "Length of the current or last session",
+            session.duration_secs,
+        );
+        out.counter(
+            "raflow_session_audio_packets_processed",
+            "Audio packets processed in the session",
+            session.audio_packets_processed,
+        );
+        out.counter(
+            "raflow_session_audio_packets_dropped",
+            "Audio chunks dropped in the session",
+            session.audio_packets_dropped,
+        );
+        out.counter(
+            "raflow_session_ws_reconnects",
+            "Reconnects in the session",
+            session.ws_reconnects,
+        );
+        out.counter(
+            "raflow_session_partial_transcripts",
+            "Partial transcripts in the session",
+            session.partial_transcripts,
+        );
+        out.counter(
+            "raflow_session_committed_transcripts",
+            "Committed transcripts in the session",
+            session.committed_transcripts,
+        );
+        out.counter(
+            "raflow_session_errors",
+            "Errors in the session",
+            session.errors_total,
+        );
+    }
+
+    out.out.push_str("# EOF\n");
+    out.out
+}
+
+/// Escape a label value
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Writes metric families
+struct OpenMetricsWriter {
+    out: String,
+    /// Rendered `name="value"` pairs added to every sample, may be empty
+    labels: String,
+}
+
+impl OpenMetricsWriter {
+    fn header(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.out, "# TYPE {} {}", name, kind);
+        let _ = writeln!(self.out, "# HELP {} {}", name, help);
+    }
+
+    fn sample(&mut self, name: &str, extra_labels: &str, value: impl Display) {
+        let labels = match (self.labels.is_empty(), extra_labels.is_empty()) {
+            (true, true) => String::new(),
+            (false, true) => format!("{{{}}}", self.labels),
+            (true, false) => format!("{{{}}}", extra_labels),
+            (false, false) => format!("{{{},{}}}", self.labels, extra_labels),
+        };
+        let _ = writeln!(self.out, "{}{} {}", name, labels, value);
+    }
+
+    fn counter(&mut self, name: &str, help: &str, value: u64) {
+        self.header(name, "counter", help);
+        self.sample(&format!("{}_total", name), "", value);
+    }
+
+    fn gauge(&mut self, name: &str, help: &str, value: impl Display) {
+        self.header(name, "gauge", help);
+        self.sample(name, "", value);
+    }
+
+    /// A series whose labels are the information
+    fn info(&mut self, name: &str, help: &str) {
+        self.header(name, "info", help);
+        self.sample(&format!("{}_info", name), "", 1);
+    }
+
+    fn latency_histogram(&mut self, name: &str, help: &str, metrics: &Metrics) {
+        let histogram = metrics.transcript_latency();
+        let counts = histogram.bucket_counts();
+
+        self.header(name, "histogram", help);
+        let mut cumulative = 0;
+        for (bound, count) in LATENCY_BUCKETS_MS.iter().zip(&counts) {
+            cumulative += count;
+            // Canonical float form, e.g. "1.0" rather than "1"
+            let le = format!("le=\"{:?}\"", *bound as f64 / 1000.0);
+            self.sample(&format!("{}_bucket", name), &le, cumulative);
+        }
+        let total: u64 = counts.iter().sum();
+        self.sample(&format!("{}_bucket", name), "le=\"+Inf\"", total);
+        self.sample(&format!("{}_count", name), "", total);
+        self.sample(
+            &format!("{}_sum", name),
+            "",
+            histogram.sum_ms() as f64 / 1000.0,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_openmetrics() {
+        let metrics = Metrics::new();
+        metrics.record_audio_packet_dropped();
+        metrics.start_session("elevenlabs", "USB \"Pro\" Mic");
+        metrics.record_audio_packet_dropped();
+        metrics.record_committed_transcript(Some(Duration::from_millis(120)));
+        metrics.record_committed_transcript(Some(Duration::from_millis(900)));
+
+        let text = render(&metrics);
+        assert!(text.contains("# TYPE raflow_audio_packets_dropped counter\n"));
+        assert!(text.contains("raflow_audio_packets_dropped_total 2\n"));
+
+        // Only the session series are labeled
+        let labels = r#"provider="elevenlabs",device="USB \"Pro\" Mic""#;
+        assert!(text.contains("# TYPE raflow_session info\n"));
+        assert!(text.contains(&format!("raflow_session_info{{{}}} 1\n", labels)));
+        assert!(text.contains(&format!(
+            "raflow_session_audio_packets_dropped_total{{{}}} 1\n",
+            labels
+        )));
+        assert_eq!(text.matches("provider=").count(), 8);
+
+        // Buckets are cumulative
+        assert!(text.contains("# TYPE raflow_transcript_latency_seconds histogram\n"));
+        assert!(text.contains("raflow_transcript_latency_seconds_bucket{le=\"0.1\"} 0\n"));
+        assert!(text.contains("raflow_transcript_latency_seconds_bucket{le=\"0.15\"} 1\n"));
+        assert!(text.contains("raflow_transcript_latency_seconds_bucket{le=\"1.0\"} 2\n"));
+        assert!(text.contains("raflow_transcript_latency_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("raflow_transcript_latency_seconds_sum 1.02\n"));
+        assert!(text.ends_with("# EOF\n"));
+    }
+
+    #[test]
+    fn test_render_without_session() {
+        let text = render(&Metrics::new());
+        assert!(text.contains("raflow_healthy 1\n"));
+        assert!(!text.contains("raflow_session_"));
+    }
+
+    async fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_serves_metrics_on_localhost() {
+        let metrics = Metrics::new();
+        metrics.record_ws_reconnect();
+        let exporter = MetricsExporter::start(metrics, 0).await.unwrap();
+        assert!(exporter.local_addr().ip().is_loopback());
+
+        let response = get(exporter.local_addr(), "/metrics").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains(CONTENT_TYPE));
+        assert!(response.contains("raflow_ws_reconnects_total 1\n"));
+
+        let response = get(exporter.local_addr(), "/").await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
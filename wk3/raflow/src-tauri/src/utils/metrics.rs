@@ -1,8 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use tracing::{debug, info, warn};
+use tracing::{info, warn};
 
 /// Performance metrics tracker
 #[derive(Debug, Clone)]
@@ -23,11 +23,13 @@ struct MetricsInner {
     ws_bytes_sent: AtomicU64,
     ws_bytes_received: AtomicU64,
     ws_reconnects: AtomicU64,
+    audio_pcm_bytes: AtomicU64,
+    audio_wire_bytes: AtomicU64,
 
     // Transcription metrics
     partial_transcripts: AtomicU64,
     committed_transcripts: AtomicU64,
-    transcript_latency_ms: AtomicU64,
+    transcript_latency: LatencyHistogram,
 
     // System metrics
     memory_usage_mb: AtomicUsize,
@@ -38,9 +40,23 @@ struct MetricsInner {
     errors_retried: AtomicU64,
     errors_recovered: AtomicU64,
 
+    // Session the counters are attributed to
+    session: Mutex<Option<ActiveSession>>,
+
     start_time: Instant,
 }
 
+/// The current or last dictation session
+#[derive(Debug)]
+struct ActiveSession {
+    provider: String,
+    device: String,
+    started: Instant,
+    ended: Option<Instant>,
+    /// Totals when the session started
+    baseline: SessionMetrics,
+}
+
 impl Metrics {
     pub fn new() -> Self {
         Self {
@@ -53,14 +69,17 @@ impl Metrics {
                 ws_bytes_sent: AtomicU64::new(0),
                 ws_bytes_received: AtomicU64::new(0),
                 ws_reconnects: AtomicU64::new(0),
+                audio_pcm_bytes: AtomicU64::new(0),
+                audio_wire_bytes: AtomicU64::new(0),
                 partial_transcripts: AtomicU64::new(0),
                 committed_transcripts: AtomicU64::new(0),
-                transcript_latency_ms: AtomicU64::new(0),
+                transcript_latency: LatencyHistogram::new(),
                 memory_usage_mb: AtomicUsize::new(0),
                 cpu_usage_percent: AtomicUsize::new(0),
                 errors_total: AtomicU64::new(0),
                 errors_retried: AtomicU64::new(0),
                 errors_recovered: AtomicU64::new(0),
+                session: Mutex::new(None),
                 start_time: Instant::now(),
             }),
         }
@@ -103,6 +122,20 @@ impl Metrics {
         self.inner.ws_reconnects.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Record an uploaded audio chunk
+    ///
+    /// # Arguments
+    /// * `pcm_bytes` - Size of the chunk as raw 16-bit PCM
+    /// * `wire_bytes` - Bytes actually sent for it, after encoding and framing
+    pub fn record_audio_sent(&self, pcm_bytes: usize, wire_bytes: usize) {
+        self.inner
+            .audio_pcm_bytes
+            .fetch_add(pcm_bytes as u64, Ordering::Relaxed);
+        self.inner
+            .audio_wire_bytes
+            .fetch_add(wire_bytes as u64, Ordering::Relaxed);
+    }
+
     // Transcription metrics
     pub fn record_partial_transcript(&self) {
         self.inner
@@ -110,13 +143,18 @@ impl Metrics {
             .fetch_add(1, Ordering::Relaxed);
     }
 
-    pub fn record_committed_transcript(&self, latency: Duration) {
+    /// Record a committed transcript
+    ///
+    /// # Arguments
+    /// * `latency` - Time from the end of speech to the transcript, if
+    ///   it could be matched to a commit
+    pub fn record_committed_transcript(&self, latency: Option<Duration>) {
         self.inner
             .committed_transcripts
             .fetch_add(1, Ordering::Relaxed);
-        self.inner
-            .transcript_latency_ms
-            .store(latency.as_millis() as u64, Ordering::Relaxed);
+        if let Some(latency) = latency {
+            self.inner.transcript_latency.record(latency);
+        }
     }
 
     // Error metrics
@@ -129,9 +167,7 @@ impl Metrics {
     }
 
     pub fn record_error_recovered(&self) {
-        self.inner
-            .errors_recovered
-            .fetch_add(1, Ordering::Relaxed);
+        self.inner.errors_recovered.fetch_add(1, Ordering::Relaxed);
     }
 
     // System metrics
@@ -140,7 +176,80 @@ impl Metrics {
     }
 
     pub fn update_cpu_usage(&self, percent: usize) {
-        self.inner.cpu_usage_percent.store(percent, Ordering::Relaxed);
+        self.inner
+            .cpu_usage_percent
+            .store(percent, Ordering::Relaxed);
+    }
+
+    // Session metrics
+    /// Attribute the following metrics to a new dictation session
+    ///
+    /// # Arguments
+    /// * `provider` - Name of the speech-to-text provider
+    /// * `device` - Name of the input device
+    pub fn start_session(&self, provider: &str, device: &str) {
+        *self.inner.session.lock().unwrap() = Some(ActiveSession {
+            provider: provider.to_string(),
+            device: device.to_string(),
+            started: Instant::now(),
+            ended: None,
+            baseline: self.session_totals(),
+        });
+    }
+
+    /// Record that the session moved to another input device
+    pub fn set_session_device(&self, device: &str) {
+        if let Some(session) = self.inner.session.lock().unwrap().as_mut() {
+            session.device = device.to_string();
+        }
+    }
+
+    /// Record that the session ended, its counters stay available
+    pub fn end_session(&self) {
+        if let Some(session) = self.inner.session.lock().unwrap().as_mut() {
+            session.ended.get_or_insert_with(Instant::now);
+        }
+    }
+
+    /// Counters since the app started, in the shape of a session
+    fn session_totals(&self) -> SessionMetrics {
+        SessionMetrics {
+            audio_packets_processed: self.inner.audio_packets_processed.load(Ordering::Relaxed),
+            audio_packets_dropped: self.inner.audio_packets_dropped.load(Ordering::Relaxed),
+            ws_reconnects: self.inner.ws_reconnects.load(Ordering::Relaxed),
+            partial_transcripts: self.inner.partial_transcripts.load(Ordering::Relaxed),
+            committed_transcripts: self.inner.committed_transcripts.load(Ordering::Relaxed),
+            errors_total: self.inner.errors_total.load(Ordering::Relaxed),
+            ..SessionMetrics::default()
+        }
+    }
+
+    /// Counters of the current or last session
+    fn session(&self) -> Option<SessionMetrics> {
+        let session = self.inner.session.lock().unwrap();
+        let session = session.as_ref()?;
+        let totals = self.session_totals();
+        let baseline = &session.baseline;
+        let end = session.ended.unwrap_or_else(Instant::now);
+
+        Some(SessionMetrics {
+            provider: session.provider.clone(),
+            device: session.device.clone(),
+            duration_secs: end.duration_since(session.started).as_secs(),
+            audio_packets_processed: totals.audio_packets_processed
+                - baseline.audio_packets_processed,
+            audio_packets_dropped: totals.audio_packets_dropped - baseline.audio_packets_dropped,
+            ws_reconnects: totals.ws_reconnects - baseline.ws_reconnects,
+            partial_transcripts: totals.partial_transcripts - baseline.partial_transcripts,
+            committed_transcripts: totals.committed_transcripts - baseline.committed_transcripts,
+            errors_total: totals.errors_total - baseline.errors_total,
+        })
+    }
+
+    /// Transcript latency distribution
+    #[cfg_attr(not(feature = "prometheus"), allow(dead_code))]
+    pub(crate) fn transcript_latency(&self) -> &LatencyHistogram {
+        &self.inner.transcript_latency
     }
 
     /// Get current performance snapshot
@@ -149,19 +258,27 @@ impl Metrics {
 
         let audio_packets_processed = self.inner.audio_packets_processed.load(Ordering::Relaxed);
         let audio_packets_dropped = self.inner.audio_packets_dropped.load(Ordering::Relaxed);
-        let audio_processing_time_us =
-            self.inner.audio_processing_time_us.load(Ordering::Relaxed);
+        let audio_processing_time_us = self.inner.audio_processing_time_us.load(Ordering::Relaxed);
 
         // Calculate average processing time
-        let avg_audio_processing_us = if audio_packets_processed > 0 {
-            audio_processing_time_us / audio_packets_processed
+        let avg_audio_processing_us = audio_processing_time_us
+            .checked_div(audio_packets_processed)
+            .unwrap_or(0);
+
+        // Calculate drop rate
+        let audio_packets_total = audio_packets_processed + audio_packets_dropped;
+        let audio_drop_rate = if audio_packets_total > 0 {
+            (audio_packets_dropped as f64 / audio_packets_total as f64) * 100.0
         } else {
-            0
+            0.0
         };
 
-        // Calculate drop rate
-        let audio_drop_rate = if audio_packets_processed > 0 {
-            (audio_packets_dropped as f64 / audio_packets_processed as f64) * 100.0
+        let audio_pcm_bytes = self.inner.audio_pcm_bytes.load(Ordering::Relaxed);
+        let audio_wire_bytes = self.inner.audio_wire_bytes.load(Ordering::Relaxed);
+
+        // Negative when the encoding adds overhead, e.g. base64
+        let audio_bandwidth_savings = if audio_pcm_bytes > 0 {
+            (1.0 - audio_wire_bytes as f64 / audio_pcm_bytes as f64) * 100.0
         } else {
             0.0
         };
@@ -177,14 +294,20 @@ impl Metrics {
             ws_bytes_sent: self.inner.ws_bytes_sent.load(Ordering::Relaxed),
             ws_bytes_received: self.inner.ws_bytes_received.load(Ordering::Relaxed),
             ws_reconnects: self.inner.ws_reconnects.load(Ordering::Relaxed),
+            audio_pcm_bytes,
+            audio_wire_bytes,
+            audio_bandwidth_savings,
             partial_transcripts: self.inner.partial_transcripts.load(Ordering::Relaxed),
             committed_transcripts: self.inner.committed_transcripts.load(Ordering::Relaxed),
-            transcript_latency_ms: self.inner.transcript_latency_ms.load(Ordering::Relaxed),
+            transcript_latency_p50_ms: self.inner.transcript_latency.quantile(0.50),
+            transcript_latency_p95_ms: self.inner.transcript_latency.quantile(0.95),
+            transcript_latency_p99_ms: self.inner.transcript_latency.quantile(0.99),
             memory_usage_mb: self.inner.memory_usage_mb.load(Ordering::Relaxed),
             cpu_usage_percent: self.inner.cpu_usage_percent.load(Ordering::Relaxed),
             errors_total: self.inner.errors_total.load(Ordering::Relaxed),
             errors_retried: self.inner.errors_retried.load(Ordering::Relaxed),
             errors_recovered: self.inner.errors_recovered.load(Ordering::Relaxed),
+            session: self.session(),
         }
     }
 
@@ -207,8 +330,20 @@ impl Metrics {
             metrics.ws_messages_sent, metrics.ws_messages_received, metrics.ws_reconnects
         );
         info!(
-            "Transcripts: {} partial, {} committed, {}ms latency",
-            metrics.partial_transcripts, metrics.committed_transcripts, metrics.transcript_latency_ms
+            "Audio upload: {} KB PCM sent as {} KB ({:.1}% saved)",
+            metrics.audio_pcm_bytes / 1024,
+            metrics.audio_wire_bytes / 1024,
+            metrics.audio_bandwidth_savings
+        );
+        info!(
+            "Transcripts: {} partial, {} committed",
+            metrics.partial_transcripts, metrics.committed_transcripts
+        );
+        info!(
+            "Transcript latency: p50 {}ms, p95 {}ms, p99 {}ms",
+            metrics.transcript_latency_p50_ms,
+            metrics.transcript_latency_p95_ms,
+            metrics.transcript_latency_p99_ms
         );
         info!(
             "System: {} MB memory, {}% CPU",
@@ -223,8 +358,11 @@ impl Metrics {
         if metrics.audio_drop_rate > 1.0 {
             warn!("High audio drop rate: {:.2}%", metrics.audio_drop_rate);
         }
-        if metrics.transcript_latency_ms > 500 {
-            warn!("High transcript latency: {}ms", metrics.transcript_latency_ms);
+        if metrics.transcript_latency_p95_ms > 500 {
+            warn!(
+                "High transcript latency: {}ms p95",
+                metrics.transcript_latency_p95_ms
+            );
         }
         if metrics.memory_usage_mb > 100 {
             warn!("High memory usage: {} MB", metrics.memory_usage_mb);
@@ -237,7 +375,7 @@ impl Metrics {
 
         // Define health criteria
         metrics.audio_drop_rate < 5.0
-            && metrics.transcript_latency_ms < 1000
+            && metrics.transcript_latency_p95_ms < 1000
             && metrics.memory_usage_mb < 150
     }
 }
@@ -248,6 +386,95 @@ impl Default for Metrics {
     }
 }
 
+/// Upper bounds of the latency histogram buckets in milliseconds
+pub const LATENCY_BUCKETS_MS: [u64; 14] = [
+    50, 100, 150, 200, 300, 400, 500, 750, 1000, 1500, 2000, 3000, 5000, 10000,
+];
+
+/// Latency distribution in fixed buckets
+///
+/// Recording is lock-free, percentiles are estimated by interpolating
+/// within the bucket they fall into.
+#[derive(Debug)]
+pub struct LatencyHistogram {
+    /// One count per bound, then one for latencies above all of them
+    buckets: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+    sum_ms: AtomicU64,
+    max_ms: AtomicU64,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            sum_ms: AtomicU64::new(0),
+            max_ms: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, latency: Duration) {
+        let ms = latency.as_millis() as u64;
+        let bucket = LATENCY_BUCKETS_MS.partition_point(|&bound| bound < ms);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum_ms.fetch_add(ms, Ordering::Relaxed);
+        self.max_ms.fetch_max(ms, Ordering::Relaxed);
+    }
+
+    /// Recordings per bucket, the last one above all bounds
+    #[cfg_attr(not(feature = "prometheus"), allow(dead_code))]
+    pub(crate) fn bucket_counts(&self) -> Vec<u64> {
+        self.buckets
+            .iter()
+            .map(|b| b.load(Ordering::Relaxed))
+            .collect()
+    }
+
+    /// Sum of all recorded latencies in milliseconds
+    #[cfg_attr(not(feature = "prometheus"), allow(dead_code))]
+    pub(crate) fn sum_ms(&self) -> u64 {
+        self.sum_ms.load(Ordering::Relaxed)
+    }
+
+    /// Estimate the latency below which a fraction of the recordings fall
+    ///
+    /// # Arguments
+    /// * `quantile` - Fraction between 0 and 1, e.g. 0.95 for p95
+    ///
+    /// # Returns
+    /// The latency in milliseconds, 0 if nothing was recorded
+    pub fn quantile(&self, quantile: f64) -> u64 {
+        let counts = self.bucket_counts();
+        let total: u64 = counts.iter().sum();
+        if total == 0 {
+            return 0;
+        }
+        let max_ms = self.max_ms.load(Ordering::Relaxed);
+
+        let rank = ((quantile * total as f64).ceil() as u64).clamp(1, total);
+        let mut below = 0;
+        for (i, &count) in counts.iter().enumerate() {
+            if below + count >= rank {
+                let lower = if i == 0 { 0 } else { LATENCY_BUCKETS_MS[i - 1] };
+                let upper = LATENCY_BUCKETS_MS
+                    .get(i)
+                    .copied()
+                    .unwrap_or(max_ms)
+                    .min(max_ms);
+                let fraction = (rank - below) as f64 / count as f64;
+                return lower + (upper.saturating_sub(lower) as f64 * fraction).round() as u64;
+            }
+            below += count;
+        }
+        max_ms
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Performance metrics snapshot
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerformanceMetrics {
@@ -261,14 +488,38 @@ pub struct PerformanceMetrics {
     pub ws_bytes_sent: u64,
     pub ws_bytes_received: u64,
     pub ws_reconnects: u64,
+    pub audio_pcm_bytes: u64,
+    pub audio_wire_bytes: u64,
+    /// Percent of the PCM size saved by the upload encoding
+    pub audio_bandwidth_savings: f64,
     pub partial_transcripts: u64,
     pub committed_transcripts: u64,
-    pub transcript_latency_ms: u64,
+    /// Median time from the end of speech to its committed transcript
+    pub transcript_latency_p50_ms: u64,
+    pub transcript_latency_p95_ms: u64,
+    pub transcript_latency_p99_ms: u64,
     pub memory_usage_mb: usize,
     pub cpu_usage_percent: usize,
     pub errors_total: u64,
     pub errors_retried: u64,
     pub errors_recovered: u64,
+    /// Counters of the current or last dictation session
+    pub session: Option<SessionMetrics>,
+}
+
+/// Counters of one dictation session
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionMetrics {
+    pub provider: String,
+    /// Input device, updated when the session fails over to another one
+    pub device: String,
+    pub duration_secs: u64,
+    pub audio_packets_processed: u64,
+    pub audio_packets_dropped: u64,
+    pub ws_reconnects: u64,
+    pub partial_transcripts: u64,
+    pub committed_transcripts: u64,
+    pub errors_total: u64,
 }
 
 #[cfg(test)]
@@ -289,6 +540,67 @@ mod tests {
         assert_eq!(snapshot.avg_audio_processing_us, 150);
     }
 
+    #[test]
+    fn test_audio_bandwidth() {
+        let metrics = Metrics::new();
+        assert_eq!(metrics.snapshot().audio_bandwidth_savings, 0.0);
+
+        metrics.record_audio_sent(3200, 800);
+        metrics.record_audio_sent(3200, 800);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.audio_pcm_bytes, 6400);
+        assert_eq!(snapshot.audio_wire_bytes, 1600);
+        assert_eq!(snapshot.audio_bandwidth_savings, 75.0);
+    }
+
+    #[test]
+    fn test_transcript_latency_percentiles() {
+        let metrics = Metrics::new();
+        assert_eq!(metrics.snapshot().transcript_latency_p50_ms, 0);
+
+        // 90 fast commits, 9 slow ones and an outlier
+        for _ in 0..90 {
+            metrics.record_committed_transcript(Some(Duration::from_millis(180)));
+        }
+        for _ in 0..9 {
+            metrics.record_committed_transcript(Some(Duration::from_millis(700)));
+        }
+        metrics.record_committed_transcript(Some(Duration::from_millis(4000)));
+        metrics.record_committed_transcript(None);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.committed_transcripts, 101);
+        assert!((150..=200).contains(&snapshot.transcript_latency_p50_ms));
+        assert!((500..=750).contains(&snapshot.transcript_latency_p95_ms));
+        assert!((500..=750).contains(&snapshot.transcript_latency_p99_ms));
+        assert_eq!(metrics.inner.transcript_latency.quantile(1.0), 4000);
+    }
+
+    #[test]
+    fn test_session_counters() {
+        let metrics = Metrics::new();
+        metrics.record_audio_packet_dropped();
+        assert_eq!(metrics.snapshot().session, None);
+
+        metrics.start_session("elevenlabs", "Built-in");
+        metrics.record_audio_packet_dropped();
+        metrics.record_committed_transcript(None);
+        metrics.set_session_device("USB Mic");
+        metrics.end_session();
+
+        let session = metrics.snapshot().session.unwrap();
+        assert_eq!(session.provider, "elevenlabs");
+        assert_eq!(session.device, "USB Mic");
+        assert_eq!(session.audio_packets_dropped, 1);
+        assert_eq!(session.committed_transcripts, 1);
+        assert_eq!(metrics.snapshot().audio_packets_dropped, 2);
+
+        // A new session starts from zero
+        metrics.start_session("whisper", "Built-in");
+        assert_eq!(metrics.snapshot().session.unwrap().audio_packets_dropped, 0);
+    }
+
     #[test]
     fn test_health_check() {
         let metrics = Metrics::new();
@@ -1,10 +1,18 @@
+pub mod download;
 pub mod error;
+#[cfg(feature = "prometheus")]
+pub mod exporter;
 pub mod metrics;
 pub mod permissions;
+pub mod sampler;
 
-pub use error::{ErrorContext, RAFlowError, RecoveryStrategy, Result};
+pub use download::download_file;
+pub use error::{ErrorContext, RAFlowError, RecoveryStrategy};
+#[cfg(feature = "prometheus")]
+pub use exporter::MetricsExporter;
 pub use metrics::{Metrics, PerformanceMetrics};
 pub use permissions::{
     check_accessibility_permission, check_microphone_permission, open_system_preferences,
     PermissionStatus,
 };
+pub use sampler::{ResourceSampler, DEFAULT_SAMPLE_INTERVAL};
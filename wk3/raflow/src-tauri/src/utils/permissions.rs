@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+#[cfg(target_os = "macos")]
 use tracing::{debug, error, info};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -24,7 +25,8 @@ pub fn check_microphone_permission() -> PermissionStatus {
         // We need to get it from the framework
         let media_type_audio_str = NSString::alloc(nil).init_str("soun"); // AVMediaTypeAudio
 
-        let status: isize = msg_send![av_capture_device, authorizationStatusForMediaType: media_type_audio_str];
+        let status: isize =
+            msg_send![av_capture_device, authorizationStatusForMediaType: media_type_audio_str];
 
         // AVAuthorizationStatus values:
         // 0 = NotDetermined, 1 = Restricted, 2 = Denied, 3 = Authorized
@@ -82,7 +84,8 @@ pub fn open_system_preferences(permission_type: &str) -> Result<(), String> {
         }
         "accessibility" => {
             info!("Opening accessibility permission settings");
-            let url = "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility";
+            let url =
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility";
             Command::new("open").arg(url).spawn().map_err(|e| {
                 error!("Failed to open system preferences: {}", e);
                 format!("Failed to open system preferences: {}", e)
@@ -105,14 +108,6 @@ pub fn open_system_preferences(_permission_type: &str) -> Result<(), String> {
     Err("System preferences only available on macOS".to_string())
 }
 
-/// Check if all required permissions are granted
-pub fn check_all_permissions() -> bool {
-    let microphone = check_microphone_permission();
-    let accessibility = check_accessibility_permission();
-
-    microphone == PermissionStatus::Granted && accessibility == PermissionStatus::Granted
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
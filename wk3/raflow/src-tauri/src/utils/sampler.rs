@@ -0,0 +1,337 @@
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use super::metrics::Metrics;
+
+/// How often the process is sampled by default
+pub const DEFAULT_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Resources used by this process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessUsage {
+    /// Resident set size
+    pub rss_bytes: u64,
+    /// User plus system CPU time since the process started
+    pub cpu_time: Duration,
+}
+
+impl ProcessUsage {
+    /// Read the current usage from the operating system
+    pub fn current() -> Result<Self> {
+        platform::process_usage()
+    }
+}
+
+/// Turns cumulative CPU time into a usage percentage between samples
+#[derive(Debug, Default)]
+pub struct CpuTracker {
+    last: Option<(Instant, Duration)>,
+}
+
+impl CpuTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a sample
+    ///
+    /// # Returns
+    /// CPU used since the previous sample in percent of one core, so busy
+    /// threads on several cores add up to more than 100. `None` for the
+    /// first sample.
+    pub fn update(&mut self, at: Instant, cpu_time: Duration) -> Option<f64> {
+        let previous = self.last.replace((at, cpu_time));
+        let (last_at, last_cpu) = previous?;
+        let wall = at.saturating_duration_since(last_at).as_secs_f64();
+        if wall <= 0.0 {
+            return None;
+        }
+        Some(cpu_time.saturating_sub(last_cpu).as_secs_f64() / wall * 100.0)
+    }
+}
+
+/// Samples the process's memory and CPU use on a background thread
+///
+/// Every sample updates the gauges in [`Metrics`], and the callback is told
+/// when [`Metrics::is_healthy`] changes. Sampling stops when the sampler is
+/// dropped.
+pub struct ResourceSampler {
+    stop: Arc<AtomicBool>,
+}
+
+impl ResourceSampler {
+    /// Start sampling
+    ///
+    /// # Arguments
+    /// * `metrics` - Where the memory and CPU gauges are updated
+    /// * `interval` - Time between two samples
+    /// * `on_health_change` - Called with the new health whenever it flips
+    pub fn start<F>(metrics: Metrics, interval: Duration, mut on_health_change: F) -> Result<Self>
+    where
+        F: FnMut(bool) + Send + 'static,
+    {
+        // Fail early on platforms without support
+        ProcessUsage::current()?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+
+        std::thread::Builder::new()
+            .name("resource-sampler".to_string())
+            .spawn(move || {
+                let mut cpu = CpuTracker::new();
+                // Listeners assume a healthy start, so report it if it isn't
+                let mut healthy = true;
+
+                while !stopped.load(Ordering::Relaxed) {
+                    match ProcessUsage::current() {
+                        Ok(usage) => {
+                            let rss_mb = usage.rss_bytes / (1024 * 1024);
+                            metrics.update_memory_usage(rss_mb as usize);
+                            if let Some(percent) = cpu.update(Instant::now(), usage.cpu_time) {
+                                metrics.update_cpu_usage(percent.round() as usize);
+                            }
+                        }
+                        Err(e) => debug!("Failed to sample process usage: {}", e),
+                    }
+
+                    let now_healthy = metrics.is_healthy();
+                    if now_healthy != healthy {
+                        healthy = now_healthy;
+                        if healthy {
+                            info!("💚 Performance is healthy again");
+                        } else {
+                            warn!("🩺 Performance degraded");
+                            metrics.log_metrics();
+                        }
+                        on_health_change(healthy);
+                    }
+
+                    std::thread::sleep(interval);
+                }
+            })?;
+
+        Ok(Self { stop })
+    }
+}
+
+impl Drop for ResourceSampler {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Resident pages from the contents of `/proc/self/statm`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_statm_resident(statm: &str) -> Option<u64> {
+    statm.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// User and system clock ticks from the contents of `/proc/self/stat`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_stat_cpu_ticks(stat: &str) -> Option<u64> {
+    // The command name may contain spaces, the fields after it don't
+    let (_, fields) = stat.rsplit_once(')')?;
+    let mut fields = fields.split_whitespace().skip(11);
+    let utime: u64 = fields.next()?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    Some(utime + stime)
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{parse_stat_cpu_ticks, parse_statm_resident, ProcessUsage};
+    use anyhow::{anyhow, Result};
+    use std::time::Duration;
+
+    pub fn process_usage() -> Result<ProcessUsage> {
+        let statm = std::fs::read_to_string("/proc/self/statm")?;
+        let stat = std::fs::read_to_string("/proc/self/stat")?;
+        let pages =
+            parse_statm_resident(&statm).ok_or_else(|| anyhow!("Malformed /proc/self/statm"))?;
+        let ticks =
+            parse_stat_cpu_ticks(&stat).ok_or_else(|| anyhow!("Malformed /proc/self/stat"))?;
+
+        // SAFETY: sysconf only reads system configuration
+        let (page_size, ticks_per_sec) = unsafe {
+            (
+                libc::sysconf(libc::_SC_PAGESIZE),
+                libc::sysconf(libc::_SC_CLK_TCK),
+            )
+        };
+        if page_size <= 0 || ticks_per_sec <= 0 {
+            anyhow::bail!("Failed to read the page size or clock rate");
+        }
+
+        Ok(ProcessUsage {
+            rss_bytes: pages * page_size as u64,
+            cpu_time: Duration::from_secs_f64(ticks as f64 / ticks_per_sec as f64),
+        })
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::ProcessUsage;
+    use anyhow::Result;
+    use std::time::Duration;
+
+    pub fn process_usage() -> Result<ProcessUsage> {
+        // SAFETY: both calls only write to the zeroed structs passed in
+        unsafe {
+            let mut info: libc::proc_taskinfo = std::mem::zeroed();
+            let size = std::mem::size_of::<libc::proc_taskinfo>() as libc::c_int;
+            let written = libc::proc_pidinfo(
+                libc::getpid(),
+                libc::PROC_PIDTASKINFO,
+                0,
+                &mut info as *mut _ as *mut libc::c_void,
+                size,
+            );
+            if written != size {
+                anyhow::bail!("proc_pidinfo failed: {}", std::io::Error::last_os_error());
+            }
+
+            // Task info counts CPU time in Mach ticks, rusage in microseconds
+            let mut usage: libc::rusage = std::mem::zeroed();
+            if libc::getrusage(libc::RUSAGE_SELF, &mut usage) != 0 {
+                anyhow::bail!("getrusage failed: {}", std::io::Error::last_os_error());
+            }
+            let micros = |t: libc::timeval| t.tv_sec as u64 * 1_000_000 + t.tv_usec as u64;
+
+            Ok(ProcessUsage {
+                rss_bytes: info.pti_resident_size,
+                cpu_time: Duration::from_micros(micros(usage.ru_utime) + micros(usage.ru_stime)),
+            })
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::ProcessUsage;
+    use anyhow::Result;
+    use std::time::Duration;
+    use windows_sys::Win32::Foundation::FILETIME;
+    use windows_sys::Win32::System::ProcessStatus::{
+        GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS,
+    };
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, GetProcessTimes};
+
+    pub fn process_usage() -> Result<ProcessUsage> {
+        // SAFETY: the calls only write to the zeroed structs passed in, the
+        // pseudo handle of the current process needs no closing
+        unsafe {
+            let process = GetCurrentProcess();
+
+            let mut counters: PROCESS_MEMORY_COUNTERS = std::mem::zeroed();
+            counters.cb = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+            if GetProcessMemoryInfo(process, &mut counters, counters.cb) == 0 {
+                anyhow::bail!(
+                    "GetProcessMemoryInfo failed: {}",
+                    std::io::Error::last_os_error()
+                );
+            }
+
+            let mut creation: FILETIME = std::mem::zeroed();
+            let mut exit: FILETIME = std::mem::zeroed();
+            let mut kernel: FILETIME = std::mem::zeroed();
+            let mut user: FILETIME = std::mem::zeroed();
+            if GetProcessTimes(process, &mut creation, &mut exit, &mut kernel, &mut user) == 0 {
+                anyhow::bail!(
+                    "GetProcessTimes failed: {}",
+                    std::io::Error::last_os_error()
+                );
+            }
+            // In units of 100ns
+            let ticks = |t: FILETIME| ((t.dwHighDateTime as u64) << 32) | t.dwLowDateTime as u64;
+
+            Ok(ProcessUsage {
+                rss_bytes: counters.WorkingSetSize as u64,
+                cpu_time: Duration::from_nanos((ticks(kernel) + ticks(user)) * 100),
+            })
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use super::ProcessUsage;
+    use anyhow::Result;
+
+    pub fn process_usage() -> Result<ProcessUsage> {
+        anyhow::bail!("Process sampling is not supported on this platform")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_files() {
+        assert_eq!(
+            parse_statm_resident("10349 2631 1580 2 0 1094 0\n"),
+            Some(2631)
+        );
+        assert_eq!(parse_statm_resident(""), None);
+
+        let stat = "4242 (tokio (rt) 1) S 1 4242 4242 0 -1 4194560 2300 0 0 0 \
+                    175 31 0 0 20 0 14 0 5000 380000000 2631 18446744073709551615";
+        assert_eq!(parse_stat_cpu_ticks(stat), Some(206));
+        assert_eq!(parse_stat_cpu_ticks("4242 (raflow"), None);
+    }
+
+    #[test]
+    fn test_cpu_percentage() {
+        let mut cpu = CpuTracker::new();
+        let start = Instant::now();
+        assert_eq!(cpu.update(start, Duration::from_secs(3)), None);
+
+        let percent = cpu
+            .update(start + Duration::from_secs(2), Duration::from_millis(4000))
+            .unwrap();
+        assert!((percent - 50.0).abs() < 1e-6);
+
+        // Two busy cores
+        let percent = cpu
+            .update(start + Duration::from_secs(3), Duration::from_millis(6000))
+            .unwrap();
+        assert!((percent - 200.0).abs() < 1e-6);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos", windows))]
+    #[test]
+    fn test_current_process_usage() {
+        let usage = ProcessUsage::current().unwrap();
+        assert!(usage.rss_bytes > 0);
+
+        // Burn some CPU so the time visibly advances
+        let mut x = 0u64;
+        for i in 0..50_000_000u64 {
+            x = std::hint::black_box(x.wrapping_add(i));
+        }
+        assert!(ProcessUsage::current().unwrap().cpu_time > usage.cpu_time);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos", windows))]
+    #[test]
+    fn test_sampler_reports_health_changes() {
+        let metrics = Metrics::new();
+        let (health_tx, health_rx) = std::sync::mpsc::channel();
+        let _sampler =
+            ResourceSampler::start(metrics.clone(), Duration::from_millis(10), move |healthy| {
+                let _ = health_tx.send(healthy);
+            })
+            .unwrap();
+
+        for _ in 0..100 {
+            metrics.record_audio_packet_dropped();
+        }
+        assert_eq!(health_rx.recv_timeout(Duration::from_secs(2)), Ok(false));
+        assert!(metrics.snapshot().memory_usage_mb > 0);
+    }
+}
@@ -0,0 +1,269 @@
+use anyhow::{anyhow, Result};
+use candle_core::{Device, IndexOp, Tensor, D};
+use candle_nn::VarBuilder;
+use candle_transformers::models::whisper::{
+    self as whisper_model, audio, model::Whisper, Config, N_FRAMES,
+};
+use std::path::Path;
+use tokenizers::Tokenizer;
+use tracing::{debug, info};
+
+use super::mel::mel_filters;
+
+/// Result of transcribing one segment
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transcription {
+    pub text: String,
+    /// Spoken language as an ISO 639-3 code, if known
+    pub language_code: Option<String>,
+    /// Geometric mean of the token probabilities
+    pub confidence: f32,
+}
+
+/// Speech-to-text model running locally
+pub trait Transcriber: Send {
+    /// Transcribe up to 30 seconds of 16kHz mono audio
+    ///
+    /// # Arguments
+    /// * `language` - ISO 639-3 (or 639-1) code, `None` to detect it
+    fn transcribe(&mut self, pcm: &[f32], language: Option<&str>) -> Result<Transcription>;
+}
+
+/// ISO 639-3 codes used by the app and their Whisper equivalents
+const LANGUAGES: [(&str, &str); 23] = [
+    ("zho", "zh"),
+    ("cmn", "zh"),
+    ("yue", "yue"),
+    ("eng", "en"),
+    ("jpn", "ja"),
+    ("kor", "ko"),
+    ("spa", "es"),
+    ("fra", "fr"),
+    ("deu", "de"),
+    ("ita", "it"),
+    ("por", "pt"),
+    ("rus", "ru"),
+    ("ara", "ar"),
+    ("hin", "hi"),
+    ("vie", "vi"),
+    ("tha", "th"),
+    ("ind", "id"),
+    ("msa", "ms"),
+    ("tur", "tr"),
+    ("pol", "pl"),
+    ("nld", "nl"),
+    ("swe", "sv"),
+    ("ukr", "uk"),
+];
+
+/// Whisper language code for an app language code
+///
+/// Two-letter codes are passed through unchanged.
+pub fn whisper_language(code: &str) -> Option<&str> {
+    let code = code.trim();
+    LANGUAGES
+        .iter()
+        .find(|(iso, _)| iso.eq_ignore_ascii_case(code))
+        .map(|(_, whisper)| *whisper)
+        .or_else(|| (code.len() == 2).then_some(code))
+}
+
+/// ISO 639-3 code for a Whisper language code
+pub fn iso_language(whisper: &str) -> Option<&'static str> {
+    LANGUAGES
+        .iter()
+        .find(|(_, code)| *code == whisper)
+        .map(|(iso, _)| *iso)
+}
+
+/// Whisper inference on the CPU with greedy decoding
+pub struct WhisperEngine {
+    model: Whisper,
+    tokenizer: Tokenizer,
+    mel_filters: Vec<f32>,
+    device: Device,
+    /// Added to the logits, `-inf` for tokens that must not be sampled
+    suppress: Tensor,
+    /// Language token ids and their Whisper codes
+    languages: Vec<(u32, String)>,
+    sot: u32,
+    transcribe: u32,
+    eot: u32,
+    no_timestamps: u32,
+}
+
+impl WhisperEngine {
+    /// Load a model downloaded by [`super::ModelManager`]
+    ///
+    /// # Arguments
+    /// * `dir` - Directory with `config.json`, `tokenizer.json` and `model.safetensors`
+    pub fn load(dir: &Path) -> Result<Self> {
+        let device = Device::Cpu;
+        let config: Config =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("config.json"))?)?;
+        let tokenizer = Tokenizer::from_file(dir.join("tokenizer.json"))
+            .map_err(|e| anyhow!("Failed to load tokenizer: {}", e))?;
+
+        let weights = std::fs::read(dir.join("model.safetensors"))?;
+        let vb = VarBuilder::from_buffered_safetensors(weights, whisper_model::DTYPE, &device)?;
+        let model = Whisper::load(&vb, config.clone())?;
+
+        let token = |name: &str| {
+            tokenizer
+                .token_to_id(name)
+                .ok_or_else(|| anyhow!("Token {} missing from tokenizer", name))
+        };
+        let sot = token(whisper_model::SOT_TOKEN)?;
+        let transcribe = token(whisper_model::TRANSCRIBE_TOKEN)?;
+        let eot = token(whisper_model::EOT_TOKEN)?;
+        let no_timestamps = token(whisper_model::NO_TIMESTAMPS_TOKEN)?;
+
+        // English-only models have a smaller vocabulary without language tokens
+        let mut languages: Vec<(u32, String)> = if config.vocab_size >= 51865 {
+            tokenizer
+                .get_vocab(true)
+                .into_iter()
+                .filter_map(|(token, id)| {
+                    let code = token.strip_prefix("<|")?.strip_suffix("|>")?;
+                    let is_language = (2..=3).contains(&code.len())
+                        && code.chars().all(|c| c.is_ascii_lowercase());
+                    is_language.then(|| (id, code.to_string()))
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        languages.sort_unstable();
+
+        // Timestamp tokens follow `<|notimestamps|>` in the vocabulary
+        let suppress: Vec<f32> = (0..config.vocab_size as u32)
+            .map(|id| {
+                if config.suppress_tokens.contains(&id) || id >= no_timestamps {
+                    f32::NEG_INFINITY
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let suppress = Tensor::new(suppress.as_slice(), &device)?;
+
+        info!(
+            "✅ Whisper model loaded from {} ({} languages)",
+            dir.display(),
+            languages.len()
+        );
+
+        Ok(Self {
+            mel_filters: mel_filters(config.num_mel_bins),
+            model,
+            tokenizer,
+            device,
+            suppress,
+            languages,
+            sot,
+            transcribe,
+            eot,
+            no_timestamps,
+        })
+    }
+
+    fn encode(&mut self, pcm: &[f32]) -> Result<Tensor> {
+        let n_mels = self.model.config.num_mel_bins;
+        let mel = audio::pcm_to_mel(&self.model.config, pcm, &self.mel_filters);
+        let frames = mel.len() / n_mels;
+        // The mel is padded to at least a full window
+        let mel = Tensor::from_vec(mel, (1, n_mels, frames), &self.device)?.narrow(
+            2,
+            0,
+            N_FRAMES.min(frames),
+        )?;
+        Ok(self.model.encoder.forward(&mel, true)?)
+    }
+
+    /// Pick the most likely language token
+    fn detect_language(&mut self, features: &Tensor) -> Result<Option<(u32, String)>> {
+        if self.languages.is_empty() {
+            return Ok(None);
+        }
+
+        let tokens = Tensor::new(&[[self.sot]], &self.device)?;
+        let ys = self.model.decoder.forward(&tokens, features, true)?;
+        let logits = self.model.decoder.final_linear(&ys.i(..1)?)?.i(0)?.i(0)?;
+        let ids: Vec<u32> = self.languages.iter().map(|(id, _)| *id).collect();
+        let logits = logits.index_select(&Tensor::new(ids.as_slice(), &self.device)?, 0)?;
+        let best = logits.argmax(D::Minus1)?.to_scalar::<u32>()? as usize;
+
+        Ok(self.languages.get(best).cloned())
+    }
+
+    fn language_token(&self, language: &str) -> Option<(u32, String)> {
+        let code = whisper_language(language)?;
+        self.languages.iter().find(|(_, c)| c == code).cloned()
+    }
+}
+
+impl Transcriber for WhisperEngine {
+    fn transcribe(&mut self, pcm: &[f32], language: Option<&str>) -> Result<Transcription> {
+        let features = self.encode(pcm)?;
+
+        let language = match language.and_then(|code| self.language_token(code)) {
+            Some(language) => Some(language),
+            None => self.detect_language(&features)?,
+        };
+
+        let mut tokens = vec![self.sot];
+        if let Some((token, _)) = &language {
+            tokens.push(*token);
+            tokens.push(self.transcribe);
+        }
+        tokens.push(self.no_timestamps);
+        let prompt_len = tokens.len();
+
+        let max_len = self.model.config.max_target_positions / 2;
+        let mut sum_logprob = 0.0f64;
+        for i in 0..max_len {
+            let input = Tensor::new(tokens.as_slice(), &self.device)?.unsqueeze(0)?;
+            let ys = self.model.decoder.forward(&input, &features, i == 0)?;
+            let (_, seq_len, _) = ys.dims3()?;
+            let logits = self
+                .model
+                .decoder
+                .final_linear(&ys.i((..1, seq_len - 1..))?)?
+                .i(0)?
+                .i(0)?
+                .broadcast_add(&self.suppress)?;
+
+            let next = logits.argmax(D::Minus1)?.to_scalar::<u32>()?;
+            if next == self.eot {
+                break;
+            }
+            let logprobs = candle_nn::ops::log_softmax(&logits, D::Minus1)?;
+            sum_logprob += logprobs.i(next as usize)?.to_scalar::<f32>()? as f64;
+            tokens.push(next);
+        }
+
+        let generated = &tokens[prompt_len..];
+        let text = self
+            .tokenizer
+            .decode(generated, true)
+            .map_err(|e| anyhow!("Failed to decode tokens: {}", e))?
+            .trim()
+            .to_string();
+        let confidence = if generated.is_empty() {
+            0.0
+        } else {
+            (sum_logprob / generated.len() as f64).exp() as f32
+        };
+        debug!("Whisper decoded {} tokens", generated.len());
+
+        Ok(Transcription {
+            text,
+            language_code: match language {
+                Some((_, code)) => iso_language(&code).map(str::to_string),
+                // Only English-only models have no language tokens
+                None => Some("eng".to_string()),
+            },
+            confidence,
+        })
+    }
+}
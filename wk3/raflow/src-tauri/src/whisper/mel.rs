@@ -0,0 +1,63 @@
+use candle_transformers::models::whisper::{N_FFT, SAMPLE_RATE};
+
+/// Number of frequency bins of one FFT frame
+pub const N_FREQS: usize = N_FFT / 2 + 1;
+
+/// Mel filterbank used by Whisper
+///
+/// Same as `librosa.filters.mel(sr=16000, n_fft=400, n_mels=n_mels)`,
+/// i.e. the Slaney mel scale with Slaney area normalization, which is
+/// what the reference implementation ships as `mel_filters.npz`.
+///
+/// # Returns
+/// `n_mels` rows of [`N_FREQS`] weights, row-major
+pub fn mel_filters(n_mels: usize) -> Vec<f32> {
+    let nyquist = SAMPLE_RATE as f64 / 2.0;
+    let fft_freqs: Vec<f64> = (0..N_FREQS)
+        .map(|i| i as f64 * nyquist / (N_FREQS - 1) as f64)
+        .collect();
+
+    // Band edges, evenly spaced on the mel scale
+    let max_mel = hz_to_mel(nyquist);
+    let mel_freqs: Vec<f64> = (0..n_mels + 2)
+        .map(|i| mel_to_hz(i as f64 * max_mel / (n_mels + 1) as f64))
+        .collect();
+
+    let mut filters = vec![0.0f32; n_mels * N_FREQS];
+    for m in 0..n_mels {
+        let (left, center, right) = (mel_freqs[m], mel_freqs[m + 1], mel_freqs[m + 2]);
+        let norm = 2.0 / (right - left);
+        for (k, &freq) in fft_freqs.iter().enumerate() {
+            let lower = (freq - left) / (center - left);
+            let upper = (right - freq) / (right - center);
+            let weight = lower.min(upper).max(0.0);
+            filters[m * N_FREQS + k] = (weight * norm) as f32;
+        }
+    }
+    filters
+}
+
+// Slaney scale: linear below 1 kHz, logarithmic above
+const F_SP: f64 = 200.0 / 3.0;
+const MIN_LOG_HZ: f64 = 1000.0;
+const MIN_LOG_MEL: f64 = MIN_LOG_HZ / F_SP;
+
+fn log_step() -> f64 {
+    6.4f64.ln() / 27.0
+}
+
+fn hz_to_mel(hz: f64) -> f64 {
+    if hz < MIN_LOG_HZ {
+        hz / F_SP
+    } else {
+        MIN_LOG_MEL + (hz / MIN_LOG_HZ).ln() / log_step()
+    }
+}
+
+fn mel_to_hz(mel: f64) -> f64 {
+    if mel < MIN_LOG_MEL {
+        mel * F_SP
+    } else {
+        MIN_LOG_HZ * ((mel - MIN_LOG_MEL) * log_step()).exp()
+    }
+}
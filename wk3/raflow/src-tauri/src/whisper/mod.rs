@@ -0,0 +1,11 @@
+pub mod engine;
+pub mod mel;
+pub mod models;
+pub mod provider;
+
+#[cfg(test)]
+mod tests;
+
+pub use engine::{Transcriber, Transcription, WhisperEngine};
+pub use models::{DownloadProgress, ModelManager, ModelStatus, WhisperModel};
+pub use provider::WhisperProvider;
@@ -0,0 +1,177 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::utils::download_file;
+
+/// Directory of the downloaded models inside the app data directory
+pub const MODELS_DIR: &str = "models/whisper";
+
+/// Files a model needs, all taken from its Hugging Face repository
+pub const MODEL_FILES: [&str; 3] = ["config.json", "tokenizer.json", "model.safetensors"];
+
+/// Whisper models that can be used for offline transcription
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhisperModel {
+    Tiny,
+    TinyEn,
+    #[default]
+    Base,
+    BaseEn,
+    Small,
+}
+
+impl WhisperModel {
+    pub const ALL: [WhisperModel; 5] = [
+        WhisperModel::Tiny,
+        WhisperModel::TinyEn,
+        WhisperModel::Base,
+        WhisperModel::BaseEn,
+        WhisperModel::Small,
+    ];
+
+    /// Model id, also used as the directory name
+    pub fn id(&self) -> &'static str {
+        match self {
+            WhisperModel::Tiny => "whisper-tiny",
+            WhisperModel::TinyEn => "whisper-tiny.en",
+            WhisperModel::Base => "whisper-base",
+            WhisperModel::BaseEn => "whisper-base.en",
+            WhisperModel::Small => "whisper-small",
+        }
+    }
+
+    /// Hugging Face repository the model is downloaded from
+    pub fn repo(&self) -> String {
+        format!("openai/{}", self.id())
+    }
+
+    /// Whether the model only understands English
+    pub fn is_english_only(&self) -> bool {
+        matches!(self, WhisperModel::TinyEn | WhisperModel::BaseEn)
+    }
+
+    /// Approximate download size in megabytes
+    pub fn size_mb(&self) -> u32 {
+        match self {
+            WhisperModel::Tiny | WhisperModel::TinyEn => 151,
+            WhisperModel::Base | WhisperModel::BaseEn => 290,
+            WhisperModel::Small => 967,
+        }
+    }
+
+    fn file_url(&self, file: &str) -> String {
+        format!(
+            "https://huggingface.co/{}/resolve/main/{}",
+            self.repo(),
+            file
+        )
+    }
+}
+
+/// A model and whether it's available locally
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelStatus {
+    pub model: WhisperModel,
+    pub id: &'static str,
+    pub size_mb: u32,
+    pub english_only: bool,
+    pub downloaded: bool,
+}
+
+/// Progress of a model download
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DownloadProgress {
+    pub model: WhisperModel,
+    pub file: &'static str,
+    /// Bytes of the current file downloaded so far
+    pub downloaded: u64,
+    /// Size of the current file, if the server reported it
+    pub total: Option<u64>,
+}
+
+/// Downloads and keeps track of Whisper models in the app data directory
+#[derive(Debug, Clone)]
+pub struct ModelManager {
+    dir: PathBuf,
+}
+
+impl ModelManager {
+    /// # Arguments
+    /// * `data_dir` - App data directory, models go into [`MODELS_DIR`]
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            dir: data_dir.join(MODELS_DIR),
+        }
+    }
+
+    /// Directory holding the files of a model
+    pub fn model_dir(&self, model: WhisperModel) -> PathBuf {
+        self.dir.join(model.id())
+    }
+
+    /// Whether all files of the model are present
+    pub fn is_downloaded(&self, model: WhisperModel) -> bool {
+        let dir = self.model_dir(model);
+        MODEL_FILES.iter().all(|file| dir.join(file).is_file())
+    }
+
+    /// All known models with their local status
+    pub fn list(&self) -> Vec<ModelStatus> {
+        WhisperModel::ALL
+            .iter()
+            .map(|&model| ModelStatus {
+                model,
+                id: model.id(),
+                size_mb: model.size_mb(),
+                english_only: model.is_english_only(),
+                downloaded: self.is_downloaded(model),
+            })
+            .collect()
+    }
+
+    /// Download the missing files of a model
+    ///
+    /// # Arguments
+    /// * `on_progress` - Called after every received chunk
+    pub async fn download<F>(&self, model: WhisperModel, mut on_progress: F) -> Result<()>
+    where
+        F: FnMut(DownloadProgress),
+    {
+        let dir = self.model_dir(model);
+        tokio::fs::create_dir_all(&dir).await?;
+
+        for file in MODEL_FILES {
+            let path = dir.join(file);
+            if path.is_file() {
+                continue;
+            }
+
+            info!("⬇️ Downloading {} of {}", file, model.id());
+            download_file(&model.file_url(file), &path, |downloaded, total| {
+                on_progress(DownloadProgress {
+                    model,
+                    file,
+                    downloaded,
+                    total,
+                })
+            })
+            .await?;
+        }
+
+        info!("✅ Model {} downloaded", model.id());
+        Ok(())
+    }
+
+    /// Remove a downloaded model
+    pub async fn delete(&self, model: WhisperModel) -> Result<()> {
+        let dir = self.model_dir(model);
+        if dir.exists() {
+            tokio::fs::remove_dir_all(&dir).await?;
+            info!("🗑️ Model {} deleted", model.id());
+        }
+        Ok(())
+    }
+}
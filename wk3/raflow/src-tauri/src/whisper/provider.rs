@@ -0,0 +1,282 @@
+use anyhow::{anyhow, Result};
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use super::engine::{Transcriber, Transcription, WhisperEngine};
+use super::models::{ModelManager, WhisperModel};
+use crate::network::options::SessionOptions;
+use crate::network::protocol::ServerMessage;
+use crate::network::provider::{
+    AudioSink, ProviderConnection, SpeechToTextProvider, TranscriptEvent,
+};
+use candle_transformers::models::whisper::{N_SAMPLES, SAMPLE_RATE};
+
+/// New audio needed before the pending segment is transcribed again
+pub const PARTIAL_INTERVAL_SAMPLES: usize = SAMPLE_RATE;
+
+/// Segments shorter than this are not transcribed (300ms)
+pub const MIN_SEGMENT_SAMPLES: usize = SAMPLE_RATE * 3 / 10;
+
+/// Segments quieter than this are silence, e.g. keep-alive chunks
+const SILENCE_PEAK: f32 = 1e-3;
+
+type SharedTranscriber = Arc<Mutex<Box<dyn Transcriber>>>;
+
+/// Offline provider running Whisper on the CPU
+///
+/// Consumes the same 16kHz mono chunks as the streaming providers. The
+/// pending segment is transcribed again every second for partial results
+/// and once more when it's committed or reaches Whisper's 30 second window.
+pub struct WhisperProvider {
+    models: ModelManager,
+    model: Mutex<WhisperModel>,
+    // Loaded lazily and kept across sessions
+    engine: tokio::sync::Mutex<Option<(WhisperModel, SharedTranscriber)>>,
+}
+
+impl WhisperProvider {
+    pub fn new(models: ModelManager) -> Self {
+        Self {
+            models,
+            model: Mutex::new(WhisperModel::default()),
+            engine: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Use an already loaded transcriber for `model`
+    #[cfg(test)]
+    pub fn with_transcriber(
+        models: ModelManager,
+        model: WhisperModel,
+        transcriber: Box<dyn Transcriber>,
+    ) -> Self {
+        Self {
+            models,
+            model: Mutex::new(model),
+            engine: tokio::sync::Mutex::new(Some((model, Arc::new(Mutex::new(transcriber))))),
+        }
+    }
+
+    pub fn models(&self) -> &ModelManager {
+        &self.models
+    }
+
+    pub fn model(&self) -> WhisperModel {
+        *self.model.lock().unwrap()
+    }
+
+    /// Model used by the next session, loaded on connect
+    pub fn select_model(&self, model: WhisperModel) {
+        *self.model.lock().unwrap() = model;
+    }
+
+    /// Load the selected model, or reuse the loaded one
+    async fn transcriber(&self) -> Result<SharedTranscriber> {
+        let model = self.model();
+        let mut engine = self.engine.lock().await;
+        if let Some((loaded, transcriber)) = engine.as_ref() {
+            if *loaded == model {
+                return Ok(transcriber.clone());
+            }
+        }
+
+        if !self.models.is_downloaded(model) {
+            return Err(anyhow!(
+                "Whisper model {} is not downloaded yet, download it in the settings first",
+                model.id()
+            ));
+        }
+
+        info!("Loading Whisper model {}", model.id());
+        let dir = self.models.model_dir(model);
+        let loaded = tokio::task::spawn_blocking(move || WhisperEngine::load(&dir)).await??;
+        let transcriber: SharedTranscriber = Arc::new(Mutex::new(Box::new(loaded)));
+        *engine = Some((model, transcriber.clone()));
+        Ok(transcriber)
+    }
+}
+
+impl SpeechToTextProvider for WhisperProvider {
+    fn name(&self) -> &str {
+        "whisper"
+    }
+
+    fn connect<'a>(
+        &'a self,
+        options: &'a SessionOptions,
+    ) -> BoxFuture<'a, Result<ProviderConnection>> {
+        async move {
+            let transcriber = self.transcriber().await?;
+
+            let (event_tx, event_rx) = mpsc::channel(100);
+            let started = ServerMessage::SessionStarted {
+                session_id: format!("local-{}", chrono::Utc::now().timestamp_millis()),
+                model_id: self.model().id().to_string(),
+            };
+            if let Some(event) = started.into_event() {
+                event_tx.send(event).await?;
+            }
+
+            let (audio_tx, audio_rx) = mpsc::unbounded_channel();
+            let worker = Worker {
+                transcriber,
+                language: options.language().map(str::to_string),
+                events: event_tx,
+            };
+            tokio::spawn(worker.run(audio_rx));
+
+            Ok(ProviderConnection {
+                sink: Box::new(WhisperSink { tx: audio_tx }),
+                events: event_rx,
+            })
+        }
+        .boxed()
+    }
+}
+
+enum SinkMessage {
+    Audio { samples: Vec<f32>, commit: bool },
+    Close,
+}
+
+/// Write half of a local connection, feeding the worker
+struct WhisperSink {
+    tx: mpsc::UnboundedSender<SinkMessage>,
+}
+
+impl AudioSink for WhisperSink {
+    fn send_audio<'a>(&'a mut self, samples: &'a [f32], commit: bool) -> BoxFuture<'a, Result<()>> {
+        let msg = SinkMessage::Audio {
+            samples: samples.to_vec(),
+            commit,
+        };
+        let result = self
+            .tx
+            .send(msg)
+            .map_err(|_| anyhow!("Whisper worker stopped"));
+        async move { result }.boxed()
+    }
+
+    fn close(&mut self) -> BoxFuture<'_, Result<()>> {
+        // The worker may already be gone, which is fine when closing
+        let _ = self.tx.send(SinkMessage::Close);
+        async { Ok(()) }.boxed()
+    }
+}
+
+/// Accumulates a segment and runs inference on it
+struct Worker {
+    transcriber: SharedTranscriber,
+    language: Option<String>,
+    events: mpsc::Sender<TranscriptEvent>,
+}
+
+impl Worker {
+    async fn run(self, mut rx: mpsc::UnboundedReceiver<SinkMessage>) {
+        let mut segment: Vec<f32> = Vec::new();
+        let mut since_partial = 0;
+
+        while let Some(msg) = rx.recv().await {
+            let (samples, commit) = match msg {
+                SinkMessage::Audio { samples, commit } => (samples, commit),
+                SinkMessage::Close => break,
+            };
+            segment.extend_from_slice(&samples);
+            since_partial += samples.len();
+
+            // Whisper can't see past its window, so long speech is split
+            while segment.len() >= N_SAMPLES {
+                let rest = segment.split_off(N_SAMPLES);
+                let full = std::mem::replace(&mut segment, rest);
+                self.commit(full).await;
+                since_partial = segment.len();
+            }
+
+            if commit {
+                self.commit(std::mem::take(&mut segment)).await;
+                since_partial = 0;
+            } else if since_partial >= PARTIAL_INTERVAL_SAMPLES
+                && segment.len() >= MIN_SEGMENT_SAMPLES
+                && rx.is_empty()
+            {
+                // Skipped while behind so partials never delay commits
+                since_partial = 0;
+                self.partial(&segment).await;
+            }
+        }
+
+        info!("Whisper worker stopped");
+    }
+
+    async fn partial(&self, segment: &[f32]) {
+        if is_silent(segment) {
+            return;
+        }
+        let msg = match self.transcribe(segment.to_vec()).await {
+            Ok(result) if !result.text.is_empty() => ServerMessage::PartialTranscript {
+                text: result.text,
+                created_at_ms: chrono::Utc::now().timestamp_millis() as u64,
+                normalized_text: String::new(),
+            },
+            Ok(_) => return,
+            Err(e) => return self.error(e).await,
+        };
+        self.emit(msg).await;
+    }
+
+    async fn commit(&self, segment: Vec<f32>) {
+        if segment.len() < MIN_SEGMENT_SAMPLES || is_silent(&segment) {
+            return;
+        }
+        let msg = match self.transcribe(segment).await {
+            Ok(result) if !result.text.is_empty() => {
+                ServerMessage::CommittedTranscriptWithTimestamps {
+                    text: result.text,
+                    normalized_text: String::new(),
+                    confidence: result.confidence,
+                    words: Vec::new(),
+                    language_code: result.language_code.unwrap_or_default(),
+                }
+            }
+            Ok(_) => return,
+            Err(e) => return self.error(e).await,
+        };
+        self.emit(msg).await;
+    }
+
+    async fn transcribe(&self, segment: Vec<f32>) -> Result<Transcription> {
+        let transcriber = self.transcriber.clone();
+        let language = self.language.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut transcriber = transcriber
+                .lock()
+                .map_err(|_| anyhow!("Whisper engine is unusable after a panic"))?;
+            transcriber.transcribe(&segment, language.as_deref())
+        })
+        .await?
+    }
+
+    async fn error(&self, e: anyhow::Error) {
+        warn!("Whisper transcription failed: {}", e);
+        let _ = self
+            .events
+            .send(TranscriptEvent::Error {
+                message: format!("Local transcription failed: {}", e),
+                fatal: false,
+            })
+            .await;
+    }
+
+    async fn emit(&self, msg: ServerMessage) {
+        if let Some(event) = msg.into_event() {
+            let _ = self.events.send(event).await;
+        }
+    }
+}
+
+fn is_silent(samples: &[f32]) -> bool {
+    samples.iter().all(|s| s.abs() < SILENCE_PEAK)
+}
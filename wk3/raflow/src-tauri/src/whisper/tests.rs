@@ -0,0 +1,229 @@
+use super::*;
+
+#[cfg(test)]
+mod mel_tests {
+    use crate::whisper::mel::{mel_filters, N_FREQS};
+
+    #[test]
+    fn test_mel_filter_shape() {
+        assert_eq!(N_FREQS, 201);
+        assert_eq!(mel_filters(80).len(), 80 * N_FREQS);
+        assert_eq!(mel_filters(128).len(), 128 * N_FREQS);
+    }
+
+    #[test]
+    fn test_mel_filters_match_reference() {
+        let filters = mel_filters(80);
+
+        // First row of the reference `mel_filters.npz`
+        assert!((filters[1] - 0.024_862_6).abs() < 1e-5);
+        assert_eq!(filters[0], 0.0);
+        assert!(filters.iter().all(|w| *w >= 0.0));
+
+        // Every band covers some frequency
+        for row in filters.chunks(N_FREQS) {
+            assert!(row.iter().any(|w| *w > 0.0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod language_tests {
+    use crate::whisper::engine::{iso_language, whisper_language};
+
+    #[test]
+    fn test_language_mapping() {
+        assert_eq!(whisper_language("zho"), Some("zh"));
+        assert_eq!(whisper_language("YUE"), Some("yue"));
+        assert_eq!(whisper_language("eng"), Some("en"));
+        assert_eq!(whisper_language("ja"), Some("ja"));
+        assert_eq!(whisper_language("xyz"), None);
+
+        assert_eq!(iso_language("zh"), Some("zho"));
+        assert_eq!(iso_language("en"), Some("eng"));
+        assert_eq!(iso_language("xx"), None);
+    }
+}
+
+#[cfg(test)]
+mod model_tests {
+    use super::*;
+    use crate::whisper::models::MODEL_FILES;
+
+    #[test]
+    fn test_model_ids() {
+        assert_eq!(WhisperModel::default(), WhisperModel::Base);
+        assert_eq!(WhisperModel::BaseEn.repo(), "openai/whisper-base.en");
+        assert!(WhisperModel::TinyEn.is_english_only());
+        assert!(!WhisperModel::Small.is_english_only());
+        assert_eq!(
+            serde_json::to_value(WhisperModel::TinyEn).unwrap(),
+            "tiny_en"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_model_manager_tracks_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = ModelManager::new(dir.path());
+        assert!(manager.list().iter().all(|status| !status.downloaded));
+
+        let model_dir = manager.model_dir(WhisperModel::Tiny);
+        std::fs::create_dir_all(&model_dir).unwrap();
+        std::fs::write(model_dir.join(MODEL_FILES[0]), "{}").unwrap();
+        std::fs::write(model_dir.join("model.safetensors.part"), "").unwrap();
+        assert!(!manager.is_downloaded(WhisperModel::Tiny));
+
+        for file in MODEL_FILES {
+            std::fs::write(model_dir.join(file), "").unwrap();
+        }
+        assert!(manager.is_downloaded(WhisperModel::Tiny));
+        let downloaded: Vec<_> = manager
+            .list()
+            .into_iter()
+            .filter(|status| status.downloaded)
+            .map(|status| status.model)
+            .collect();
+        assert_eq!(downloaded, vec![WhisperModel::Tiny]);
+
+        manager.delete(WhisperModel::Tiny).await.unwrap();
+        assert!(!manager.is_downloaded(WhisperModel::Tiny));
+        assert!(!model_dir.exists());
+    }
+}
+
+#[cfg(test)]
+mod provider_tests {
+    use super::*;
+    use crate::network::{SessionOptions, SpeechToTextProvider, TranscriptEvent};
+    use anyhow::Result;
+    use candle_transformers::models::whisper::N_SAMPLES;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// Reports the segment length and the requested language
+    struct FakeTranscriber {
+        calls: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl Transcriber for FakeTranscriber {
+        fn transcribe(&mut self, pcm: &[f32], language: Option<&str>) -> Result<Transcription> {
+            self.calls.lock().unwrap().push(pcm.len());
+            Ok(Transcription {
+                text: format!("{} samples", pcm.len()),
+                language_code: language.map(str::to_string),
+                confidence: 0.5,
+            })
+        }
+    }
+
+    fn provider(dir: &std::path::Path) -> (WhisperProvider, Arc<Mutex<Vec<usize>>>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let transcriber = FakeTranscriber {
+            calls: calls.clone(),
+        };
+        let provider = WhisperProvider::with_transcriber(
+            ModelManager::new(dir),
+            WhisperModel::Tiny,
+            Box::new(transcriber),
+        );
+        (provider, calls)
+    }
+
+    async fn next(events: &mut tokio::sync::mpsc::Receiver<TranscriptEvent>) -> TranscriptEvent {
+        tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("timed out waiting for event")
+            .expect("events closed")
+    }
+
+    #[tokio::test]
+    async fn test_session_emits_partial_and_committed() {
+        let dir = tempfile::tempdir().unwrap();
+        let (provider, calls) = provider(dir.path());
+        let options = SessionOptions::new().with_language("eng");
+        let mut connection = provider.connect(&options).await.unwrap();
+
+        match next(&mut connection.events).await {
+            TranscriptEvent::SessionStarted {
+                session_id,
+                model_id,
+            } => {
+                assert!(session_id.starts_with("local-"));
+                assert_eq!(model_id, "whisper-tiny");
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        // One second of speech triggers a partial
+        let speech = vec![0.1; 8000];
+        connection.sink.send_audio(&speech, false).await.unwrap();
+        connection.sink.send_audio(&speech, false).await.unwrap();
+        assert_eq!(
+            next(&mut connection.events).await,
+            TranscriptEvent::Partial {
+                text: "16000 samples".to_string()
+            }
+        );
+
+        connection.sink.send_audio(&speech, true).await.unwrap();
+        assert_eq!(
+            next(&mut connection.events).await,
+            TranscriptEvent::Committed {
+                text: "24000 samples".to_string(),
+                language_code: Some("eng".to_string()),
+                confidence: Some(0.5),
+                words: Vec::new(),
+            }
+        );
+
+        // Silence and very short segments are not transcribed
+        connection
+            .sink
+            .send_audio(&[0.0; 16000], true)
+            .await
+            .unwrap();
+        connection.sink.send_audio(&[0.1; 160], true).await.unwrap();
+        connection.sink.close().await.unwrap();
+        assert!(connection.events.recv().await.is_none());
+        assert_eq!(*calls.lock().unwrap(), vec![16000, 24000]);
+    }
+
+    #[tokio::test]
+    async fn test_long_speech_is_split_at_the_window() {
+        let dir = tempfile::tempdir().unwrap();
+        let (provider, calls) = provider(dir.path());
+        let mut connection = provider.connect(&SessionOptions::new()).await.unwrap();
+        next(&mut connection.events).await;
+
+        // Sent in one go so no partials are produced
+        let speech = vec![0.1; N_SAMPLES + 8000];
+        connection.sink.send_audio(&speech, true).await.unwrap();
+        connection.sink.close().await.unwrap();
+
+        let mut committed = Vec::new();
+        while let Some(event) = connection.events.recv().await {
+            if let TranscriptEvent::Committed { text, .. } = event {
+                committed.push(text);
+            }
+        }
+        assert_eq!(
+            committed,
+            vec![format!("{} samples", N_SAMPLES), "8000 samples".to_string()]
+        );
+        assert_eq!(*calls.lock().unwrap(), vec![N_SAMPLES, 8000]);
+    }
+
+    #[tokio::test]
+    async fn test_connect_requires_downloaded_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = WhisperProvider::new(ModelManager::new(dir.path()));
+
+        let err = provider
+            .connect(&SessionOptions::new())
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("not downloaded"));
+    }
+}
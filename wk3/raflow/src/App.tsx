@@ -1,24 +1,39 @@
-import { useState } from 'react';
+import { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { Tabs, TabsContent, TabsList, TabsTrigger } from './components/ui/tabs';
 import { Settings } from './components/Settings/Settings';
 import { TextInjectionTest } from './components/Settings/TextInjectionTest';
 import { OverlayWindow } from './components/Overlay/OverlayWindow';
 import { PermissionsCheck } from './components/Permissions/PermissionsCheck';
 import { PerformanceMonitor } from './components/Performance/PerformanceMonitor';
+import { HistoryPanel } from './components/History/HistoryPanel';
 import './App.css';
 
 function App() {
   const [isRecording, setIsRecording] = useState(false);
   const [activeTab, setActiveTab] = useState('permissions');
 
-  const handleStartRecording = async (apiKey: string, deviceName?: string) => {
+  // Recording can also be started and stopped with the global hotkeys
+  useEffect(() => {
+    const unlistenStarted = listen('recording-started', () => {
+      setIsRecording(true);
+      setActiveTab('overlay');
+    });
+    const unlistenStopped = listen<string>('recording-stopped', () => {
+      setIsRecording(false);
+    });
+
+    return () => {
+      unlistenStarted.then((f) => f());
+      unlistenStopped.then((f) => f());
+    };
+  }, []);
+
+  const handleStartRecording = async () => {
     try {
-      console.log('Starting recording with:', { apiKey: apiKey.substring(0, 10) + '...', deviceName });
-      await invoke('start_recording', {
-        apiKey,
-        deviceName: deviceName || null,
-      });
+      // API key, device and language come from the saved settings
+      await invoke('start_recording');
       console.log('Recording started successfully');
       setIsRecording(true);
       setActiveTab('overlay');
@@ -53,13 +68,14 @@ function App() {
 
         {/* Main Content */}
         <Tabs value={activeTab} onValueChange={setActiveTab} className="w-full">
-          <TabsList className="grid w-full max-w-4xl mx-auto grid-cols-5">
+          <TabsList className="grid w-full max-w-4xl mx-auto grid-cols-6">
             <TabsTrigger value="permissions">权限</TabsTrigger>
             <TabsTrigger value="settings">转写设置</TabsTrigger>
             <TabsTrigger value="test">文本注入</TabsTrigger>
             <TabsTrigger value="overlay" disabled={!isRecording}>
               转写界面
             </TabsTrigger>
+            <TabsTrigger value="history">历史记录</TabsTrigger>
             <TabsTrigger value="performance">性能监控</TabsTrigger>
           </TabsList>
 
@@ -97,6 +113,10 @@ function App() {
             )}
           </TabsContent>
 
+          <TabsContent value="history" className="mt-6">
+            <HistoryPanel />
+          </TabsContent>
+
           <TabsContent value="performance" className="mt-6">
             <PerformanceMonitor />
           </TabsContent>
@@ -0,0 +1,129 @@
+import { useEffect, useState } from 'react';
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '../ui/card';
+import {
+  HistoryEntry,
+  clearHistory,
+  copyHistoryEntry,
+  deleteHistoryEntry,
+  listHistory,
+  reinjectHistoryEntry,
+  searchHistory,
+} from '../../lib/history';
+
+export function HistoryPanel() {
+  const [entries, setEntries] = useState<HistoryEntry[]>([]);
+  const [query, setQuery] = useState('');
+  const [message, setMessage] = useState('');
+
+  const refresh = async (search = query) => {
+    try {
+      const result = search.trim() ? await searchHistory(search) : await listHistory();
+      setEntries(result);
+    } catch (error) {
+      setMessage(`加载历史记录失败: ${error}`);
+    }
+  };
+
+  useEffect(() => {
+    // Debounce so every keystroke doesn't hit the database
+    const timeout = setTimeout(() => refresh(query), 200);
+    return () => clearTimeout(timeout);
+  }, [query]);
+
+  const run = async (action: () => Promise<void>, success: string) => {
+    try {
+      await action();
+      setMessage(success);
+    } catch (error) {
+      setMessage(`✗ ${error}`);
+    }
+  };
+
+  const handleDelete = async (id: number) => {
+    await run(() => deleteHistoryEntry(id), '已删除');
+    await refresh();
+  };
+
+  const handleClear = async () => {
+    if (!confirm('确定要清空全部历史记录吗？')) return;
+    await run(clearHistory, '历史记录已清空');
+    await refresh();
+  };
+
+  return (
+    <div className="container mx-auto p-8 max-w-3xl space-y-6">
+      <Card>
+        <CardHeader>
+          <CardTitle>历史记录</CardTitle>
+          <CardDescription>搜索、复制或重新输入以前的转写内容</CardDescription>
+        </CardHeader>
+        <CardContent className="space-y-4">
+          <div className="flex gap-2">
+            <input
+              type="search"
+              className="flex-1 px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+              placeholder="搜索转写内容..."
+              value={query}
+              onChange={(e) => setQuery(e.target.value)}
+            />
+            <button
+              onClick={handleClear}
+              disabled={entries.length === 0}
+              className="px-4 py-2 border border-red-300 text-red-600 rounded-md hover:bg-red-50 disabled:opacity-50"
+            >
+              清空
+            </button>
+          </div>
+
+          {message && <p className="text-sm text-gray-600">{message}</p>}
+
+          {entries.length === 0 ? (
+            <p className="text-center text-gray-500 py-8">暂无记录</p>
+          ) : (
+            <ul className="space-y-3">
+              {entries.map((entry) => (
+                <li key={entry.id} className="p-3 border border-gray-200 rounded-md space-y-2">
+                  <p className="text-gray-900 dark:text-white whitespace-pre-wrap">{entry.text}</p>
+                  <div className="flex flex-wrap items-center gap-2 text-xs text-gray-500">
+                    <span>{new Date(entry.created_at).toLocaleString()}</span>
+                    {entry.window && <span>· {entry.window.app_name}</span>}
+                    {entry.language_code && (
+                      <span className="px-1.5 py-0.5 bg-gray-100 rounded uppercase">
+                        {entry.language_code}
+                      </span>
+                    )}
+                    {entry.confidence !== null && (
+                      <span>· {Math.round(entry.confidence * 100)}%</span>
+                    )}
+                    <div className="ml-auto flex gap-2">
+                      <button
+                        onClick={() => run(() => copyHistoryEntry(entry.id), '✓ 已复制到剪贴板')}
+                        className="text-blue-600 hover:underline"
+                      >
+                        复制
+                      </button>
+                      <button
+                        onClick={() =>
+                          run(() => reinjectHistoryEntry(entry.id), '✓ 已输入到当前窗口')
+                        }
+                        className="text-blue-600 hover:underline"
+                      >
+                        重新输入
+                      </button>
+                      <button
+                        onClick={() => handleDelete(entry.id)}
+                        className="text-red-600 hover:underline"
+                      >
+                        删除
+                      </button>
+                    </div>
+                  </div>
+                </li>
+              ))}
+            </ul>
+          )}
+        </CardContent>
+      </Card>
+    </div>
+  );
+}
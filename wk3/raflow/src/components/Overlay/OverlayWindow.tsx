@@ -1,15 +1,14 @@
 import { useEffect, useState } from 'react';
 import { listen } from '@tauri-apps/api/event';
-
-interface TranscriptEvent {
-  text: string;
-  is_final: boolean;
-}
+import { AudioLevelEvent, TranscriptEvent } from '../../lib/transcript';
 
 export function OverlayWindow() {
   const [text, setText] = useState('');
   const [audioLevel, setAudioLevel] = useState(0);
   const [isFinal, setIsFinal] = useState(false);
+  const [languageCode, setLanguageCode] = useState<string | null>(null);
+  const [reconnecting, setReconnecting] = useState(false);
+  const [deviceLost, setDeviceLost] = useState(false);
 
   useEffect(() => {
     const unlistenTranscript = listen<TranscriptEvent>(
@@ -17,19 +16,39 @@ export function OverlayWindow() {
       (event) => {
         setText(event.payload.text);
         setIsFinal(event.payload.is_final);
+        if (event.payload.language_code) {
+          setLanguageCode(event.payload.language_code);
+        }
       }
     );
 
-    const unlistenAudio = listen<number>(
+    const unlistenAudio = listen<AudioLevelEvent>(
       'audio-level',
       (event) => {
-        setAudioLevel(event.payload);
+        setAudioLevel(event.payload.level);
       }
     );
 
+    // Audio is buffered while the connection is restored
+    const unlistenLost = listen('connection-lost', () => setReconnecting(true));
+    const unlistenRestored = listen('connection-restored', () => setReconnecting(false));
+    const unlistenStopped = listen('recording-stopped', () => {
+      setReconnecting(false);
+      setDeviceLost(false);
+    });
+
+    // The microphone was unplugged, capture moves to another device
+    const unlistenDeviceLost = listen('audio-device-lost', () => setDeviceLost(true));
+    const unlistenDeviceChanged = listen('audio-device-changed', () => setDeviceLost(false));
+
     return () => {
       unlistenTranscript.then((f) => f());
       unlistenAudio.then((f) => f());
+      unlistenLost.then((f) => f());
+      unlistenRestored.then((f) => f());
+      unlistenStopped.then((f) => f());
+      unlistenDeviceLost.then((f) => f());
+      unlistenDeviceChanged.then((f) => f());
     };
   }, []);
 
@@ -60,6 +79,21 @@ export function OverlayWindow() {
           <span className="text-xs text-gray-400">
             {audioLevel > 0.1 ? '正在说话' : '等待输入'}
           </span>
+          {reconnecting && (
+            <span className="ml-2 px-2 py-0.5 rounded bg-yellow-500/20 text-xs text-yellow-300">
+              正在重新连接...
+            </span>
+          )}
+          {deviceLost && (
+            <span className="ml-2 px-2 py-0.5 rounded bg-red-500/20 text-xs text-red-300">
+              麦克风已断开
+            </span>
+          )}
+          {languageCode && (
+            <span className="ml-2 px-2 py-0.5 rounded bg-white/10 text-xs text-gray-300 uppercase">
+              {languageCode}
+            </span>
+          )}
         </div>
       </div>
     </div>
@@ -1,7 +1,20 @@
 import { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '../ui/card';
 
+interface SessionMetrics {
+  provider: string;
+  device: string;
+  duration_secs: number;
+  audio_packets_processed: number;
+  audio_packets_dropped: number;
+  ws_reconnects: number;
+  partial_transcripts: number;
+  committed_transcripts: number;
+  errors_total: number;
+}
+
 interface PerformanceMetrics {
   uptime_secs: number;
   audio_packets_processed: number;
@@ -13,14 +26,20 @@ interface PerformanceMetrics {
   ws_bytes_sent: number;
   ws_bytes_received: number;
   ws_reconnects: number;
+  audio_pcm_bytes: number;
+  audio_wire_bytes: number;
+  audio_bandwidth_savings: number;
   partial_transcripts: number;
   committed_transcripts: number;
-  transcript_latency_ms: number;
+  transcript_latency_p50_ms: number;
+  transcript_latency_p95_ms: number;
+  transcript_latency_p99_ms: number;
   memory_usage_mb: number;
   cpu_usage_percent: number;
   errors_total: number;
   errors_retried: number;
   errors_recovered: number;
+  session: SessionMetrics | null;
 }
 
 export function PerformanceMonitor() {
@@ -37,6 +56,17 @@ export function PerformanceMonitor() {
     }
   }, [autoRefresh]);
 
+  // The backend samples the process and reports when the health flips
+  useEffect(() => {
+    const unlistenHealth = listen<boolean>('health-changed', (event) => {
+      setIsHealthy(event.payload);
+    });
+
+    return () => {
+      unlistenHealth.then((f) => f());
+    };
+  }, []);
+
   const fetchMetrics = async () => {
     try {
       const [metricsData, healthStatus] = await Promise.all([
@@ -168,6 +198,14 @@ export function PerformanceMonitor() {
               value={metrics.ws_reconnects.toString()}
               alert={metrics.ws_reconnects > 3}
             />
+            <MetricRow
+              label="音频上传"
+              value={`${formatBytes(metrics.audio_wire_bytes)} / ${formatBytes(metrics.audio_pcm_bytes)} PCM`}
+            />
+            <MetricRow
+              label="带宽节省"
+              value={`${metrics.audio_bandwidth_savings.toFixed(1)}%`}
+            />
           </CardContent>
         </Card>
 
@@ -187,9 +225,17 @@ export function PerformanceMonitor() {
               value={metrics.committed_transcripts.toLocaleString()}
             />
             <MetricRow
-              label="转写延迟"
-              value={`${metrics.transcript_latency_ms}ms`}
-              alert={metrics.transcript_latency_ms > 500}
+              label="转写延迟 (P50)"
+              value={`${metrics.transcript_latency_p50_ms}ms`}
+            />
+            <MetricRow
+              label="转写延迟 (P95)"
+              value={`${metrics.transcript_latency_p95_ms}ms`}
+              alert={metrics.transcript_latency_p95_ms > 500}
+            />
+            <MetricRow
+              label="转写延迟 (P99)"
+              value={`${metrics.transcript_latency_p99_ms}ms`}
             />
           </CardContent>
         </Card>
@@ -227,7 +273,7 @@ export function PerformanceMonitor() {
             {metrics.audio_drop_rate > 1 && (
               <p>• 音频丢包率较高，建议关闭其他占用音频的应用</p>
             )}
-            {metrics.transcript_latency_ms > 500 && (
+            {metrics.transcript_latency_p95_ms > 500 && (
               <p>• 转写延迟较高，请检查网络连接</p>
             )}
             {metrics.memory_usage_mb > 100 && (
@@ -1,67 +1,211 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '../ui/card';
+import { Checkbox } from '../ui/checkbox';
 import { Label } from '../ui/label';
+import {
+  ApiKeyStatus,
+  AppSettings,
+  AudioEncoding,
+  ChannelMode,
+  DEFAULT_MODEL,
+  InjectionStrategy,
+  clearApiKey,
+  getApiKeyStatus,
+  getSettings,
+  migrateLegacySettings,
+  setApiKey,
+  updateSettings,
+  VadEngine,
+  WhisperModel,
+} from '../../lib/settings';
+import {
+  DownloadProgress,
+  ModelStatus,
+  deleteWhisperModel,
+  downloadWhisperModel,
+  listWhisperModels,
+  onDownloadProgress,
+} from '../../lib/whisper';
 
 interface DeviceInfo {
   name: string;
   is_default: boolean;
+  sample_rates: number[];
+  channels: number[];
+  sample_formats: string[];
+}
+
+const BUFFER_SIZES = [128, 256, 512, 1024, 2048];
+
+const AUDIO_ENCODINGS: { value: AudioEncoding; label: string }[] = [
+  { value: 'pcm_base64', label: 'PCM (JSON, 默认)' },
+  { value: 'mulaw', label: 'μ-law 8kHz（省 75% 流量）' },
+  { value: 'pcm_binary', label: 'PCM 二进制帧' },
+  { value: 'opus', label: 'Opus 二进制帧' },
+];
+
+const LANGUAGES = [
+  { code: 'auto', label: '自动检测' },
+  { code: 'zho', label: '普通话 (zho)' },
+  { code: 'yue', label: '粤语 (yue)' },
+  { code: 'nan', label: '闽南语 (nan)' },
+  { code: 'eng', label: 'English (eng)' },
+  { code: 'jpn', label: '日本語 (jpn)' },
+  { code: 'kor', label: '한국어 (kor)' },
+  { code: 'spa', label: 'Español (spa)' },
+  { code: 'fra', label: 'Français (fra)' },
+  { code: 'deu', label: 'Deutsch (deu)' },
+];
+
+/** Select value of a channel mode, single channels are their index */
+function channelModeValue(mode: ChannelMode): string {
+  return typeof mode === 'string' ? mode : String(mode.single);
+}
+
+function parseChannelMode(value: string): ChannelMode {
+  return value === 'mix' || value === 'auto' ? value : { single: Number(value) };
 }
 
 interface SettingsProps {
-  onStartRecording: (apiKey: string, deviceName?: string) => void;
+  onStartRecording: () => void;
   onStopRecording: () => void;
   isRecording: boolean;
 }
 
 export function Settings({ onStartRecording, onStopRecording, isRecording }: SettingsProps) {
-  const [apiKey, setApiKey] = useState('');
+  const [settings, setSettings] = useState<AppSettings | null>(null);
   const [devices, setDevices] = useState<DeviceInfo[]>([]);
-  const [selectedDevice, setSelectedDevice] = useState<string>('');
+  const [apiKeyStatus, setApiKeyStatus] = useState<ApiKeyStatus | null>(null);
+  const [apiKeyInput, setApiKeyInput] = useState('');
   const [loading, setLoading] = useState(false);
+  const [models, setModels] = useState<ModelStatus[]>([]);
+  const [download, setDownload] = useState<DownloadProgress | null>(null);
 
   useEffect(() => {
     loadDevices();
-    loadSavedApiKey();
+    loadSettings();
+    loadModels();
+
+    const unlisten = onDownloadProgress(setDownload);
+    // Microphones plugged in or removed
+    const unlistenDevices = listen<DeviceInfo[]>('devices-changed', (event) =>
+      setDevices(event.payload)
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+      unlistenDevices.then((fn) => fn());
+    };
   }, []);
 
+  const loadModels = async () => {
+    try {
+      setModels(await listWhisperModels());
+    } catch (error) {
+      console.error('Failed to load models:', error);
+    }
+  };
+
+  const handleDownloadModel = async (model: WhisperModel) => {
+    try {
+      await downloadWhisperModel(model);
+    } catch (error) {
+      console.error('Failed to download model:', error);
+      alert(`下载模型失败: ${error}`);
+    } finally {
+      setDownload(null);
+      await loadModels();
+    }
+  };
+
+  const handleDeleteModel = async (model: WhisperModel) => {
+    try {
+      await deleteWhisperModel(model);
+    } catch (error) {
+      console.error('Failed to delete model:', error);
+    }
+    await loadModels();
+  };
+
   const loadDevices = async () => {
     try {
       const deviceList = await invoke<DeviceInfo[]>('list_audio_devices');
       setDevices(deviceList);
-
-      // Select default device
-      const defaultDevice = deviceList.find(d => d.is_default);
-      if (defaultDevice) {
-        setSelectedDevice(defaultDevice.name);
-      }
     } catch (error) {
       console.error('Failed to load devices:', error);
     }
   };
 
-  const loadSavedApiKey = () => {
-    const saved = localStorage.getItem('elevenlabs_api_key');
-    if (saved) {
-      setApiKey(saved);
+  const loadSettings = async () => {
+    try {
+      const saved = await getSettings();
+      setSettings(await migrateLegacySettings(saved));
+      setApiKeyStatus(await getApiKeyStatus());
+    } catch (error) {
+      console.error('Failed to load settings:', error);
+    }
+  };
+
+  const handleSaveApiKey = async () => {
+    try {
+      await setApiKey(apiKeyInput);
+      setApiKeyInput('');
+      setApiKeyStatus(await getApiKeyStatus());
+    } catch (error) {
+      console.error('Failed to save API key:', error);
+      alert(`保存 API 密钥失败: ${error}`);
+    }
+  };
+
+  const handleClearApiKey = async () => {
+    try {
+      await clearApiKey();
+      setApiKeyStatus(await getApiKeyStatus());
+    } catch (error) {
+      console.error('Failed to clear API key:', error);
     }
   };
 
-  const saveApiKey = () => {
-    localStorage.setItem('elevenlabs_api_key', apiKey);
+  const update = (patch: Partial<AppSettings>) => {
+    setSettings((current) => (current ? { ...current, ...patch } : current));
   };
 
+  const hasApiKey = apiKeyStatus?.configured ?? false;
+  const offline = settings?.offline.enabled ?? false;
+  const selectedModel = models.find((m) => m.model === settings?.offline.model);
+  const canStart = offline ? selectedModel?.downloaded ?? false : hasApiKey;
+  const language = settings?.transcription.language_code ?? 'auto';
+  const defaultDevice = devices.find((d) => d.is_default)?.name ?? '';
+  const selectedDevice = devices.find((d) => d.name === (settings?.device_name ?? defaultDevice));
+  // Devices that don't report their channels get the common maximum
+  const maxChannels = Math.max(...(selectedDevice?.channels ?? []), 0) || 8;
+
   const handleStartRecording = async () => {
-    if (!apiKey) {
+    if (!settings) {
+      return;
+    }
+    if (!offline && !hasApiKey) {
       alert('Please enter your ElevenLabs API key');
       return;
     }
+    if (offline && !selectedModel?.downloaded) {
+      alert('请先下载所选的离线模型');
+      return;
+    }
 
     setLoading(true);
-    saveApiKey();
 
     try {
-      await onStartRecording(apiKey, selectedDevice || undefined);
+      const saved = await updateSettings({
+        ...settings,
+        transcription: {
+          ...settings.transcription,
+          model_id: settings.transcription.model_id.trim() || DEFAULT_MODEL,
+        },
+      });
+      setSettings(saved);
+      await onStartRecording();
     } catch (error) {
       console.error('Failed to start recording:', error);
       alert(`Failed to start: ${error}`);
@@ -83,15 +227,38 @@ export function Settings({ onStartRecording, onStopRecording, isRecording }: Set
           {/* API Key */}
           <div className="space-y-2">
             <Label htmlFor="apiKey">ElevenLabs API Key</Label>
-            <input
-              id="apiKey"
-              type="password"
-              className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
-              placeholder="sk-..."
-              value={apiKey}
-              onChange={(e) => setApiKey(e.target.value)}
-              disabled={isRecording}
-            />
+            <div className="flex gap-2">
+              <input
+                id="apiKey"
+                type="password"
+                className="flex-1 px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                placeholder={hasApiKey ? '已保存，输入新密钥以替换' : 'sk-...'}
+                value={apiKeyInput}
+                onChange={(e) => setApiKeyInput(e.target.value)}
+                disabled={isRecording}
+              />
+              <button
+                onClick={handleSaveApiKey}
+                disabled={isRecording || !apiKeyInput.trim()}
+                className="px-4 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700 disabled:bg-gray-300 disabled:cursor-not-allowed transition-colors"
+              >
+                保存
+              </button>
+              {hasApiKey && (
+                <button
+                  onClick={handleClearApiKey}
+                  disabled={isRecording}
+                  className="px-4 py-2 border border-gray-300 rounded-md hover:bg-gray-100 disabled:cursor-not-allowed transition-colors"
+                >
+                  清除
+                </button>
+              )}
+            </div>
+            {hasApiKey && (
+              <p className="text-xs text-green-600">
+                API 密钥已安全保存 ({apiKeyStatus?.backend === 'keychain' ? '系统钥匙串' : '加密文件'})
+              </p>
+            )}
             <p className="text-xs text-gray-500">
               您可以在 <a href="https://elevenlabs.io" target="_blank" rel="noopener noreferrer" className="text-blue-500 hover:underline">elevenlabs.io</a> 获取 API 密钥
             </p>
@@ -103,8 +270,10 @@ export function Settings({ onStartRecording, onStopRecording, isRecording }: Set
             <select
               id="device"
               className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
-              value={selectedDevice}
-              onChange={(e) => setSelectedDevice(e.target.value)}
+              value={settings?.device_name ?? defaultDevice}
+              onChange={(e) =>
+                update({ device_name: e.target.value === defaultDevice ? null : e.target.value })
+              }
               disabled={isRecording}
             >
               {devices.map((device) => (
@@ -113,6 +282,441 @@ export function Settings({ onStartRecording, onStopRecording, isRecording }: Set
                 </option>
               ))}
             </select>
+            {selectedDevice && selectedDevice.sample_rates.length > 0 && (
+              <p className="text-xs text-gray-500">
+                支持 {selectedDevice.sample_rates.map((rate) => rate / 1000).join(' / ')} kHz ·{' '}
+                {selectedDevice.channels.join(' / ')} 声道 · {selectedDevice.sample_formats.join(', ')}
+              </p>
+            )}
+          </div>
+
+          {/* Fallback Devices */}
+          <div className="space-y-2">
+            <Label htmlFor="devicePriority">备用设备</Label>
+            {(settings?.device_priority ?? []).map((name, index) => (
+              <div key={name} className="flex items-center justify-between text-sm">
+                <span>
+                  {index + 1}. {name}
+                </span>
+                <button
+                  type="button"
+                  className="text-xs text-red-500 hover:underline disabled:opacity-50"
+                  onClick={() =>
+                    settings &&
+                    update({ device_priority: settings.device_priority.filter((n) => n !== name) })
+                  }
+                  disabled={isRecording}
+                >
+                  移除
+                </button>
+              </div>
+            ))}
+            <select
+              id="devicePriority"
+              className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+              value=""
+              onChange={(e) =>
+                settings &&
+                e.target.value &&
+                update({ device_priority: [...settings.device_priority, e.target.value] })
+              }
+              disabled={isRecording}
+            >
+              <option value="">添加备用设备...</option>
+              {devices
+                .filter((device) => !settings?.device_priority.includes(device.name))
+                .map((device) => (
+                  <option key={device.name} value={device.name}>
+                    {device.name}
+                  </option>
+                ))}
+            </select>
+            <p className="text-xs text-gray-500">当前设备断开时按顺序切换，最后使用系统默认设备，录音不会中断</p>
+          </div>
+
+          {/* Buffer Size */}
+          <div className="space-y-2">
+            <Label htmlFor="bufferSize">采集缓冲区</Label>
+            <select
+              id="bufferSize"
+              className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+              value={settings?.buffer_size ?? 'default'}
+              onChange={(e) =>
+                update({ buffer_size: e.target.value === 'default' ? null : Number(e.target.value) })
+              }
+              disabled={isRecording}
+            >
+              <option value="default">设备默认</option>
+              {BUFFER_SIZES.map((frames) => (
+                <option key={frames} value={frames}>
+                  {frames} 帧
+                </option>
+              ))}
+            </select>
+            <p className="text-xs text-gray-500">较小的缓冲区延迟更低，出现断音时请调大</p>
+          </div>
+
+          {/* Channel Mode */}
+          <div className="space-y-2">
+            <Label htmlFor="channelMode">声道</Label>
+            <select
+              id="channelMode"
+              className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+              value={channelModeValue(settings?.channel_mode ?? 'mix')}
+              onChange={(e) => update({ channel_mode: parseChannelMode(e.target.value) })}
+              disabled={isRecording}
+            >
+              <option value="mix">混合全部声道</option>
+              <option value="auto">自动选择</option>
+              {Array.from({ length: maxChannels }, (_, i) => (
+                <option key={i} value={String(i)}>
+                  第 {i + 1} 声道
+                </option>
+              ))}
+            </select>
+            <p className="text-xs text-gray-500">自动选择会跳过静音或反相的声道，避免多声道麦克风混合后声音变小</p>
+          </div>
+
+          {/* Transcription Language */}
+          <div className="space-y-2">
+            <Label htmlFor="language">识别语言</Label>
+            <select
+              id="language"
+              className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+              value={language}
+              onChange={(e) =>
+                settings &&
+                update({
+                  transcription: {
+                    ...settings.transcription,
+                    language_code: e.target.value === 'auto' ? null : e.target.value,
+                  },
+                })
+              }
+              disabled={isRecording}
+            >
+              {LANGUAGES.map((lang) => (
+                <option key={lang.code} value={lang.code}>
+                  {lang.label}
+                </option>
+              ))}
+            </select>
+          </div>
+
+          {/* Transcription Model */}
+          <div className="space-y-2">
+            <Label htmlFor="model">模型</Label>
+            <input
+              id="model"
+              type="text"
+              className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+              placeholder={DEFAULT_MODEL}
+              value={settings?.transcription.model_id ?? DEFAULT_MODEL}
+              onChange={(e) =>
+                settings &&
+                update({ transcription: { ...settings.transcription, model_id: e.target.value } })
+              }
+              disabled={isRecording}
+            />
+          </div>
+
+          {/* Provider Endpoint */}
+          <div className="space-y-2">
+            <Label htmlFor="endpoint">服务地址（可选）</Label>
+            <input
+              id="endpoint"
+              type="text"
+              className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+              placeholder="wss://api.elevenlabs.io/v1/speech-to-text/realtime"
+              value={settings?.endpoint ?? ''}
+              onChange={(e) => update({ endpoint: e.target.value.trim() || null })}
+              disabled={isRecording}
+            />
+            <p className="text-xs text-gray-500">留空使用 ElevenLabs 官方服务，可填写代理或本地测试服务器</p>
+          </div>
+
+          {/* Audio Upload Encoding */}
+          <div className="space-y-2">
+            <Label htmlFor="audioEncoding">上传编码</Label>
+            <select
+              id="audioEncoding"
+              className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+              value={settings?.transcription.audio_encoding ?? 'pcm_base64'}
+              onChange={(e) =>
+                settings &&
+                update({
+                  transcription: {
+                    ...settings.transcription,
+                    audio_encoding: e.target.value as AudioEncoding,
+                  },
+                })
+              }
+              disabled={isRecording}
+            >
+              {AUDIO_ENCODINGS.map((encoding) => (
+                <option key={encoding.value} value={encoding.value}>
+                  {encoding.label}
+                </option>
+              ))}
+            </select>
+            <p className="text-xs text-gray-500">二进制帧仅在自定义服务地址下可用，不支持时自动使用 PCM</p>
+          </div>
+
+          {/* Offline Transcription */}
+          <div className="space-y-2">
+            <div className="flex items-center gap-2">
+              <Checkbox
+                id="offlineEnabled"
+                checked={offline}
+                disabled={isRecording}
+                onCheckedChange={(checked) =>
+                  settings && update({ offline: { ...settings.offline, enabled: checked === true } })
+                }
+              />
+              <Label htmlFor="offlineEnabled">离线识别（本地 Whisper 模型，无需 API 密钥）</Label>
+            </div>
+            <Label htmlFor="whisperModel">离线模型</Label>
+            <div className="flex gap-2">
+              <select
+                id="whisperModel"
+                className="flex-1 px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                value={settings?.offline.model ?? 'base'}
+                onChange={(e) =>
+                  settings &&
+                  update({ offline: { ...settings.offline, model: e.target.value as WhisperModel } })
+                }
+                disabled={isRecording}
+              >
+                {models.map((m) => (
+                  <option key={m.model} value={m.model}>
+                    {m.id} ({m.size_mb} MB{m.english_only ? '，仅英语' : ''}){m.downloaded ? ' ✓' : ''}
+                  </option>
+                ))}
+              </select>
+              {selectedModel?.downloaded ? (
+                <button
+                  onClick={() => handleDeleteModel(selectedModel.model)}
+                  disabled={isRecording}
+                  className="px-4 py-2 border border-red-300 text-red-600 rounded-md hover:bg-red-50 disabled:opacity-50"
+                >
+                  删除
+                </button>
+              ) : (
+                <button
+                  onClick={() => selectedModel && handleDownloadModel(selectedModel.model)}
+                  disabled={!selectedModel || download !== null}
+                  className="px-4 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700 disabled:bg-gray-300"
+                >
+                  下载
+                </button>
+              )}
+            </div>
+            {download && (
+              <p className="text-xs text-gray-500">
+                正在下载 {download.file}:{' '}
+                {download.total
+                  ? `${Math.round((download.downloaded / download.total) * 100)}%`
+                  : `${(download.downloaded / 1024 / 1024).toFixed(1)} MB`}
+              </p>
+            )}
+            <p className="text-xs text-gray-500">模型保存在应用数据目录中，识别完全在本机进行</p>
+          </div>
+
+          {/* VAD */}
+          <div className="space-y-2">
+            <Label htmlFor="vadEngine">语音检测方式</Label>
+            <select
+              id="vadEngine"
+              className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+              value={settings?.vad_engine ?? 'energy'}
+              onChange={(e) => update({ vad_engine: e.target.value as VadEngine })}
+              disabled={isRecording}
+            >
+              <option value="energy">音量阈值</option>
+              <option value="adaptive">自适应噪声</option>
+              <option value="silero">神经网络 (Silero)</option>
+            </select>
+            <p className="text-xs text-gray-500">自适应噪声会根据环境噪声自动调整阈值；神经网络检测在嘈杂环境中更准确，首次使用时会下载约 2 MB 的模型</p>
+          </div>
+
+          {/* VAD Threshold */}
+          <div className="space-y-2">
+            <Label htmlFor="vadThreshold">
+              语音检测灵敏度阈值: {(settings?.vad_threshold ?? 0.03).toFixed(3)}
+            </Label>
+            <input
+              id="vadThreshold"
+              type="range"
+              min={0.005}
+              max={0.1}
+              step={0.005}
+              className="w-full"
+              value={settings?.vad_threshold ?? 0.03}
+              onChange={(e) => update({ vad_threshold: Number(e.target.value) })}
+              disabled={isRecording || (settings?.vad_engine ?? 'energy') !== 'energy'}
+            />
+            <p className="text-xs text-gray-500">数值越低越灵敏，环境嘈杂时请调高</p>
+          </div>
+
+          {/* Noise Suppression */}
+          <div className="space-y-2">
+            <div className="flex items-center gap-2">
+              <Checkbox
+                id="noiseSuppression"
+                checked={settings?.noise_suppression.enabled ?? false}
+                disabled={isRecording}
+                onCheckedChange={(checked) =>
+                  settings &&
+                  update({
+                    noise_suppression: { ...settings.noise_suppression, enabled: checked === true },
+                  })
+                }
+              />
+              <Label htmlFor="noiseSuppression">
+                降噪强度: {Math.round((settings?.noise_suppression.strength ?? 0.5) * 100)}%
+              </Label>
+            </div>
+            <input
+              id="noiseSuppressionStrength"
+              type="range"
+              min={0}
+              max={1}
+              step={0.05}
+              className="w-full"
+              value={settings?.noise_suppression.strength ?? 0.5}
+              onChange={(e) =>
+                settings &&
+                update({
+                  noise_suppression: { ...settings.noise_suppression, strength: Number(e.target.value) },
+                })
+              }
+              disabled={isRecording || !settings?.noise_suppression.enabled}
+            />
+            <p className="text-xs text-gray-500">过滤风扇、键盘等持续噪声，强度过高可能让语音失真</p>
+          </div>
+
+          {/* Automatic Gain */}
+          <div className="space-y-2">
+            <div className="flex items-center gap-2">
+              <Checkbox
+                id="autoGain"
+                checked={settings?.auto_gain ?? false}
+                disabled={isRecording}
+                onCheckedChange={(checked) => update({ auto_gain: checked === true })}
+              />
+              <Label htmlFor="autoGain">自动增益（自动调节麦克风音量，防止爆音）</Label>
+            </div>
+            <p className="text-xs text-gray-500">笔记本内置麦克风声音太小时建议开启</p>
+          </div>
+
+          {/* Pre-roll */}
+          <div className="space-y-2">
+            <Label htmlFor="preRoll">语音起始前保留: {settings?.pre_roll_ms ?? 500} ms</Label>
+            <input
+              id="preRoll"
+              type="range"
+              min={0}
+              max={2000}
+              step={100}
+              className="w-full"
+              value={settings?.pre_roll_ms ?? 500}
+              onChange={(e) => update({ pre_roll_ms: Number(e.target.value) })}
+              disabled={isRecording}
+            />
+            <p className="text-xs text-gray-500">检测到说话时一并发送之前的音频，避免开头的字被截掉</p>
+          </div>
+
+          {/* Injection Strategy */}
+          <div className="space-y-2">
+            <Label htmlFor="injectionStrategy">文本注入方式</Label>
+            <select
+              id="injectionStrategy"
+              className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+              value={settings?.injection_strategy ?? 'auto'}
+              onChange={(e) =>
+                update({
+                  injection_strategy:
+                    e.target.value === 'auto' ? null : (e.target.value as InjectionStrategy),
+                })
+              }
+            >
+              <option value="auto">自动选择</option>
+              <option value="keyboard">键盘输入</option>
+              <option value="clipboard">剪贴板粘贴</option>
+            </select>
+            <div className="flex items-center gap-2">
+              <Checkbox
+                id="autoInject"
+                checked={settings?.auto_inject ?? false}
+                disabled={isRecording}
+                onCheckedChange={(checked) => update({ auto_inject: checked === true })}
+              />
+              <Label htmlFor="autoInject">识别完成后自动输入到当前窗口</Label>
+            </div>
+          </div>
+
+          {/* Hotkeys */}
+          <div className="space-y-2">
+            <Label htmlFor="pushToTalk">按住说话快捷键</Label>
+            <input
+              id="pushToTalk"
+              type="text"
+              className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+              placeholder="Alt+Space"
+              value={settings?.hotkeys.push_to_talk ?? ''}
+              onChange={(e) =>
+                settings &&
+                update({ hotkeys: { ...settings.hotkeys, push_to_talk: e.target.value || null } })
+              }
+            />
+            <Label htmlFor="toggleHotkey">开始/停止快捷键</Label>
+            <input
+              id="toggleHotkey"
+              type="text"
+              className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+              placeholder="CommandOrControl+Shift+Space"
+              value={settings?.hotkeys.toggle ?? ''}
+              onChange={(e) =>
+                settings &&
+                update({ hotkeys: { ...settings.hotkeys, toggle: e.target.value || null } })
+              }
+            />
+            <p className="text-xs text-gray-500">留空以禁用该快捷键</p>
+          </div>
+
+          {/* History */}
+          <div className="space-y-2">
+            <div className="flex items-center gap-2">
+              <Checkbox
+                id="historyEnabled"
+                checked={settings?.history.enabled ?? true}
+                disabled={isRecording}
+                onCheckedChange={(checked) =>
+                  settings && update({ history: { ...settings.history, enabled: checked === true } })
+                }
+              />
+              <Label htmlFor="historyEnabled">保存转写历史记录</Label>
+            </div>
+            <Label htmlFor="retentionDays">历史记录保留天数</Label>
+            <input
+              id="retentionDays"
+              type="number"
+              min={1}
+              className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+              placeholder="永久保留"
+              value={settings?.history.retention_days ?? ''}
+              onChange={(e) =>
+                settings &&
+                update({
+                  history: {
+                    ...settings.history,
+                    retention_days: e.target.value ? Number(e.target.value) : null,
+                  },
+                })
+              }
+              disabled={isRecording}
+            />
+            <p className="text-xs text-gray-500">留空则永久保留</p>
           </div>
 
           {/* Control Buttons */}
@@ -120,7 +724,7 @@ export function Settings({ onStartRecording, onStopRecording, isRecording }: Set
             {!isRecording ? (
               <button
                 onClick={handleStartRecording}
-                disabled={loading || !apiKey}
+                disabled={loading || !settings || !canStart}
                 className="flex-1 bg-blue-600 text-white px-4 py-2 rounded-md hover:bg-blue-700 disabled:bg-gray-300 disabled:cursor-not-allowed transition-colors"
               >
                 {loading ? '启动中...' : '开始录音'}
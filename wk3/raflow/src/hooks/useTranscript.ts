@@ -1,19 +1,21 @@
 import { useEffect, useState } from 'react';
 import { listen } from '@tauri-apps/api/event';
-
-interface TranscriptEvent {
-  text: string;
-  is_final: boolean;
-}
+import { AudioLevelEvent, TranscriptEvent, WordTiming } from '../lib/transcript';
 
 export function useTranscript() {
   const [text, setText] = useState('');
   const [isFinal, setIsFinal] = useState(false);
+  const [languageCode, setLanguageCode] = useState<string | null>(null);
+  const [words, setWords] = useState<WordTiming[]>([]);
 
   useEffect(() => {
     const unlisten = listen<TranscriptEvent>('transcript-update', (event) => {
       setText(event.payload.text);
       setIsFinal(event.payload.is_final);
+      setWords(event.payload.words ?? []);
+      if (event.payload.language_code) {
+        setLanguageCode(event.payload.language_code);
+      }
     });
 
     return () => {
@@ -21,15 +23,15 @@ export function useTranscript() {
     };
   }, []);
 
-  return { text, isFinal };
+  return { text, isFinal, languageCode, words };
 }
 
 export function useAudioLevel() {
   const [level, setLevel] = useState(0);
 
   useEffect(() => {
-    const unlisten = listen<number>('audio-level', (event) => {
-      setLevel(event.payload);
+    const unlisten = listen<AudioLevelEvent>('audio-level', (event) => {
+      setLevel(event.payload.level);
     });
 
     return () => {
@@ -0,0 +1,42 @@
+import { invoke } from '@tauri-apps/api/core';
+import { InjectionStrategy } from './settings';
+
+export interface WindowInfo {
+  app_name: string;
+  title: string;
+  process_id: number;
+}
+
+export interface HistoryEntry {
+  id: number;
+  session_id: number;
+  created_at: string;
+  text: string;
+  language_code: string | null;
+  confidence: number | null;
+  window: WindowInfo | null;
+}
+
+export function listHistory(limit?: number, offset?: number): Promise<HistoryEntry[]> {
+  return invoke<HistoryEntry[]>('list_history', { limit, offset });
+}
+
+export function searchHistory(query: string, limit?: number): Promise<HistoryEntry[]> {
+  return invoke<HistoryEntry[]>('search_history', { query, limit });
+}
+
+export function copyHistoryEntry(id: number): Promise<void> {
+  return invoke('copy_history_entry', { id });
+}
+
+export function reinjectHistoryEntry(id: number, strategy?: InjectionStrategy): Promise<void> {
+  return invoke('reinject_history_entry', { id, strategy });
+}
+
+export function deleteHistoryEntry(id: number): Promise<void> {
+  return invoke('delete_history_entry', { id });
+}
+
+export function clearHistory(): Promise<void> {
+  return invoke('clear_history');
+}
@@ -0,0 +1,138 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export type InjectionStrategy = 'keyboard' | 'clipboard';
+
+export type VadEngine = 'energy' | 'silero' | 'adaptive';
+
+/** How multichannel input is mixed to mono, `single` counts from 0 */
+export type ChannelMode = 'mix' | 'auto' | { single: number };
+
+/** Binary encodings need a custom endpoint, opus needs the `opus` build feature */
+export type AudioEncoding = 'pcm_base64' | 'pcm_binary' | 'opus' | 'mulaw';
+
+export interface SessionOptions {
+  language_code: string | null;
+  model_id: string;
+  extra_params?: Record<string, string>;
+  audio_encoding?: AudioEncoding;
+}
+
+export interface HotkeySettings {
+  push_to_talk: string | null;
+  toggle: string | null;
+}
+
+export interface HistorySettings {
+  enabled: boolean;
+  retention_days: number | null;
+}
+
+export interface NoiseSuppressionSettings {
+  enabled: boolean;
+  strength: number;
+}
+
+export type WhisperModel = 'tiny' | 'tiny_en' | 'base' | 'base_en' | 'small';
+
+export interface OfflineSettings {
+  enabled: boolean;
+  model: WhisperModel;
+}
+
+export interface MetricsExporterSettings {
+  enabled: boolean;
+  port: number;
+}
+
+export interface AppSettings {
+  version: number;
+  device_name: string | null;
+  device_priority: string[];
+  channel_mode: ChannelMode;
+  buffer_size: number | null;
+  vad_engine: VadEngine;
+  vad_threshold: number;
+  pre_roll_ms: number;
+  noise_suppression: NoiseSuppressionSettings;
+  auto_gain: boolean;
+  injection_strategy: InjectionStrategy | null;
+  auto_inject: boolean;
+  transcription: SessionOptions;
+  endpoint: string | null;
+  hotkeys: HotkeySettings;
+  history: HistorySettings;
+  offline: OfflineSettings;
+  metrics_exporter: MetricsExporterSettings;
+  /** Time between two memory and CPU samples, applied on restart */
+  sample_interval_ms: number;
+}
+
+export const DEFAULT_MODEL = 'scribe_v2_realtime';
+
+export function getSettings(): Promise<AppSettings> {
+  return invoke<AppSettings>('get_settings');
+}
+
+export function updateSettings(settings: AppSettings): Promise<AppSettings> {
+  return invoke<AppSettings>('update_settings', { settings });
+}
+
+export interface ApiKeyStatus {
+  configured: boolean;
+  backend: string | null;
+}
+
+export function getApiKeyStatus(): Promise<ApiKeyStatus> {
+  return invoke<ApiKeyStatus>('get_api_key_status');
+}
+
+/** The key is kept in the OS credential store and never sent back to the UI. */
+export function setApiKey(apiKey: string): Promise<void> {
+  return invoke('set_api_key', { apiKey });
+}
+
+export function clearApiKey(): Promise<void> {
+  return invoke('clear_api_key');
+}
+
+// Keys used before settings moved to the backend store
+const LEGACY_KEYS = {
+  apiKey: 'elevenlabs_api_key',
+  language: 'transcription_language',
+  model: 'transcription_model',
+};
+
+/**
+ * Move settings that older versions kept in localStorage into the store.
+ * Returns the (possibly updated) settings.
+ */
+export async function migrateLegacySettings(settings: AppSettings): Promise<AppSettings> {
+  const apiKey = localStorage.getItem(LEGACY_KEYS.apiKey);
+  const language = localStorage.getItem(LEGACY_KEYS.language);
+  const model = localStorage.getItem(LEGACY_KEYS.model);
+
+  if (apiKey === null && language === null && model === null) {
+    return settings;
+  }
+
+  if (apiKey) {
+    const status = await getApiKeyStatus();
+    if (!status.configured) {
+      await setApiKey(apiKey);
+    }
+  }
+
+  const migrated: AppSettings = {
+    ...settings,
+    transcription: {
+      ...settings.transcription,
+      language_code:
+        language === null ? settings.transcription.language_code : language === 'auto' ? null : language,
+      model_id: model || settings.transcription.model_id,
+    },
+  };
+
+  const saved = await updateSettings(migrated);
+  Object.values(LEGACY_KEYS).forEach((key) => localStorage.removeItem(key));
+  return saved;
+}
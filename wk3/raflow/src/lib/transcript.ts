@@ -0,0 +1,29 @@
+export type WordKind = 'word' | 'spacing' | 'audio_event' | 'unknown';
+
+/** Timing of a word in a committed transcript, in seconds from session start */
+export interface WordTiming {
+  text: string;
+  start: number;
+  end: number;
+  type: WordKind;
+  speaker: string | null;
+  logprob: number | null;
+}
+
+/** Payload of the `audio-level` event, levels are 0.0 - 1.0 */
+export interface AudioLevelEvent {
+  /** Level sent to the transcription service */
+  level: number;
+  /** Level as captured, before automatic gain control */
+  input_level: number;
+}
+
+/** Payload of the `transcript-update` event */
+export interface TranscriptEvent {
+  text: string;
+  is_final: boolean;
+  language_code?: string | null;
+  confidence?: number | null;
+  /** Only present on committed transcripts that carry timestamps */
+  words?: WordTiming[];
+}
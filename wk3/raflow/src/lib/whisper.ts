@@ -0,0 +1,34 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import { WhisperModel } from './settings';
+
+export interface ModelStatus {
+  model: WhisperModel;
+  id: string;
+  size_mb: number;
+  english_only: boolean;
+  downloaded: boolean;
+}
+
+export interface DownloadProgress {
+  model: WhisperModel;
+  file: string;
+  downloaded: number;
+  total: number | null;
+}
+
+export function listWhisperModels(): Promise<ModelStatus[]> {
+  return invoke<ModelStatus[]>('list_whisper_models');
+}
+
+export function downloadWhisperModel(model: WhisperModel): Promise<void> {
+  return invoke('download_whisper_model', { model });
+}
+
+export function deleteWhisperModel(model: WhisperModel): Promise<void> {
+  return invoke('delete_whisper_model', { model });
+}
+
+export function onDownloadProgress(handler: (progress: DownloadProgress) => void): Promise<UnlistenFn> {
+  return listen<DownloadProgress>('model-download-progress', (event) => handler(event.payload));
+}